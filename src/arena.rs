@@ -0,0 +1,104 @@
+//! The compact storage of the words extracted from a file, every word is appended to a
+//! single buffer and only its end offset is recorded, in place of a `Vec` of 24 bytes and
+//! an allocation per word. The millions of words of a file take several times less memory
+//! and the words compared by the validations are next to each other in memory.
+//!
+//! The evaluations take any `Words`, the generated words stay in their `Vec`s and the
+//! extracted ones are stored in a `WordArena`.
+
+use std::cmp::Ordering;
+
+/// The sorted words of a dataset, the keys the workloads validate and look up.
+pub trait Words: Sync {
+    fn len(&self) -> usize;
+
+    fn word(&self, index: usize) -> &[u8];
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Searches the sorted words for the key, like `slice::binary_search`.
+    fn binary_search(&self, key: &[u8]) -> Result<usize, usize> {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.word(middle).cmp(key) {
+                Ordering::Less => low = middle + 1,
+                Ordering::Equal => return Ok(middle),
+                Ordering::Greater => high = middle,
+            }
+        }
+        Err(low)
+    }
+}
+
+impl Words for [Vec<u8>] {
+    fn len(&self) -> usize {
+        <[Vec<u8>]>::len(self)
+    }
+
+    fn word(&self, index: usize) -> &[u8] {
+        &self[index]
+    }
+}
+
+impl Words for Vec<Vec<u8>> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn word(&self, index: usize) -> &[u8] {
+        &self[index]
+    }
+}
+
+/// The words appended to a single buffer, delimited by their end offsets.
+#[derive(Debug, Default, Clone)]
+pub struct WordArena {
+    bytes: Vec<u8>,
+    ends: Vec<usize>,
+}
+
+impl WordArena {
+    pub fn with_capacity(words: usize) -> WordArena {
+        WordArena { bytes: Vec::new(), ends: Vec::with_capacity(words) }
+    }
+
+    pub fn push(&mut self, word: &[u8]) {
+        self.bytes.extend_from_slice(word);
+        self.ends.push(self.bytes.len());
+    }
+
+    /// Appends the words of the other arena, they must sort after the ones of this one.
+    pub fn append(&mut self, other: &WordArena) {
+        let shift = self.bytes.len();
+        self.bytes.extend_from_slice(&other.bytes);
+        self.ends.extend(other.ends.iter().map(|end| end + shift));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        (0..self.ends.len()).map(move |index| self.word(index))
+    }
+}
+
+impl Words for WordArena {
+    fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    fn word(&self, index: usize) -> &[u8] {
+        let start = index.checked_sub(1).map_or(0, |previous| self.ends[previous]);
+        &self.bytes[start..self.ends[index]]
+    }
+}
+
+impl<W: AsRef<[u8]>> FromIterator<W> for WordArena {
+    fn from_iter<I: IntoIterator<Item = W>>(words: I) -> WordArena {
+        let mut arena = WordArena::default();
+        for word in words {
+            arena.push(word.as_ref());
+        }
+        arena
+    }
+}
@@ -0,0 +1,138 @@
+//! The parsing of the human-friendly values of the command line, the sizes with a
+//! unit like `4KiB`, the durations like `500us` and the lists of index levels with
+//! ranges like `0..=3`.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The units of the sizes, lowercased. The single letters are binary units like
+/// the `-h` output of the coreutils, `4K` is 4096 bytes.
+const UNITS: &[(&str, u64)] = &[
+    ("", 1),
+    ("b", 1),
+    ("k", 1 << 10),
+    ("kib", 1 << 10),
+    ("kb", 1_000),
+    ("m", 1 << 20),
+    ("mib", 1 << 20),
+    ("mb", 1_000_000),
+    ("g", 1 << 30),
+    ("gib", 1 << 30),
+    ("gb", 1_000_000_000),
+    ("t", 1 << 40),
+    ("tib", 1 << 40),
+    ("tb", 1_000_000_000_000),
+];
+
+/// Parses a number of bytes like `4096`, `4KiB`, `1.5MiB` or `10GB`.
+pub fn parse_size<T: TryFrom<u64>>(s: &str) -> Result<T, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let unit = unit.trim().to_ascii_lowercase();
+    let multiplier = UNITS
+        .iter()
+        .find(|(name, _)| *name == unit)
+        .map(|(_, multiplier)| *multiplier)
+        .ok_or_else(|| {
+            format!("`{}` has an unknown unit, expected B, KiB, MiB, GiB, TiB, KB, MB, GB or TB", s)
+        })?;
+
+    let bytes = match number.parse::<u64>() {
+        Ok(number) => number.checked_mul(multiplier),
+        Err(_) => {
+            let number: f64 =
+                number.parse().map_err(|_| format!("`{}` is not a size, e.g. 4096 or 4KiB", s))?;
+            let bytes = number * multiplier as f64;
+            (bytes.fract() == 0.0 && bytes < u64::MAX as f64).then(|| bytes as u64)
+        }
+    };
+    let bytes = bytes.ok_or_else(|| format!("`{}` is not a whole number of bytes", s))?;
+    T::try_from(bytes).map_err(|_| format!("{} bytes is out of the range of this argument", bytes))
+}
+
+/// Parses a bandwidth in bytes per second like `100MBps`, `100MB/s` or `1GiB/s`.
+pub fn parse_bandwidth(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let size = s
+        .strip_suffix("ps")
+        .or_else(|| s.strip_suffix("/s"))
+        .ok_or_else(|| format!("`{}` is not a bandwidth, e.g. 100MBps or 1GiB/s", s))?;
+    match parse_size(size)? {
+        0 => Err(format!("`{}` is not a positive bandwidth", s)),
+        bandwidth => Ok(bandwidth),
+    }
+}
+
+/// Parses a duration like `500us`, `1.5ms`, `2s`, `30m` or `8h`.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 =
+        number.parse().map_err(|_| format!("`{}` is not a duration like `500us`", s))?;
+    let seconds = match unit.trim() {
+        "ns" => number / 1e9,
+        "us" | "µs" => number / 1e6,
+        "ms" => number / 1e3,
+        "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        otherwise => {
+            return Err(format!("unknown unit `{}`, expected ns, us, ms, s, m or h", otherwise))
+        }
+    };
+    if seconds <= 0.0 || !seconds.is_finite() {
+        return Err(format!("`{}` is not a positive duration", s));
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// A comma-separated list of index levels and ranges of them, e.g. `0..=3` or `0,2..4`,
+/// `recommended` adds the levels recommended for the number of entries of the dataset.
+#[derive(Debug, Clone)]
+pub struct IndexLevels {
+    pub levels: Vec<u8>,
+    pub recommended: bool,
+}
+
+impl FromStr for IndexLevels {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<IndexLevels, String> {
+        let level = |s: &str| {
+            s.trim()
+                .parse::<u8>()
+                .map_err(|_| format!("`{}` is not an index level between 0 and 255", s.trim()))
+        };
+
+        let mut levels = Vec::new();
+        let mut recommended = false;
+        for part in s.split(',') {
+            if part.trim() == "recommended" {
+                recommended = true;
+                continue;
+            }
+            let range = if let Some((start, end)) = part.split_once("..=") {
+                level(start)?..=level(end)?
+            } else if let Some((start, end)) = part.split_once("..") {
+                let (start, end) = (level(start)?, level(end)?);
+                match end.checked_sub(1) {
+                    Some(end) => start..=end,
+                    None => return Err(format!("`{}` is an empty range", part.trim())),
+                }
+            } else {
+                let level = level(part)?;
+                level..=level
+            };
+            if range.is_empty() {
+                return Err(format!("`{}` is an empty range", part.trim()));
+            }
+            for level in range {
+                if !levels.contains(&level) {
+                    levels.push(level);
+                }
+            }
+        }
+        Ok(IndexLevels { levels, recommended })
+    }
+}
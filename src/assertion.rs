@@ -0,0 +1,217 @@
+//! The `--fail-if` assertions, comparisons of the metrics of the configurations
+//! of a sweep that set the exit code of the process when they hold, to gate a
+//! script on the outcome of a benchmark.
+//!
+//! An assertion compares two products of numbers and metrics of configurations,
+//! e.g. `jump_time(default_params) > 1.2 * jump_time(best)`. The configurations are
+//! `best` and `worst`, according to the sort keys, `default_params` or a name like
+//! `None.0.8192.16`. The durations are in milliseconds and the file size in bytes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{significance, Outcome, Parameters, Results};
+
+/// The exit code of the process when an assertion holds, errors exit with 1.
+pub const FAILED_ASSERTION_EXIT_CODE: i32 = 2;
+
+#[derive(Debug, Clone)]
+pub struct Assertion {
+    source: String,
+    left: Vec<Factor>,
+    operator: Operator,
+    right: Vec<Factor>,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Operator {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+#[derive(Debug, Clone)]
+enum Factor {
+    Number(f64),
+    Metric(Metric, Configuration),
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Metric {
+    IterTime,
+    KeysIterTime,
+    JumpTime,
+    ExactGetTime,
+    WriteTime,
+    FileSize,
+    Energy,
+}
+
+#[derive(Debug, Clone)]
+enum Configuration {
+    Best,
+    Worst,
+    Default,
+    Named(String),
+}
+
+impl Assertion {
+    /// Returns whether the assertion holds on the already sorted results,
+    /// or why it can't be evaluated.
+    pub fn holds(&self, results: &[(Parameters, Outcome)]) -> Result<bool, String> {
+        let left = product(&self.left, results)?;
+        let right = product(&self.right, results)?;
+        Ok(match self.operator {
+            Operator::Greater => left > right,
+            Operator::GreaterOrEqual => left >= right,
+            Operator::Less => left < right,
+            Operator::LessOrEqual => left <= right,
+        })
+    }
+}
+
+fn product(factors: &[Factor], results: &[(Parameters, Outcome)]) -> Result<f64, String> {
+    let mut product = 1.0;
+    for factor in factors {
+        product *= match factor {
+            Factor::Number(number) => *number,
+            Factor::Metric(metric, configuration) => {
+                let found = configuration.find(results)?;
+                metric.value(found).ok_or_else(|| format!("{:?} was not measured", metric))?
+            }
+        };
+    }
+    Ok(product)
+}
+
+impl Metric {
+    fn value(self, results: &Results) -> Option<f64> {
+        let millis = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+        match self {
+            Metric::IterTime => results.iter_time.map(millis),
+            Metric::KeysIterTime => results.keys_iter_time.map(millis),
+            Metric::JumpTime => results.jump_time.map(millis),
+            Metric::ExactGetTime => results.exact_get_time.map(millis),
+            Metric::WriteTime => results.write_time.map(millis),
+            Metric::FileSize => Some(results.file_size as f64),
+            Metric::Energy => results.energy,
+        }
+    }
+}
+
+impl Configuration {
+    fn find<'a>(&self, results: &'a [(Parameters, Outcome)]) -> Result<&'a Results, String> {
+        let mut successes =
+            results.iter().filter_map(|(p, outcome)| Some((p, outcome.as_ref().ok()?)));
+        let found = match self {
+            Configuration::Best => successes.next(),
+            Configuration::Worst => successes.last(),
+            Configuration::Default => {
+                let baseline = significance::baseline_parameters();
+                successes.find(|(params, _)| significance::is_same_configuration(params, &baseline))
+            }
+            Configuration::Named(name) => successes.find(|(params, _)| {
+                let full_name = params.file_name();
+                full_name == *name || full_name.trim_end_matches(".grd") == name.as_str()
+            }),
+        };
+        found
+            .map(|(_, results)| results)
+            .ok_or_else(|| format!("no successful {} configuration", self))
+    }
+}
+
+impl fmt::Display for Configuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Configuration::Best => f.write_str("best"),
+            Configuration::Worst => f.write_str("worst"),
+            Configuration::Default => f.write_str("default_params"),
+            Configuration::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for Assertion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Assertion, String> {
+        // The two characters operators first, `>` is a prefix of `>=`.
+        let operators = [
+            (">=", Operator::GreaterOrEqual),
+            ("<=", Operator::LessOrEqual),
+            (">", Operator::Greater),
+            ("<", Operator::Less),
+        ];
+        let (position, symbol, operator) = operators
+            .iter()
+            .find_map(|&(symbol, operator)| Some((s.find(symbol)?, symbol, operator)))
+            .ok_or_else(|| format!("`{}` has no comparison operator", s))?;
+        let (left, right) = (&s[..position], &s[position + symbol.len()..]);
+        Ok(Assertion {
+            source: s.to_string(),
+            left: parse_product(left)?,
+            operator,
+            right: parse_product(right)?,
+        })
+    }
+}
+
+fn parse_product(s: &str) -> Result<Vec<Factor>, String> {
+    s.split('*').map(|factor| parse_factor(factor.trim())).collect()
+}
+
+fn parse_factor(s: &str) -> Result<Factor, String> {
+    if let Ok(number) = s.parse::<f64>() {
+        return Ok(Factor::Number(number));
+    }
+    let (metric, configuration) = s
+        .strip_suffix(')')
+        .and_then(|s| s.split_once('('))
+        .ok_or_else(|| format!("`{}` is neither a number nor a metric(configuration)", s))?;
+    let metric = match metric.trim() {
+        "iter_time" => Metric::IterTime,
+        "keys_iter_time" => Metric::KeysIterTime,
+        "jump_time" => Metric::JumpTime,
+        "exact_get_time" => Metric::ExactGetTime,
+        "write_time" => Metric::WriteTime,
+        "file_size" => Metric::FileSize,
+        "energy" => Metric::Energy,
+        otherwise => return Err(format!("unknown metric `{}`", otherwise)),
+    };
+    let configuration = match configuration.trim() {
+        "best" => Configuration::Best,
+        "worst" => Configuration::Worst,
+        "default_params" => Configuration::Default,
+        "" => return Err(format!("`{}` has no configuration", s)),
+        name => Configuration::Named(name.to_string()),
+    };
+    Ok(Factor::Metric(metric, configuration))
+}
+
+/// Evaluates the assertions and prints the ones that hold or can't be evaluated,
+/// returns whether the process must exit with `FAILED_ASSERTION_EXIT_CODE`.
+pub fn check_assertions(assertions: &[Assertion], results: &[(Parameters, Outcome)]) -> bool {
+    let mut failed = false;
+    for assertion in assertions {
+        match assertion.holds(results) {
+            Ok(true) => {
+                println!("failing because `{}` holds", assertion);
+                failed = true;
+            }
+            Ok(false) => (),
+            Err(error) => {
+                println!("failing because `{}` can't be evaluated: {}", assertion, error);
+                failed = true;
+            }
+        }
+    }
+    failed
+}
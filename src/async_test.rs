@@ -0,0 +1,95 @@
+use std::fs::File;
+use std::io::Cursor;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use grenad::Reader;
+use memmap2::Mmap;
+
+use crate::manifest::{self, Manifest};
+use crate::stats::percentile;
+
+/// A memory-mapped grenad file that can be cheaply shared between tasks.
+#[derive(Clone)]
+struct SharedMmap(Arc<Mmap>);
+
+impl AsRef<[u8]> for SharedMmap {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct AsyncResults {
+    pub lookups: u64,
+    pub elapsed: Duration,
+    pub p50_latency: Duration,
+    pub p99_latency: Duration,
+    pub max_latency: Duration,
+}
+
+/// Executes the random lookups from a tokio runtime, every lookup is
+/// executed in the blocking thread pool and `concurrency` lookups are in flight.
+///
+/// The measured latencies include the time spent waiting to be scheduled,
+/// which is what a web service calling into grenad would experience.
+pub fn test_cursor_async(
+    filepath: &Path,
+    words: &[Vec<u8>],
+    lookups: &[usize],
+    worker_threads: NonZeroUsize,
+    concurrency: NonZeroUsize,
+) -> anyhow::Result<AsyncResults> {
+    let max_bitmap_len = Manifest::max_bitmap_len_of(filepath)?;
+    let file = File::open(filepath)?;
+    let map = SharedMmap(Arc::new(unsafe { Mmap::map(&file)? }));
+
+    let lookups: Vec<Vec<u8>> = lookups.iter().map(|&i| words[i].clone()).collect();
+    let concurrency = concurrency.get();
+    let chunk_size = (lookups.len() + concurrency - 1) / concurrency;
+
+    let runtime =
+        tokio::runtime::Builder::new_multi_thread().worker_threads(worker_threads.get()).build()?;
+
+    runtime.block_on(async {
+        let before = Instant::now();
+        let mut handles = Vec::with_capacity(concurrency);
+        for chunk in lookups.chunks(chunk_size.max(1)) {
+            let chunk = chunk.to_vec();
+            let map = map.clone();
+            handles.push(tokio::spawn(async move {
+                let mut cursor = Reader::new(Cursor::new(map))?.into_cursor()?;
+                let mut latencies = Vec::with_capacity(chunk.len());
+                for word in chunk {
+                    let before_lookup = Instant::now();
+                    cursor = tokio::task::spawn_blocking(move || -> anyhow::Result<_> {
+                        let (k, v) = cursor.move_on_key_greater_than_or_equal_to(&word)?.unwrap();
+                        assert_eq!(k, &word[..]);
+                        manifest::check_bitmap(v, max_bitmap_len);
+                        Ok(cursor)
+                    })
+                    .await??;
+                    latencies.push(before_lookup.elapsed());
+                }
+                Ok::<_, anyhow::Error>(latencies)
+            }));
+        }
+
+        let mut latencies = Vec::with_capacity(lookups.len());
+        for handle in handles {
+            latencies.extend(handle.await??);
+        }
+        let elapsed = before.elapsed();
+
+        latencies.sort_unstable();
+        Ok(AsyncResults {
+            lookups: latencies.len() as u64,
+            elapsed,
+            p50_latency: percentile(&latencies, 50.0),
+            p99_latency: percentile(&latencies, 99.0),
+            max_latency: latencies.last().copied().unwrap_or_default(),
+        })
+    })
+}
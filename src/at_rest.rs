@@ -0,0 +1,54 @@
+//! The study of where the compression should live, the blocks compressed by grenad
+//! against the uncompressed file compressed at rest, by the filesystem of the folder
+//! or through the zstd seekable format, with the sizes and the read timings of both.
+
+use std::time::Duration;
+
+use crate::platform::FilesystemCompression;
+use crate::Outcome;
+
+/// A way of compressing the dataset and the outcome of its evaluation.
+pub struct StudyRow {
+    pub label: String,
+    pub outcome: Outcome,
+    /// The space allocated on the disk, `None` if it is unknown.
+    pub allocated_size: Option<u64>,
+}
+
+pub fn print_compression_study(filesystem: Option<&FilesystemCompression>, rows: &[StudyRow]) {
+    match filesystem {
+        Some(FilesystemCompression { filesystem, algorithm: Some(algorithm) }) => {
+            println!("the {} folder compresses the files with {}", filesystem, algorithm)
+        }
+        Some(FilesystemCompression { filesystem, algorithm: None }) => {
+            println!("the {} folder stores the files uncompressed", filesystem)
+        }
+        None => println!("the compression of the folder is unknown"),
+    }
+
+    println!(
+        "{:>32} {:>14} {:>14} {:>12} {:>12}",
+        "compression", "file size", "on disk", "iter", "jump"
+    );
+    for row in rows {
+        match &row.outcome {
+            Ok(results) => println!(
+                "{:>32} {:>14} {:>14} {:>12} {:>12}",
+                row.label,
+                results.file_size,
+                row.allocated_size.map_or_else(|| "unknown".to_string(), |size| size.to_string()),
+                format_time(results.iter_time),
+                format_time(results.jump_time),
+            ),
+            Err(error) => println!("{:>32} failed: {}", row.label, error),
+        }
+    }
+    println!();
+}
+
+fn format_time(time: Option<Duration>) -> String {
+    match time {
+        Some(time) => format!("{:.02?}", time),
+        None => "skipped".to_string(),
+    }
+}
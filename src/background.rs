@@ -0,0 +1,150 @@
+//! The background load of the evaluations, `--background-load 4` runs four threads
+//! competing for the CPU while every configuration is evaluated. The timings of an idle
+//! machine hide that the configurations decompressing a lot degrade the most when the
+//! search and the indexing share the cores.
+//!
+//! The `spin` workload hashes a buffer that fits in the cache of a core again and again,
+//! the `iterate` workload iterates over the evaluated file again and again and competes
+//! for the decompression, the page cache and the memory bandwidth too. The load runs
+//! during the repetitions of a configuration, the configurations evaluated in parallel
+//! each have their own.
+
+use std::fmt;
+use std::fs::File;
+use std::io::Cursor;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use anyhow::Context;
+use grenad::Reader;
+use xxhash_rust::xxh3::xxh3_64_with_seed;
+
+pub const POSSIBLE_WORKLOADS: &[&str] = &["spin", "iterate"];
+
+/// The size of the buffer hashed by the `spin` workload, it fits in the L2 cache.
+const SPIN_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BackgroundWorkload {
+    /// Hashes a small buffer, only the cores are busy.
+    Spin,
+    /// Iterates over the evaluated file, like a competing reader.
+    Iterate,
+}
+
+/// The number of threads of the load and their workload.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BackgroundLoad {
+    pub threads: NonZeroUsize,
+    pub workload: BackgroundWorkload,
+}
+
+impl BackgroundLoad {
+    /// Starts the threads of the load, the `iterate` ones read the grenad file at this path.
+    pub fn start(self, path: &Path) -> RunningLoad {
+        let stop = Arc::new(AtomicBool::new(false));
+        let threads = (0..self.threads.get())
+            .map(|_| {
+                let stop = stop.clone();
+                let path = path.to_path_buf();
+                thread::spawn(move || match self.workload {
+                    BackgroundWorkload::Spin => {
+                        spin(&stop);
+                        Ok(())
+                    }
+                    BackgroundWorkload::Iterate => iterate(&path, &stop),
+                })
+            })
+            .collect();
+        RunningLoad { stop, threads }
+    }
+}
+
+/// The threads of a load, they are stopped when it is stopped or dropped.
+pub struct RunningLoad {
+    stop: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<anyhow::Result<()>>>,
+}
+
+impl RunningLoad {
+    /// Stops the threads of the load and waits for them, fails if one of them failed,
+    /// the timings measured while it wasn't running are not under load.
+    pub fn stop(mut self) -> anyhow::Result<()> {
+        self.stop.store(true, Ordering::Relaxed);
+        for thread in self.threads.drain(..) {
+            match thread.join() {
+                Ok(result) => result.context("the background load failed")?,
+                Err(_) => anyhow::bail!("the background load panicked"),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RunningLoad {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn spin(stop: &AtomicBool) {
+    let mut buffer: Vec<u8> = (0..SPIN_BUFFER_SIZE).map(|i| i as u8).collect();
+    let mut round = 0;
+    while !stop.load(Ordering::Relaxed) {
+        // The digest is written back for the hashing not to be optimized away.
+        let digest = xxh3_64_with_seed(&buffer, round);
+        buffer[round as usize % SPIN_BUFFER_SIZE] = digest as u8;
+        round += 1;
+    }
+}
+
+fn iterate(path: &Path, stop: &AtomicBool) -> anyhow::Result<()> {
+    let file = File::open(path).with_context(|| format!("while opening {}", path.display()))?;
+    let map = unsafe { memmap2::Mmap::map(&file)? };
+    while !stop.load(Ordering::Relaxed) {
+        let mut cursor = Reader::new(Cursor::new(&map[..]))?.into_cursor()?;
+        while cursor.move_on_next()?.is_some() {
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl FromStr for BackgroundWorkload {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<BackgroundWorkload, String> {
+        match s {
+            "spin" => Ok(BackgroundWorkload::Spin),
+            "iterate" => Ok(BackgroundWorkload::Iterate),
+            otherwise => Err(format!(
+                "invalid background workload {:?}, expected spin or iterate",
+                otherwise
+            )),
+        }
+    }
+}
+
+impl fmt::Display for BackgroundWorkload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BackgroundWorkload::Spin => f.write_str("spin"),
+            BackgroundWorkload::Iterate => f.write_str("iterate"),
+        }
+    }
+}
+
+impl fmt::Display for BackgroundLoad {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} threads", self.threads, self.workload)
+    }
+}
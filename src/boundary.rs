@@ -0,0 +1,219 @@
+//! The checks of the seeks at the boundaries of the files, before the first key, on
+//! and after the last one, and of the empty files, with every configuration of a grid.
+//!
+//! The edges are where the bugs of the index levels hide and the random jumps never
+//! exercise them, every lookup of a sweep targets a key of the file. The files are
+//! written in memory, their layout is the one of the files written on disk.
+//!
+//! The `sanity` profile checks the degenerate datasets too, the empty one, one and
+//! two entries and the entries around the capacity of a block, every entry is read
+//! back by the iteration, the jumps and the exact gets, nothing is timed.
+
+use std::fmt;
+use std::io::Cursor;
+use std::str::FromStr;
+
+use grenad::{Reader, ReaderCursor, WriterBuilder};
+
+use crate::{capture_failure, Parameters};
+
+pub const POSSIBLE_PROFILES: &[&str] = &["boundaries", "sanity"];
+
+/// The approximate overhead of an entry in a block, the lengths of its key and value.
+const ENTRY_OVERHEAD: usize = 2;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Profile {
+    /// The boundaries of the generated dataset and of the empty file.
+    Boundaries,
+    /// The boundaries and every entry of the degenerate datasets.
+    Sanity,
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Profile, String> {
+        match s {
+            "boundaries" => Ok(Profile::Boundaries),
+            "sanity" => Ok(Profile::Sanity),
+            otherwise => {
+                Err(format!("invalid profile {:?}, expected boundaries or sanity", otherwise))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Profile::Boundaries => f.write_str("boundaries"),
+            Profile::Sanity => f.write_str("sanity"),
+        }
+    }
+}
+
+/// Writes the words with their index as value, and an empty file, with the parameters
+/// and checks the seeks at their boundaries, returns why they failed.
+pub fn check(params: &Parameters, words: &[Vec<u8>]) -> Result<(), String> {
+    capture_failure(|| {
+        let mut cursor = write_in_memory(params, words)?;
+        check_cursor(&mut cursor, words)?;
+        let mut cursor = write_in_memory(params, &[])?;
+        check_cursor(&mut cursor, &[]).map_err(|e| e.context("with the empty file"))
+    })
+}
+
+/// The number of entries of the degenerate datasets of the parameters, the block
+/// capacity is estimated from the average length of the words.
+pub fn sanity_entry_counts(params: &Parameters, words: &[Vec<u8>]) -> Vec<usize> {
+    let average_len = match words.len() {
+        0 => 0,
+        len => words.iter().map(Vec::len).sum::<usize>() / len,
+    };
+    let per_block = (params.block_size / (average_len + 4 + ENTRY_OVERHEAD)).max(1);
+    let mut counts = vec![0, 1, 2, per_block - 1, per_block, per_block + 1, per_block * 2 + 1];
+    counts.sort_unstable();
+    counts.dedup();
+    counts
+}
+
+/// Writes the first words of every degenerate dataset with the parameters and reads
+/// every entry back, checks the boundaries too, returns why it failed.
+pub fn check_sanity(params: &Parameters, words: &[Vec<u8>]) -> Result<(), String> {
+    capture_failure(|| {
+        for count in sanity_entry_counts(params, words) {
+            let words = &words[..count.min(words.len())];
+            let mut cursor = write_in_memory(params, words)?;
+            check_entries(&mut cursor, words)
+                .and_then(|()| check_cursor(&mut cursor, words))
+                .map_err(|e| e.context(format!("with {} entries", words.len())))?;
+        }
+        Ok(())
+    })
+}
+
+/// Reads every entry back by iterating, jumping on and getting every key.
+fn check_entries(
+    cursor: &mut ReaderCursor<Cursor<Vec<u8>>>,
+    words: &[Vec<u8>],
+) -> anyhow::Result<()> {
+    for index in 0..=words.len() {
+        let entry = found(cursor.move_on_next()?)?;
+        ensure("iterating", entry, &expected(words, index))?;
+    }
+    for (index, word) in words.iter().enumerate() {
+        let entry = found(cursor.move_on_key_greater_than_or_equal_to(word)?)?;
+        ensure("jumping on a key", entry, &expected(words, index))?;
+        let entry = found(cursor.move_on_key_equal_to(word)?)?;
+        ensure("getting a key", entry, &expected(words, index))?;
+    }
+    Ok(())
+}
+
+fn write_in_memory(
+    params: &Parameters,
+    words: &[Vec<u8>],
+) -> anyhow::Result<ReaderCursor<Cursor<Vec<u8>>>> {
+    let mut writer = WriterBuilder::new()
+        .compression_type(params.compression)
+        .index_levels(params.index_levels)
+        .block_size(params.block_size)
+        .index_key_interval(params.index_key_interval)
+        .build(Vec::new());
+    for (i, word) in (0u32..).zip(words) {
+        writer.insert(word, i.to_be_bytes())?;
+    }
+    let bytes = writer.into_inner()?;
+    Ok(Reader::new(Cursor::new(bytes))?.into_cursor()?)
+}
+
+/// The entry the cursor returned, the key and the index stored as value.
+type Found = Option<(Vec<u8>, u32)>;
+
+fn found(entry: Option<(&[u8], &[u8])>) -> anyhow::Result<Found> {
+    match entry {
+        Some((key, value)) => {
+            let index = value.try_into().map(u32::from_be_bytes).map_err(|_| {
+                anyhow::anyhow!("the value of {:?} is {} bytes long", key, value.len())
+            })?;
+            Ok(Some((key.to_vec(), index)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The entry of the word at the index, the one the cursor must return.
+fn expected(words: &[Vec<u8>], index: usize) -> Found {
+    words.get(index).map(|word| (word.clone(), index as u32))
+}
+
+fn ensure(operation: &str, found: Found, expected: &Found) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        &found == expected,
+        "{} returned {:?} instead of {:?}",
+        operation,
+        found,
+        expected
+    );
+    Ok(())
+}
+
+fn check_cursor(
+    cursor: &mut ReaderCursor<Cursor<Vec<u8>>>,
+    words: &[Vec<u8>],
+) -> anyhow::Result<()> {
+    let first = expected(words, 0);
+    let last = words.len().checked_sub(1).and_then(|index| expected(words, index));
+
+    anyhow::ensure!(cursor.len() == words.len() as u64, "the file has {} entries", cursor.len());
+    ensure("moving on the first entry", found(cursor.move_on_first()?)?, &first)?;
+    ensure("moving on the last entry", found(cursor.move_on_last()?)?, &last)?;
+
+    // The empty key is before every other key.
+    let entry = found(cursor.move_on_key_greater_than_or_equal_to(b"")?)?;
+    ensure("seeking the empty key", entry, &first)?;
+    if let Some(word) = words.first().filter(|word| !word.is_empty()) {
+        // A strict prefix of the first key is before it and is not in the file.
+        let before = &word[..word.len() - 1];
+        let entry = found(cursor.move_on_key_greater_than_or_equal_to(before)?)?;
+        ensure("seeking before the first key", entry, &first)?;
+        let entry = found(cursor.move_on_key_equal_to(before)?)?;
+        ensure("getting a key before the first one", entry, &None)?;
+    }
+
+    if let Some(word) = words.last() {
+        let entry = found(cursor.move_on_key_greater_than_or_equal_to(word)?)?;
+        ensure("seeking the last key", entry, &last)?;
+        let entry = found(cursor.move_on_key_equal_to(word)?)?;
+        ensure("getting the last key", entry, &last)?;
+
+        // The last key followed by a zero byte is right after it.
+        let mut after = word.clone();
+        after.push(0);
+        let entry = found(cursor.move_on_key_greater_than_or_equal_to(&after)?)?;
+        ensure("seeking after the last key", entry, &None)?;
+        let entry = found(cursor.move_on_key_equal_to(&after)?)?;
+        ensure("getting a key after the last one", entry, &None)?;
+    }
+
+    // A cursor that went past the end must still seek backward.
+    let entry = found(cursor.move_on_key_greater_than_or_equal_to(b"")?)?;
+    ensure("seeking the empty key after the end", entry, &first)?;
+    ensure("moving after the first entry", found(cursor.move_on_next()?)?, &expected(words, 1))?;
+    Ok(())
+}
+
+pub fn print_boundary_report(results: &[(Parameters, Result<(), String>)]) {
+    let failed: Vec<_> = results.iter().filter(|(_, result)| result.is_err()).collect();
+    for (params, result) in &failed {
+        if let Err(error) = result {
+            println!("{}: {}", params.file_name(), error);
+        }
+    }
+    println!(
+        "{} configurations passed the checks, {} failed",
+        results.len() - failed.len(),
+        failed.len()
+    );
+}
@@ -0,0 +1,110 @@
+//! The time budget of a sweep, `--time-budget 2h` only evaluates the configurations of the
+//! grid that fit in it. The pilot, a few configurations evaluated first, estimates the cost
+//! of a configuration and how many of the others can be evaluated in the remaining time.
+//!
+//! The configurations are ordered to spread across the dimensions of the grid, every next
+//! one is the farthest from the ones before it, the grid is covered coarsely before it is
+//! covered finely and the configurations cut by the budget are close to evaluated ones.
+//! The baseline is always evaluated, the comparisons and the sentinel need it.
+
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use crate::{sampling, significance, Parameters};
+
+/// The reason of the configurations skipped by the budget.
+pub const BEYOND_BUDGET: &str = "beyond the time budget";
+
+/// The position of every configuration in the grid, every dimension is scaled to `[0, 1]`
+/// by the rank of its values, whatever their number the dimensions weigh the same.
+fn grid_coordinates(parameters: &[Parameters]) -> Vec<[f64; 4]> {
+    let (levels, counts) = sampling::grid_levels(parameters);
+    levels
+        .into_iter()
+        .map(|levels| {
+            let mut coordinates = [0.0; 4];
+            for ((coordinate, level), count) in coordinates.iter_mut().zip(levels).zip(counts) {
+                if count > 1 {
+                    *coordinate = level as f64 / (count - 1) as f64;
+                }
+            }
+            coordinates
+        })
+        .collect()
+}
+
+/// Orders the configurations from the center of the grid, every next configuration is
+/// the one the farthest from the ones before it.
+pub fn spread_order(parameters: Vec<Parameters>) -> Vec<Parameters> {
+    let coordinates = grid_coordinates(&parameters);
+    let distance =
+        |a: &[f64; 4], b: &[f64; 4]| -> f64 { a.iter().zip(b).map(|(a, b)| (a - b).powi(2)).sum() };
+
+    let mut nearest = vec![f64::INFINITY; parameters.len()];
+    let mut taken = vec![false; parameters.len()];
+    let mut order = Vec::with_capacity(parameters.len());
+    let center = [0.5; 4];
+    let mut next = (0..parameters.len()).min_by(|&a, &b| {
+        let (a, b) = (distance(&coordinates[a], &center), distance(&coordinates[b], &center));
+        a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+    });
+    while let Some(i) = next {
+        taken[i] = true;
+        order.push(parameters[i]);
+        for (nearest, other) in nearest.iter_mut().zip(&coordinates) {
+            *nearest = nearest.min(distance(&coordinates[i], other));
+        }
+        next = (0..parameters.len())
+            .filter(|&j| !taken[j])
+            .min_by(|&a, &b| nearest[b].partial_cmp(&nearest[a]).unwrap_or(Ordering::Equal));
+    }
+    order
+}
+
+/// Splits the spread configurations into the pilot, one configuration per thread, and the
+/// others, the baseline in first position.
+pub fn split_pilot(parameters: Vec<Parameters>) -> (Vec<Parameters>, Vec<Parameters>) {
+    let baseline = significance::baseline_parameters();
+    let (baselines, others): (Vec<_>, Vec<_>) = spread_order(parameters)
+        .into_iter()
+        .partition(|params| significance::is_same_configuration(params, &baseline));
+    let pilot_len = rayon::current_num_threads().max(2).min(others.len());
+    let (pilot, others) = others.split_at(pilot_len);
+    (pilot.to_vec(), baselines.into_iter().chain(others.iter().copied()).collect())
+}
+
+/// Selects the configurations evaluated in the remaining time at the cost of the pilot,
+/// they are evaluated in parallel like the pilot, returns them and the skipped ones.
+pub fn select(
+    parameters: Vec<Parameters>,
+    pilot_len: usize,
+    pilot_time: Duration,
+    remaining: Duration,
+) -> (Vec<Parameters>, Vec<Parameters>) {
+    let cost = pilot_time.as_secs_f64() / pilot_len.max(1) as f64;
+    let affordable = if cost > 0.0 { remaining.as_secs_f64() / cost } else { f64::INFINITY };
+    // The baseline is evaluated even when nothing else fits.
+    let count =
+        if parameters.is_empty() { 0 } else { (affordable as usize).clamp(1, parameters.len()) };
+    println!(
+        "a configuration costs {:.02?}, {} of the {} remaining configurations fit in {:.0?}",
+        Duration::from_secs_f64(cost),
+        count,
+        parameters.len(),
+        remaining,
+    );
+    let mut selected = parameters;
+    let skipped = selected.split_off(count);
+    (selected, skipped)
+}
+
+pub fn print_skipped(skipped: &[Parameters]) {
+    if skipped.is_empty() {
+        return;
+    }
+    println!("{} configurations skipped, {}:", skipped.len(), BEYOND_BUDGET);
+    for params in skipped {
+        println!("    {}", params.file_name());
+    }
+    println!();
+}
@@ -0,0 +1,94 @@
+//! The page cache budget study, how much memory a configuration needs to be fast. The
+//! jumps are run by a worker limited by a memory cgroup, for a geometric series of memory
+//! budgets, the pages of the file it reads are charged to its cgroup and evicted once the
+//! budget is reached.
+//!
+//! The worker extracts its words before it is moved into its cgroup, the budget is left
+//! to the page cache, only the small allocations of the lookups are charged with it. The
+//! latency is the mean of the lookups, the largest budget is the reference of the others.
+
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+
+use anyhow::Context;
+
+use crate::multi_process;
+use crate::params::Parameters;
+use crate::platform::MemoryCgroup;
+
+/// The mean latency of the jumps of a configuration limited to a memory budget.
+pub struct BudgetPoint {
+    pub budget: u64,
+    pub outcome: Result<Duration, String>,
+}
+
+/// Runs the jumps over the file once per memory budget, every worker in its own cgroup.
+pub fn study(
+    folder: &Path,
+    file: &Path,
+    seed: u64,
+    lookups: u64,
+    cgroup_parent: &Path,
+    budgets: &[u64],
+) -> Vec<BudgetPoint> {
+    budgets
+        .iter()
+        .map(|&budget| {
+            let outcome = (|| -> anyhow::Result<Duration> {
+                let name = format!("test-grenad-{}-{}", process::id(), budget);
+                let cgroup =
+                    MemoryCgroup::create(cgroup_parent, &name, budget).with_context(|| {
+                        format!("while creating a cgroup in {}", cgroup_parent.display())
+                    })?;
+                let results =
+                    multi_process::run_limited_worker(folder, file, seed, lookups, &cgroup)?;
+                Ok(results.elapsed.div_f64(lookups.max(1) as f64))
+            })();
+            BudgetPoint { budget, outcome: outcome.map_err(|e| format!("{:#}", e)) }
+        })
+        .collect()
+}
+
+/// Prints the latency of every configuration by budget, and the smallest budget keeping
+/// a configuration within the tolerance of its latency with the largest budget.
+pub fn print_budget_results(series: &[(Parameters, Vec<BudgetPoint>)], tolerance: f64) {
+    for (params, points) in series {
+        println!("{}:", params);
+        println!("{:>16} {:>12} {:>9}", "budget", "latency", "slowdown");
+        let reference = points.last().and_then(|point| point.outcome.as_ref().ok()).copied();
+        for point in points {
+            match (&point.outcome, reference) {
+                (Ok(latency), Some(reference)) => println!(
+                    "{:>16} {:>12.02?} {:>8.02}x",
+                    point.budget,
+                    latency,
+                    latency.as_secs_f64() / reference.as_secs_f64(),
+                ),
+                (Ok(latency), None) => println!("{:>16} {:>12.02?}", point.budget, latency),
+                (Err(error), _) => println!("{:>16} failed: {}", point.budget, error),
+            }
+        }
+
+        // The budget needed is the one from which every larger budget is fast too.
+        let needed = reference.and_then(|reference| {
+            let limit = reference.as_secs_f64() * (1.0 + tolerance / 100.0);
+            let is_fast = |point: &BudgetPoint| {
+                point.outcome.as_ref().map_or(false, |latency| latency.as_secs_f64() <= limit)
+            };
+            let first_fast = points.iter().rposition(|point| !is_fast(point)).map_or(0, |i| i + 1);
+            points.get(first_fast)
+        });
+        match needed {
+            Some(point) => println!(
+                "{} needs {} bytes of memory to be within {}% of its latency with {} bytes",
+                params,
+                point.budget,
+                tolerance,
+                points.last().unwrap().budget,
+            ),
+            None => println!("{} failed with the largest budget", params),
+        }
+        println!();
+    }
+}
@@ -0,0 +1,236 @@
+//! The checkpoint of a sweep, the outcomes of the evaluated configurations are saved
+//! in the folder every few evaluations, `--resume` evaluates the remaining ones only
+//! after a crash or a reboot, the generated files are reused anyway.
+//!
+//! The checkpoint records the fingerprint of the dataset and of the lookups, the hash
+//! of their content, a sweep over another dataset never resumes from it. Only the
+//! median timings, the sizes and the seeks are restored, the configurations restored
+//! from a checkpoint count as evaluated once.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Context;
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::arena::Words;
+use crate::platform::Telemetry;
+use crate::seeks::SeekCounts;
+use crate::{Outcome, Parameters, PhaseCpuTimes, PhaseTimings, Results, Sample, Validation};
+
+const FILE_NAME: &str = "sweep.checkpoint";
+
+/// Hashes the content of the words and of the lookups, whatever generated them.
+pub fn fingerprint<W: Words + ?Sized>(words: &W, lookups: &[usize]) -> u64 {
+    let mut hasher = Xxh3::new();
+    for word in (0..words.len()).map(|i| words.word(i)) {
+        hasher.update(&(word.len() as u64).to_le_bytes());
+        hasher.update(word);
+    }
+    for &lookup in lookups {
+        hasher.update(&(lookup as u64).to_le_bytes());
+    }
+    hasher.digest()
+}
+
+pub struct Checkpoint {
+    path: PathBuf,
+    fingerprint: u64,
+    every: usize,
+    state: Mutex<State>,
+}
+
+struct State {
+    /// The lines of the evaluated configurations, the restored ones first.
+    lines: Vec<String>,
+    unsaved: usize,
+}
+
+impl Checkpoint {
+    /// Starts the checkpoint of the folder, the previous one is resumed when asked
+    /// to, returns the outcomes of its configurations by file name.
+    pub fn open(
+        folder: &Path,
+        fingerprint: u64,
+        every: usize,
+        resume: bool,
+        validation: Validation,
+    ) -> anyhow::Result<(Checkpoint, HashMap<String, Outcome>)> {
+        let path = folder.join(FILE_NAME);
+        let mut lines = Vec::new();
+        let mut restored = HashMap::new();
+        if resume {
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    anyhow::bail!("there is no checkpoint to resume in {}", folder.display())
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("while reading {}", path.display()))
+                }
+            };
+            let mut content_lines = content.lines();
+            let recorded = content_lines
+                .next()
+                .and_then(|line| line.strip_prefix("fingerprint\t"))
+                .and_then(|hex| u64::from_str_radix(hex, 16).ok());
+            match recorded {
+                Some(recorded) if recorded == fingerprint => (),
+                Some(_) => anyhow::bail!(
+                    "the checkpoint of {} was recorded with another dataset or lookups",
+                    folder.display()
+                ),
+                None => anyhow::bail!("{} is not a checkpoint", path.display()),
+            }
+            for (i, line) in content_lines.enumerate() {
+                let (name, outcome) = parse_line(line, validation)
+                    .with_context(|| format!("at line {} of {}", i + 2, path.display()))?;
+                restored.insert(name, outcome);
+                lines.push(line.to_string());
+            }
+        }
+
+        let state = Mutex::new(State { lines, unsaved: 0 });
+        let checkpoint = Checkpoint { path, fingerprint, every, state };
+        checkpoint.save(&checkpoint.state.lock().unwrap())?;
+        Ok((checkpoint, restored))
+    }
+
+    /// Records the outcome of a configuration, the checkpoint is
+    /// saved every `every` configurations.
+    pub fn record(&self, params: &Parameters, outcome: &Outcome) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.lines.push(format_line(&params.file_name(), outcome));
+        state.unsaved += 1;
+        if state.unsaved >= self.every {
+            self.save(&state)?;
+            state.unsaved = 0;
+        }
+        Ok(())
+    }
+
+    /// Removes the checkpoint, the sweep completed.
+    pub fn finish(self) -> anyhow::Result<()> {
+        fs::remove_file(&self.path)
+            .with_context(|| format!("while removing {}", self.path.display()))
+    }
+
+    fn save(&self, state: &State) -> anyhow::Result<()> {
+        let mut content = format!("fingerprint\t{:016x}\n", self.fingerprint);
+        for line in &state.lines {
+            content.push_str(line);
+            content.push('\n');
+        }
+        // The checkpoint is replaced at once, a crash never leaves a partial one.
+        let temporary = self.path.with_extension("checkpoint.tmp");
+        fs::write(&temporary, content)
+            .with_context(|| format!("while writing {}", temporary.display()))?;
+        fs::rename(&temporary, &self.path)
+            .with_context(|| format!("while renaming {}", temporary.display()))
+    }
+}
+
+/// Splits the configurations between the ones to evaluate and the restored ones.
+pub fn split_restored(
+    parameters: Vec<Parameters>,
+    mut restored: HashMap<String, Outcome>,
+) -> (Vec<Parameters>, Vec<(Parameters, Outcome)>) {
+    let mut pending = Vec::with_capacity(parameters.len());
+    let mut outcomes = Vec::new();
+    for params in parameters {
+        match restored.remove(&params.file_name()) {
+            Some(outcome) => outcomes.push((params, outcome)),
+            None => pending.push(params),
+        }
+    }
+    (pending, outcomes)
+}
+
+fn format_line(name: &str, outcome: &Outcome) -> String {
+    let nanos =
+        |time: Option<Duration>| time.map_or_else(|| "-".to_string(), |t| t.as_nanos().to_string());
+    match outcome {
+        Ok(results) => [
+            name.to_string(),
+            "ok".to_string(),
+            nanos(results.iter_time),
+            nanos(results.keys_iter_time),
+            nanos(results.jump_time),
+            nanos(results.exact_get_time),
+            nanos(results.write_time),
+            results.file_size.to_string(),
+            results.entry_count.to_string(),
+            results.lookup_count.to_string(),
+            results.energy.map_or_else(|| "-".to_string(), |energy| energy.to_string()),
+            results.seeks.map_or_else(|| "-".to_string(), |seeks| seeks.average.to_string()),
+            results.seeks.map_or_else(|| "-".to_string(), |seeks| seeks.max.to_string()),
+        ]
+        .join("\t"),
+        Err(error) => format!("{}\tfailed\t{}", name, error.replace(&['\t', '\n', '\r'][..], " ")),
+    }
+}
+
+fn parse_line(line: &str, validation: Validation) -> anyhow::Result<(String, Outcome)> {
+    let fields: Vec<_> = line.split('\t').collect();
+    match fields.as_slice() {
+        [name, "failed", error] => Ok((name.to_string(), Err(error.to_string()))),
+        [name, "ok", fields @ ..] if fields.len() == 11 => {
+            let optional = |field: &str| -> anyhow::Result<Option<u64>> {
+                match field {
+                    "-" => Ok(None),
+                    field => Ok(Some(field.parse()?)),
+                }
+            };
+            let time = |field: &str| -> anyhow::Result<Option<Duration>> {
+                Ok(optional(field)?.map(Duration::from_nanos))
+            };
+            let energy = match fields[8] {
+                "-" => None,
+                field => Some(field.parse()?),
+            };
+            let seeks = match (fields[9], optional(fields[10])?) {
+                ("-", _) | (_, None) => None,
+                (average, Some(max)) => Some(SeekCounts { average: average.parse()?, max }),
+            };
+            let timings = PhaseTimings {
+                iter_time: time(fields[0])?,
+                keys_iter_time: time(fields[1])?,
+                jump_time: time(fields[2])?,
+                exact_get_time: time(fields[3])?,
+                slo: None,
+                warm_cold: None,
+                seeks,
+                cpu: PhaseCpuTimes::default(),
+            };
+            let results = Results {
+                iter_time: timings.iter_time,
+                keys_iter_time: timings.keys_iter_time,
+                jump_time: timings.jump_time,
+                exact_get_time: timings.exact_get_time,
+                cpu: PhaseCpuTimes::default(),
+                slo: None,
+                warm_cold: None,
+                seeks,
+                huge_pages: None,
+                validation,
+                write_time: time(fields[4])?,
+                ingestion_time: None,
+                pipeline_cost: None,
+                file_size: fields[5].parse()?,
+                entry_count: fields[6].parse()?,
+                lookup_count: fields[7].parse()?,
+                energy,
+                samples: vec![Sample { timings, energy, huge_pages: None }],
+                noisy: false,
+                telemetry_before: Telemetry::default(),
+                telemetry_after: Telemetry::default(),
+            };
+            Ok((name.to_string(), Ok(results)))
+        }
+        _ => anyhow::bail!("`{}` is not the outcome of a configuration", line),
+    }
+}
@@ -0,0 +1,134 @@
+//! The checksums of the generated files, computed while the files are written and
+//! recorded in their manifest, the files are verified before every evaluation for a
+//! silent corruption of the disk not to skew the results of long experiments.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Context;
+use xxhash_rust::xxh3::Xxh3;
+
+pub const POSSIBLE_CHECKSUMS: &[&str] = &["xxh3", "crc32"];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ChecksumKind {
+    Xxh3,
+    Crc32,
+}
+
+/// The checksum of a file and the algorithm it was computed with.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Checksum {
+    pub kind: ChecksumKind,
+    pub value: u64,
+}
+
+impl FromStr for ChecksumKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ChecksumKind, String> {
+        match s {
+            "xxh3" => Ok(ChecksumKind::Xxh3),
+            "crc32" => Ok(ChecksumKind::Crc32),
+            otherwise => Err(format!("invalid checksum {:?}, expected xxh3 or crc32", otherwise)),
+        }
+    }
+}
+
+impl fmt::Display for ChecksumKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChecksumKind::Xxh3 => f.write_str("xxh3"),
+            ChecksumKind::Crc32 => f.write_str("crc32"),
+        }
+    }
+}
+
+enum Hasher {
+    Xxh3(Box<Xxh3>),
+    Crc32(crc32fast::Hasher),
+}
+
+impl Hasher {
+    fn new(kind: ChecksumKind) -> Hasher {
+        match kind {
+            ChecksumKind::Xxh3 => Hasher::Xxh3(Box::new(Xxh3::new())),
+            ChecksumKind::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Xxh3(hasher) => hasher.update(bytes),
+            Hasher::Crc32(hasher) => hasher.update(bytes),
+        }
+    }
+
+    fn finish(self) -> Checksum {
+        match self {
+            Hasher::Xxh3(hasher) => Checksum { kind: ChecksumKind::Xxh3, value: hasher.digest() },
+            Hasher::Crc32(hasher) => {
+                Checksum { kind: ChecksumKind::Crc32, value: hasher.finalize() as u64 }
+            }
+        }
+    }
+}
+
+/// Computes the checksum of the bytes written through it, if one was requested.
+pub struct ChecksumWriter<W> {
+    inner: W,
+    hasher: Option<Hasher>,
+}
+
+impl<W> ChecksumWriter<W> {
+    pub fn new(inner: W, kind: Option<ChecksumKind>) -> ChecksumWriter<W> {
+        ChecksumWriter { inner, hasher: kind.map(Hasher::new) }
+    }
+
+    pub fn into_parts(self) -> (W, Option<Checksum>) {
+        (self.inner, self.hasher.map(Hasher::finish))
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reads the file again and compares its checksum with the expected one.
+pub fn verify(path: &Path, expected: Checksum) -> anyhow::Result<()> {
+    let mut file = File::open(path).with_context(|| format!("while opening {}", path.display()))?;
+    let mut hasher = Hasher::new(expected.kind);
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => hasher.update(&buffer[..read]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).with_context(|| format!("while reading {}", path.display())),
+        }
+    }
+    let actual = hasher.finish();
+    if actual != expected {
+        anyhow::bail!(
+            "{} is corrupted, its {} checksum is {:016x} instead of {:016x}",
+            path.display(),
+            expected.kind,
+            actual.value,
+            expected.value
+        );
+    }
+    Ok(())
+}
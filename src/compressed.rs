@@ -0,0 +1,132 @@
+//! The files compressed with gzip or zstd, `--file` accepts a `.grd.gz` or a `.grd.zst`
+//! dataset, the way the large datasets are shared, and the exports ending with `.gz` or
+//! `.zst` are written compressed.
+//!
+//! A grenad reader seeks into its file, a compressed dataset is decompressed once into a
+//! temporary file of the temporary directory, `TMPDIR`, before the extraction of its keys,
+//! the temporary file is removed when the subcommand ends.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use memmap2::Mmap;
+use tempfile::NamedTempFile;
+
+use crate::download;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// The compression of a file according to its extension, `None` for the other files.
+    pub fn of(path: &Path) -> Option<Compression> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("gz") => Some(Compression::Gzip),
+            Some("zst") => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// A grenad file given to a subcommand, mapped in memory, the decompressed
+/// copy of the file when it is compressed.
+pub struct InputFile {
+    /// The `--file`, a path or the URL of a downloaded dataset.
+    given: PathBuf,
+    /// The file on disk, the cached download of a URL.
+    path: PathBuf,
+    decompressed: Option<NamedTempFile>,
+    map: Mmap,
+}
+
+impl InputFile {
+    /// Opens the file, the URLs are downloaded into the cache first.
+    pub fn open(given: &Path, download_cache: &Path) -> anyhow::Result<InputFile> {
+        let path = match given.to_str() {
+            Some(url) if download::is_url(given) => download::fetch(url, download_cache)?,
+            _ => given.to_path_buf(),
+        };
+        let file =
+            File::open(&path).with_context(|| format!("while opening {}", path.display()))?;
+        let decompressed = match Compression::of(&path) {
+            Some(compression) => Some(decompress(&path, file, compression)?),
+            None => None,
+        };
+        let map = match &decompressed {
+            Some(temporary) => unsafe { Mmap::map(temporary.as_file())? },
+            None => unsafe { Mmap::map(&file)? },
+        };
+        Ok(InputFile { given: given.to_path_buf(), path, decompressed, map })
+    }
+
+    /// The name of the dataset in the reports, the URL of a downloaded one.
+    pub fn name(&self) -> String {
+        match download::is_url(&self.given) {
+            true => self.given.display().to_string(),
+            false => self.dataset_path().display().to_string(),
+        }
+    }
+
+    /// The path of the grenad file, the temporary file of a compressed dataset.
+    pub fn grenad_path(&self) -> &Path {
+        match &self.decompressed {
+            Some(temporary) => temporary.path(),
+            None => &self.path,
+        }
+    }
+
+    /// The path of the dataset without its compression extension, the manifest
+    /// of a compressed dataset is the one of the uncompressed file.
+    pub fn dataset_path(&self) -> PathBuf {
+        match Compression::of(&self.path) {
+            Some(_) => self.path.with_extension(""),
+            None => self.path.clone(),
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+fn decompress(path: &Path, file: File, compression: Compression) -> anyhow::Result<NamedTempFile> {
+    println!("decompressing {}...", path.display());
+    let file = BufReader::new(file);
+    let mut temporary = tempfile::Builder::new().suffix(".grd").tempfile()?;
+    let mut writer = BufWriter::new(temporary.as_file_mut());
+    let copied = match compression {
+        Compression::Gzip => io::copy(&mut MultiGzDecoder::new(file), &mut writer),
+        Compression::Zstd => io::copy(&mut zstd::stream::read::Decoder::new(file)?, &mut writer),
+    };
+    let len = copied.with_context(|| format!("while decompressing {}", path.display()))?;
+    writer.flush()?;
+    drop(writer);
+    println!("{} decompressed into {} bytes", path.display(), len);
+    Ok(temporary)
+}
+
+/// Writes an export, compressed according to the extension of its path.
+pub fn write_export(path: &Path, content: impl AsRef<[u8]>) -> anyhow::Result<()> {
+    let content = content.as_ref();
+    let written = match Compression::of(path) {
+        Some(Compression::Gzip) => File::create(path).and_then(|file| {
+            let mut encoder = GzEncoder::new(BufWriter::new(file), flate2::Compression::default());
+            encoder.write_all(content)?;
+            encoder.finish()?.flush()
+        }),
+        Some(Compression::Zstd) => File::create(path).and_then(|file| {
+            let mut encoder = zstd::stream::write::Encoder::new(BufWriter::new(file), 0)?;
+            encoder.write_all(content)?;
+            encoder.finish()?.flush()
+        }),
+        None => fs::write(path, content),
+    };
+    written.with_context(|| format!("while writing {}", path.display()))
+}
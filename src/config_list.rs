@@ -0,0 +1,36 @@
+//! The lists of configurations of `--only-configs` and `--exclude-configs`, one
+//! configuration per line in the format of the file names, e.g. `Snappy.2.4096.16`, to
+//! evaluate again exactly the configurations that failed or regressed in a previous run.
+//!
+//! The empty lines and the lines starting with `#` are ignored, the `.grd` extension and
+//! the folder of a listed file too, `ls` output of a folder of generated files is a list.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::{significance, Parameters};
+
+/// Reads the configurations listed in the file.
+pub fn read(path: &Path) -> anyhow::Result<Vec<Parameters>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("while reading {}", path.display()))?;
+    let mut list = Vec::new();
+    for (number, line) in (1..).zip(content.lines()) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let name = line.rsplit('/').next().unwrap_or(line);
+        let params = name
+            .parse()
+            .map_err(|error: String| anyhow::anyhow!("{}:{}: {}", path.display(), number, error))?;
+        list.push(params);
+    }
+    Ok(list)
+}
+
+pub fn contains(list: &[Parameters], params: &Parameters) -> bool {
+    list.iter().any(|listed| significance::is_same_configuration(listed, params))
+}
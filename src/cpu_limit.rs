@@ -0,0 +1,66 @@
+//! The limits of the CPU of a run, `--cpu-limit one-core,nice:10` approximates the small
+//! cloud instances the tuned parameters are deployed on, a recommendation found on a
+//! workstation of many idle cores doesn't necessarily hold on a single shared vCPU.
+//!
+//! The limits are applied to the process before any thread is spawned, the threads of
+//! rayon and of the background load inherit them, the setup of the run is confined too.
+//! The pool of rayon is sized by the cores the process may run on, confined to one core
+//! the configurations are evaluated one after the other.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Context;
+
+use crate::platform;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CpuLimit {
+    /// The process only runs on the first core it is allowed to.
+    OneCore,
+    /// The process runs with this niceness, between 1 and 19.
+    Nice(i32),
+}
+
+/// Applies the limits to the process, it must be called before any thread is spawned.
+pub fn apply(limits: &[CpuLimit]) -> anyhow::Result<()> {
+    for limit in limits {
+        match *limit {
+            CpuLimit::OneCore => {
+                let core =
+                    platform::pin_to_one_core().context("while confining the run to one core")?;
+                println!("the run is confined to the core {}", core);
+            }
+            CpuLimit::Nice(nice) => {
+                platform::lower_priority(nice)
+                    .with_context(|| format!("while setting the niceness to {}", nice))?;
+                println!("the run has a niceness of {}", nice);
+            }
+        }
+    }
+    Ok(())
+}
+
+impl FromStr for CpuLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<CpuLimit, String> {
+        match s.split_once(':') {
+            None if s == "one-core" => Ok(CpuLimit::OneCore),
+            Some(("nice", nice)) => match nice.parse() {
+                Ok(nice @ 1..=19) => Ok(CpuLimit::Nice(nice)),
+                _ => Err(format!("invalid niceness {:?}, expected a number from 1 to 19", nice)),
+            },
+            _ => Err(format!("invalid CPU limit {:?}, expected one-core or nice:<n>", s)),
+        }
+    }
+}
+
+impl fmt::Display for CpuLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CpuLimit::OneCore => f.write_str("one-core"),
+            CpuLimit::Nice(nice) => write!(f, "nice:{}", nice),
+        }
+    }
+}
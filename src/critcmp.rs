@@ -0,0 +1,114 @@
+//! The export of the sweep results in the format of `critcmp --export`, the
+//! exported files of several runs can be compared with `critcmp a.json b.json`
+//! like the Criterion benchmarks of the engine.
+//!
+//! Every phase of every configuration is a benchmark named `<phase>/<configuration>`,
+//! its estimates are computed from the repetitions, in nanoseconds.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Context;
+
+use crate::json::string;
+use crate::{stats, Outcome, Parameters, PhaseTimings};
+
+/// The z-score of the 95% confidence intervals.
+const Z_95: f64 = 1.96;
+
+/// Writes the successful results at `path`, the baseline is named after the file.
+pub fn write_export(path: &Path, results: &[(Parameters, Outcome)]) -> anyhow::Result<()> {
+    let baseline = path.file_stem().map_or_else(|| "test-grenad".into(), |s| s.to_string_lossy());
+
+    let phases: [(&str, fn(&PhaseTimings) -> Option<Duration>); 4] = [
+        ("iter", |t| t.iter_time),
+        ("keys-iter", |t| t.keys_iter_time),
+        ("jump", |t| t.jump_time),
+        ("exact-get", |t| t.exact_get_time),
+    ];
+
+    let mut benchmarks = Vec::new();
+    for (params, outcome) in results {
+        let results = match outcome {
+            Ok(results) => results,
+            Err(_) => continue,
+        };
+        let name = params.file_name();
+        let name = name.trim_end_matches(".grd");
+        for (phase, timing) in phases {
+            let nanos: Vec<_> = results
+                .samples
+                .iter()
+                .filter_map(|s| timing(&s.timings))
+                .map(|t| t.as_nanos() as f64)
+                .collect();
+            if !nanos.is_empty() {
+                benchmarks.push(benchmark(&baseline, phase, name, &nanos)?);
+            }
+        }
+    }
+
+    let mut json = String::new();
+    write!(json, "{{\"name\":{},\"benchmarks\":{{{}}}}}", string(&baseline), benchmarks.join(","))?;
+    fs::write(path, json).with_context(|| format!("while writing {}", path.display()))?;
+    Ok(())
+}
+
+fn benchmark(baseline: &str, group: &str, function: &str, nanos: &[f64]) -> anyhow::Result<String> {
+    let full_id = format!("{}/{}", group, function);
+    let mut json = String::new();
+    write!(
+        json,
+        "{}:{{\"baseline\":{},\"fullname\":{},\
+         \"criterion_benchmark_v1\":{{\"group_id\":{},\"function_id\":{},\"value_str\":null,\
+         \"throughput\":null,\"full_id\":{},\"directory_name\":{}}},\
+         \"criterion_estimates_v1\":{}}}",
+        string(&full_id),
+        string(baseline),
+        string(&format!("{}/{}", baseline, full_id)),
+        string(group),
+        string(function),
+        string(&full_id),
+        string(&full_id),
+        estimates(nanos)?,
+    )?;
+    Ok(json)
+}
+
+fn estimates(nanos: &[f64]) -> anyhow::Result<String> {
+    let n = nanos.len() as f64;
+    let mean = nanos.iter().sum::<f64>() / n;
+    let median = stats::median(nanos.to_vec()).unwrap_or_default();
+    let std_dev = if nanos.len() > 1 {
+        (nanos.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+    } else {
+        0.0
+    };
+    let deviations = nanos.iter().map(|x| (x - median).abs()).collect();
+    let median_abs_dev = stats::median(deviations).unwrap_or_default();
+    let standard_error = std_dev / n.sqrt();
+
+    let mut json = String::new();
+    write!(
+        json,
+        "{{\"mean\":{},\"median\":{},\"median_abs_dev\":{},\"slope\":null,\"std_dev\":{}}}",
+        estimate(mean, standard_error),
+        estimate(median, standard_error),
+        estimate(median_abs_dev, 0.0),
+        estimate(std_dev, 0.0),
+    )?;
+    Ok(json)
+}
+
+fn estimate(point: f64, standard_error: f64) -> String {
+    format!(
+        "{{\"confidence_interval\":{{\"confidence_level\":0.95,\"lower_bound\":{},\
+         \"upper_bound\":{}}},\"point_estimate\":{},\"standard_error\":{}}}",
+        point - Z_95 * standard_error,
+        point + Z_95 * standard_error,
+        point,
+        standard_error,
+    )
+}
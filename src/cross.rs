@@ -0,0 +1,133 @@
+//! The comparison of the rankings of the same configurations on two datasets, e.g.
+//! the synthetic words against a real `word_docids`, from the lines of two sweeps
+//! recorded with `--stream-results`.
+//!
+//! The configurations are joined by name, the robust ones are the configurations
+//! whose worst percentile rank on the two datasets is the lowest.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+use serde_json::Value;
+
+pub const POSSIBLE_METRICS: &[&str] =
+    &["iter", "keys-iter", "jump", "exact-get", "write", "file-size", "energy"];
+
+/// The field of the streamed lines measuring the given metric.
+fn metric_field(metric: &str) -> Option<&'static str> {
+    match metric {
+        "iter" => Some("iter_time_ns"),
+        "keys-iter" => Some("keys_iter_time_ns"),
+        "jump" => Some("jump_time_ns"),
+        "exact-get" => Some("exact_get_time_ns"),
+        "write" => Some("write_time_ns"),
+        "file-size" => Some("file_size"),
+        "energy" => Some("energy_joules"),
+        _ => None,
+    }
+}
+
+/// Reads the successful configurations of a streamed sweep, the other lines of the
+/// standard output are ignored, and returns their rank on the metric, from zero.
+fn read_ranks(path: &Path, field: &str) -> anyhow::Result<HashMap<String, usize>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("while reading {}", path.display()))?;
+    let mut scores = Vec::new();
+    for (i, line) in content.lines().enumerate().filter(|(_, line)| line.starts_with('{')) {
+        let value: Value = serde_json::from_str(line)
+            .with_context(|| format!("at line {} of {}", i + 1, path.display()))?;
+        if value["status"] != "ok" {
+            continue;
+        }
+        let name = value["name"].as_str().context("a line has no configuration name")?;
+        if let Some(score) = value[field].as_f64() {
+            scores.push((name.to_string(), score));
+        }
+    }
+    scores.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    Ok(scores.into_iter().enumerate().map(|(rank, (name, _))| (name, rank)).collect())
+}
+
+/// Prints how the ranks of the configurations shift from the first dataset
+/// to the second one, and the configurations that rank well on both.
+pub fn print_cross_dataset(
+    first: &Path,
+    second: &Path,
+    metric: &str,
+    top: usize,
+) -> anyhow::Result<()> {
+    let field = metric_field(metric).with_context(|| format!("unknown metric {:?}", metric))?;
+    let first_ranks = read_ranks(first, field)?;
+    let second_ranks = read_ranks(second, field)?;
+
+    // The ranks are renumbered over the configurations of both datasets only.
+    let mut joined: Vec<_> = first_ranks
+        .iter()
+        .filter_map(|(name, &a)| Some((name.as_str(), a, *second_ranks.get(name)?)))
+        .collect();
+    if joined.is_empty() {
+        anyhow::bail!("no successful configuration is common to both datasets");
+    }
+    let n = joined.len();
+    let renumber = |joined: &mut [(&str, usize, usize)], first: bool| {
+        joined.sort_by_key(|&(_, a, b)| if first { a } else { b });
+        for (rank, entry) in joined.iter_mut().enumerate() {
+            if first {
+                entry.1 = rank;
+            } else {
+                entry.2 = rank;
+            }
+        }
+    };
+    renumber(&mut joined, true);
+    renumber(&mut joined, false);
+
+    let squared_shifts: f64 = joined.iter().map(|&(_, a, b)| (a as f64 - b as f64).powi(2)).sum();
+    let correlation = if n > 1 {
+        1.0 - 6.0 * squared_shifts / (n as f64 * (n as f64 * n as f64 - 1.0))
+    } else {
+        1.0
+    };
+    println!(
+        "{} configurations common to {} and {}, ranked on {}",
+        n,
+        first.display(),
+        second.display(),
+        metric
+    );
+    println!("rank correlation (Spearman): {:.03}", correlation);
+    println!();
+
+    let worst_percentile = |a: usize, b: usize| a.max(b) as f64 / n as f64 * 100.0;
+    joined.sort_by(|&(an, aa, ab), &(bn, ba, bb)| {
+        worst_percentile(aa, ab)
+            .partial_cmp(&worst_percentile(ba, bb))
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| an.cmp(bn))
+    });
+    println!(
+        "{:>24} {:>8} {:>8} {:>8} {:>13}",
+        "configuration", "first", "second", "gained", "worst pctile"
+    );
+    for &(name, a, b) in joined.iter().take(top) {
+        println!(
+            "{:>24} {:>8} {:>8} {:>+8} {:>12.01}%",
+            name,
+            a + 1,
+            b + 1,
+            a as i64 - b as i64,
+            worst_percentile(a, b),
+        );
+    }
+    println!();
+
+    joined.sort_by_key(|&(_, a, b)| std::cmp::Reverse((a as i64 - b as i64).abs()));
+    println!("largest rank shifts:");
+    for &(name, a, b) in joined.iter().take(top) {
+        println!("    {}: {} -> {}", name, a + 1, b + 1);
+    }
+    Ok(())
+}
@@ -0,0 +1,312 @@
+//! The generation of the random datasets.
+//!
+//! Every word, value and lookup is generated from its own rng derived from the
+//! seed and its index, the generated datasets are therefore identical regardless
+//! of the number of threads and of the way rayon schedules the work.
+
+use std::str::FromStr;
+
+use gabble::Gabble;
+use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
+use rand::rngs::StdRng;
+use rand::{seq, Rng, SeedableRng};
+use rayon::prelude::*;
+use roaring::RoaringBitmap;
+
+/// The independent random streams, a word and a value with
+/// the same index must not be derived from the same rng.
+#[derive(Debug, Copy, Clone)]
+pub enum Stream {
+    Words = 1,
+    Values = 2,
+    Lookups = 3,
+    Insertions = 4,
+    MergeValues = 5,
+    WordPairs = 6,
+    KeySample = 7,
+}
+
+pub const POSSIBLE_KEY_PROFILES: &[&str] = &["words", "word-pair-proximity", "near-identical"];
+
+/// The serialized size of a dense RoaringBitmap container.
+const BITMAP_CONTAINER_SIZE: usize = 8 * 1024;
+/// The size of the key, cardinality and offset of a container.
+const CONTAINER_HEADER_SIZE: usize = 8;
+/// The size of the cookie and the number of containers of a RoaringBitmap.
+const BITMAP_HEADER_SIZE: usize = 8;
+
+/// The maximum proximity between the two words of a pair, milli doesn't store further pairs.
+const MAX_PROXIMITY: u8 = 7;
+
+/// The shape of the generated keys.
+#[derive(Debug, Copy, Clone)]
+pub enum KeyProfile {
+    /// Single words, of 3 to 15 characters by default.
+    Words,
+    /// The `word1\0word2\0proximity` keys of the word pair proximity database of milli,
+    /// its largest one, much longer keys that share long prefixes. The words are drawn
+    /// from a vocabulary with a Zipf-like skew, the popular pairs are repeated and
+    /// deduplicated, fewer keys than requested are therefore generated.
+    WordPairProximity,
+    /// The `word0000001`, `word0000002`... keys, the consecutive keys only differ
+    /// in their last bytes, the binary searches in the blocks compare long common
+    /// prefixes and the key index interval barely narrows them. The seed is ignored.
+    NearIdentical,
+}
+
+impl FromStr for KeyProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<KeyProfile, String> {
+        match s {
+            "words" => Ok(KeyProfile::Words),
+            "word-pair-proximity" => Ok(KeyProfile::WordPairProximity),
+            "near-identical" => Ok(KeyProfile::NearIdentical),
+            otherwise => Err(format!("invalid key profile {:?}", otherwise)),
+        }
+    }
+}
+
+pub const POSSIBLE_CHARSETS: &[&str] = &["ascii-lower", "alnum", "bytes"];
+
+/// The characters the random words are made of.
+#[derive(Debug, Copy, Clone)]
+pub enum Charset {
+    /// The pronounceable lowercase words of Gabble.
+    AsciiLower,
+    /// The ASCII letters of both cases and the digits, drawn uniformly.
+    Alnum,
+    /// Any byte, the words are not valid UTF-8.
+    Bytes,
+}
+
+impl FromStr for Charset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Charset, String> {
+        match s {
+            "ascii-lower" => Ok(Charset::AsciiLower),
+            "alnum" => Ok(Charset::Alnum),
+            "bytes" => Ok(Charset::Bytes),
+            otherwise => Err(format!("invalid charset {:?}", otherwise)),
+        }
+    }
+}
+
+/// The charset and the bounds of the lengths of the random words, the lengths
+/// are uniformly drawn, the fan-out of the index blocks depends on them.
+#[derive(Debug, Copy, Clone)]
+pub struct WordShape {
+    pub min_len: usize,
+    pub max_len: usize,
+    pub charset: Charset,
+}
+
+impl WordShape {
+    fn generate(&self, rng: &mut StdRng) -> Vec<u8> {
+        const ALNUM: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+        let len = rng.gen_range(self.min_len..=self.max_len);
+        match self.charset {
+            Charset::AsciiLower => Gabble::new().with_length(len).generate(rng).into_bytes(),
+            Charset::Alnum => (0..len).map(|_| ALNUM[rng.gen_range(0..ALNUM.len())]).collect(),
+            Charset::Bytes => (0..len).map(|_| rng.gen()).collect(),
+        }
+    }
+}
+
+/// Returns the rng dedicated to the entry at `index` of the given stream.
+pub fn entry_rng(seed: u64, stream: Stream, index: u64) -> StdRng {
+    let stream_seed = splitmix64(seed ^ splitmix64(stream as u64));
+    StdRng::seed_from_u64(splitmix64(stream_seed ^ index))
+}
+
+/// The SplitMix64 finalizer, a cheap and well-distributed bit mixer.
+fn splitmix64(x: u64) -> u64 {
+    let x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    let x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// Generates `count` random words in parallel, sorted and deduplicated.
+pub fn random_words(seed: u64, count: u64, shape: WordShape) -> Vec<Vec<u8>> {
+    let pb = ProgressBar::new(count)
+        .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
+    let mut words: Vec<_> = (0..count)
+        .into_par_iter()
+        .map(|i| {
+            let mut rng = entry_rng(seed, Stream::Words, i);
+            shape.generate(&mut rng)
+        })
+        .progress_with(pb)
+        .collect();
+    words.par_sort_unstable();
+    words.dedup();
+    words
+}
+
+/// Generates `count` random keys of the given profile, sorted and deduplicated.
+pub fn random_keys(seed: u64, count: u64, profile: KeyProfile, shape: WordShape) -> Vec<Vec<u8>> {
+    match profile {
+        KeyProfile::Words => random_words(seed, count, shape),
+        KeyProfile::WordPairProximity => random_word_pairs(seed, count, shape),
+        KeyProfile::NearIdentical => near_identical_keys(count),
+    }
+}
+
+/// The keys are zero padded to the width of the largest one, they are generated sorted.
+fn near_identical_keys(count: u64) -> Vec<Vec<u8>> {
+    let width = count.saturating_sub(1).to_string().len();
+    (0..count).map(|i| format!("word{:0width$}", i, width = width).into_bytes()).collect()
+}
+
+fn random_word_pairs(seed: u64, count: u64, shape: WordShape) -> Vec<Vec<u8>> {
+    // The vocabulary is left unsorted for the popular words
+    // not to be the first ones in lexicographic order.
+    let vocabulary_len = ((count as f64).sqrt() as u64 * 16).max(16);
+    let vocabulary: Vec<_> = (0..vocabulary_len)
+        .into_par_iter()
+        .map(|i| {
+            let mut rng = entry_rng(seed, Stream::Words, i);
+            shape.generate(&mut rng)
+        })
+        .collect();
+
+    let pb = ProgressBar::new(count)
+        .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
+    let mut pairs: Vec<_> = (0..count)
+        .into_par_iter()
+        .map(|i| {
+            let mut rng = entry_rng(seed, Stream::WordPairs, i);
+            let word1 = &vocabulary[zipf_rank(&mut rng, vocabulary.len())];
+            let word2 = &vocabulary[zipf_rank(&mut rng, vocabulary.len())];
+            // The close pairs are the most frequent ones.
+            let mut proximity = 1;
+            while proximity < MAX_PROXIMITY && rng.gen_bool(0.5) {
+                proximity += 1;
+            }
+            [&word1[..], &[0][..], &word2[..], &[0, proximity][..]].concat()
+        })
+        .progress_with(pb)
+        .collect();
+    pairs.par_sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+/// Draws a rank in `0..len` with a probability roughly inversely proportional to it.
+fn zipf_rank(rng: &mut StdRng, len: usize) -> usize {
+    let rank = (len as f64 + 1.0).powf(rng.gen::<f64>()) - 1.0;
+    (rank as usize).min(len - 1)
+}
+
+/// Generates the serialized bitmap associated with the word at `index`,
+/// of at most `max_bitmap_len` integers.
+pub fn random_value(seed: u64, index: u64, max_bitmap_len: u64, buffer: &mut Vec<u8>) {
+    let mut rng = entry_rng(seed, Stream::Values, index);
+    buffer.clear();
+    let start: u32 = rng.gen();
+    let end: u32 = start.saturating_add(rng.gen());
+    let integers = (start..=end).filter(|_| rng.gen()).take(max_bitmap_len as usize);
+    let roaring = RoaringBitmap::from_sorted_iter(integers).unwrap();
+    roaring.serialize_into(buffer).unwrap();
+}
+
+/// How the values of a generated dataset are derived from the seed and the index
+/// of their key, to validate the values read back.
+#[derive(Debug, Copy, Clone)]
+pub enum ValueGenerator {
+    Random { seed: u64, max_bitmap_len: u64 },
+    Sized { seed: u64, mean_size: u64 },
+}
+
+impl ValueGenerator {
+    pub fn generate(&self, index: u64, buffer: &mut Vec<u8>) {
+        match *self {
+            ValueGenerator::Random { seed, max_bitmap_len } => {
+                random_value(seed, index, max_bitmap_len, buffer)
+            }
+            ValueGenerator::Sized { seed, mean_size } => {
+                sized_value(seed, index, mean_size, buffer)
+            }
+        }
+    }
+}
+
+/// Generates a serialized bitmap of about `mean_size` bytes for the word at `index`,
+/// the sizes are uniformly spread between half and one and a half of the mean.
+pub fn sized_value(seed: u64, index: u64, mean_size: u64, buffer: &mut Vec<u8>) {
+    let mut rng = entry_rng(seed, Stream::Values, index);
+    buffer.clear();
+    let size = rng.gen_range(mean_size / 2..=mean_size + mean_size / 2) as usize;
+
+    // The dense containers are serialized as bitmaps of 8 KiB, the last one
+    // is an array of two bytes per integer, the headers are a few bytes each.
+    let dense_containers = size / (BITMAP_CONTAINER_SIZE + CONTAINER_HEADER_SIZE);
+    let remaining = size % (BITMAP_CONTAINER_SIZE + CONTAINER_HEADER_SIZE);
+    let array_len = remaining.saturating_sub(BITMAP_HEADER_SIZE + CONTAINER_HEADER_SIZE) / 2;
+
+    let mut bitmap = RoaringBitmap::new();
+    for container in 0..dense_containers as u32 {
+        let base = container << 16;
+        bitmap.extend((0..=u16::MAX as u32).filter(|_| rng.gen()).map(|low| base | low));
+    }
+    let base = (dense_containers as u32) << 16;
+    let mut lows = seq::index::sample(&mut rng, 1 << 16, array_len.clamp(1, 4096)).into_vec();
+    lows.sort_unstable();
+    bitmap.extend(lows.into_iter().map(|low| base | low as u32));
+    bitmap.serialize_into(buffer).unwrap();
+}
+
+/// Draws the indexes of the words to look up during the jump phase, this sequence
+/// is drawn once from the seed and shared by every configuration and backend.
+pub fn lookup_sequence(seed: u64, word_count: usize, lookup_count: u64) -> Vec<usize> {
+    let mut rng = entry_rng(seed, Stream::Lookups, 0);
+    (0..lookup_count).map(|_| rng.gen_range(0..word_count)).collect()
+}
+
+/// Draws the indexes of the keys inserted in a sorter, every key is
+/// inserted several times on average for the merge function to be called.
+pub fn insertion_sequence(seed: u64, key_count: usize, insertions: u64) -> Vec<usize> {
+    let mut rng = entry_rng(seed, Stream::Insertions, 0);
+    (0..insertions).map(|_| rng.gen_range(0..key_count)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_sequence_is_deterministic() {
+        let lookups = lookup_sequence(42, 1000, 500);
+        assert_eq!(lookups, lookup_sequence(42, 1000, 500));
+        assert_ne!(lookups, lookup_sequence(43, 1000, 500));
+        assert!(lookups.iter().all(|&index| index < 1000));
+    }
+
+    #[test]
+    fn lookup_sequence_ignores_the_other_streams() {
+        // The configurations and the backends generate their files, keys and values,
+        // from the same seed between the draws of the lookups, they must not shift them.
+        let lookups = lookup_sequence(42, 1000, 500);
+        let shape = WordShape { min_len: 3, max_len: 15, charset: Charset::AsciiLower };
+        random_keys(42, 1000, KeyProfile::WordPairProximity, shape);
+        let mut value = Vec::new();
+        ValueGenerator::Sized { seed: 42, mean_size: 64 }.generate(0, &mut value);
+        assert_eq!(lookups, lookup_sequence(42, 1000, 500));
+    }
+
+    #[test]
+    fn shorter_lookup_sequences_are_prefixes() {
+        let lookups = lookup_sequence(42, 1000, 500);
+        assert_eq!(lookup_sequence(42, 1000, 100), &lookups[..100]);
+    }
+
+    #[test]
+    fn entries_draw_from_their_own_rng() {
+        let draw = |index| entry_rng(42, Stream::Values, index).gen::<u64>();
+        assert_eq!(draw(7), draw(7));
+        assert_ne!(draw(7), draw(8));
+        assert_ne!(draw(7), entry_rng(42, Stream::Lookups, 7).gen::<u64>());
+    }
+}
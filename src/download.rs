@@ -0,0 +1,101 @@
+//! The datasets downloaded by URL, `--file https://…/word_docids.grd` downloads the
+//! file into the cache before benchmarking it, the published reference datasets are
+//! used directly from the command line.
+//!
+//! The cached files are keyed by the URL and the ETag of the file, a file that changed
+//! on the server is downloaded again. An interrupted download is resumed with a ranged
+//! request, only if the server still serves the same ETag. The file of a server that
+//! does not send an ETag can't be validated, it is downloaded every time.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use indicatif::{ProgressBar, ProgressStyle};
+use xxhash_rust::xxh3::Xxh3;
+
+/// Whether the `--file` is the URL of a dataset to download.
+pub fn is_url(path: &Path) -> bool {
+    path.to_str().map_or(false, |path| path.starts_with("http://") || path.starts_with("https://"))
+}
+
+/// Downloads the URL into the cache, if it is not already there,
+/// returns the path of the cached file.
+pub fn fetch(url: &str, cache: &Path) -> anyhow::Result<PathBuf> {
+    let head = ureq::head(url).call().with_context(|| format!("while requesting {}", url))?;
+    let etag = head.header("ETag").map(str::to_string);
+    let len = head.header("Content-Length").and_then(|len| len.parse().ok());
+
+    fs::create_dir_all(cache).with_context(|| format!("while creating {}", cache.display()))?;
+    // The name of the file is kept, its extensions tell how it is compressed.
+    let name = url.rsplit('/').next().filter(|name| !name.is_empty()).unwrap_or("dataset.grd");
+    let path = cache.join(format!("{:016x}-{}", cache_key(url, etag.as_deref()), name));
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".part");
+    let partial = PathBuf::from(partial);
+
+    match &etag {
+        Some(_) if path.exists() => {
+            println!("{} is cached in {}", url, path.display());
+            return Ok(path);
+        }
+        Some(_) => (),
+        None => {
+            println!("{} has no ETag, downloading it again", url);
+            let _ = fs::remove_file(&partial);
+        }
+    }
+
+    let resumed = match fs::metadata(&partial) {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+        Err(e) => return Err(e).with_context(|| format!("while reading {}", partial.display())),
+    };
+    let mut request = ureq::get(url);
+    if let (Some(etag), true) = (&etag, resumed != 0) {
+        // The server sends the whole file if it changed, it is downloaded from the start.
+        request = request.set("Range", &format!("bytes={}-", resumed)).set("If-Range", etag);
+    }
+    let response = request.call().with_context(|| format!("while downloading {}", url))?;
+    let (file, offset) = match response.status() {
+        206 => {
+            println!("resuming the download of {} at {} bytes", url, resumed);
+            (OpenOptions::new().append(true).open(&partial), resumed)
+        }
+        _ => (File::create(&partial), 0),
+    };
+    let file = file.with_context(|| format!("while opening {}", partial.display()))?;
+
+    let pb = ProgressBar::new(len.unwrap_or(0)).with_style(
+        ProgressStyle::default_bar().template("{wide_bar} {bytes}/{total_bytes} {eta}"),
+    );
+    pb.set_position(offset);
+    let mut writer = BufWriter::new(file);
+    io::copy(&mut pb.wrap_read(response.into_reader()), &mut writer)
+        .with_context(|| format!("while downloading {}", url))?;
+    writer.flush()?;
+    pb.finish_and_clear();
+
+    let downloaded = fs::metadata(&partial)?.len();
+    if let Some(len) = len {
+        anyhow::ensure!(
+            downloaded == len,
+            "downloaded {} bytes of {} instead of {}, run it again to resume",
+            downloaded,
+            url,
+            len
+        );
+    }
+    fs::rename(&partial, &path).with_context(|| format!("while renaming {}", partial.display()))?;
+    println!("{} downloaded into {}", url, path.display());
+    Ok(path)
+}
+
+fn cache_key(url: &str, etag: Option<&str>) -> u64 {
+    let mut hasher = Xxh3::new();
+    hasher.update(url.as_bytes());
+    hasher.update(&[0]);
+    hasher.update(etag.unwrap_or_default().as_bytes());
+    hasher.digest()
+}
@@ -0,0 +1,107 @@
+//! The energy consumed by the CPU packages, read from the RAPL counters that the
+//! Linux powercap framework exposes for both Intel and AMD CPUs.
+//!
+//! The counters are only read when the `rapl` feature is enabled, the meter is
+//! unavailable everywhere else. Note that the counters cover the whole packages,
+//! the evaluations running concurrently are accounted in every measurement.
+
+#[cfg(all(feature = "rapl", target_os = "linux"))]
+use std::path::{Path, PathBuf};
+#[cfg(all(feature = "rapl", target_os = "linux"))]
+use std::{fs, io};
+
+#[cfg(all(feature = "rapl", target_os = "linux"))]
+const POWERCAP_PATH: &str = "/sys/class/powercap";
+
+/// Measures the energy consumed since it was started.
+pub struct EnergyMeter {
+    #[cfg(all(feature = "rapl", target_os = "linux"))]
+    domains: Vec<Domain>,
+}
+
+#[cfg(all(feature = "rapl", target_os = "linux"))]
+struct Domain {
+    energy_path: PathBuf,
+    max_energy_range: u64,
+    start: u64,
+}
+
+#[cfg(all(feature = "rapl", target_os = "linux"))]
+impl EnergyMeter {
+    /// Starts measuring, returns `None` and warns once if the counters are unreadable,
+    /// recent kernels restrict them to root.
+    pub fn start() -> Option<EnergyMeter> {
+        match package_domains() {
+            Ok(domains) if !domains.is_empty() => Some(EnergyMeter { domains }),
+            Ok(_) => {
+                warn_once("no RAPL package domain found, energy will not be measured");
+                None
+            }
+            Err(error) => {
+                warn_once(&format!(
+                    "could not read the RAPL counters ({}), energy will not be measured",
+                    error
+                ));
+                None
+            }
+        }
+    }
+
+    /// Returns the joules consumed since the meter was started.
+    pub fn joules(&self) -> Option<f64> {
+        let mut microjoules = 0;
+        for domain in &self.domains {
+            let now = read_counter(&domain.energy_path).ok()?;
+            microjoules += if now >= domain.start {
+                now - domain.start
+            } else {
+                // The counter wrapped around.
+                domain.max_energy_range - domain.start + now
+            };
+        }
+        Some(microjoules as f64 / 1_000_000.0)
+    }
+}
+
+#[cfg(not(all(feature = "rapl", target_os = "linux")))]
+impl EnergyMeter {
+    pub fn start() -> Option<EnergyMeter> {
+        Some(EnergyMeter {})
+    }
+
+    pub fn joules(&self) -> Option<f64> {
+        None
+    }
+}
+
+/// Lists the top-level package domains, the subdomains
+/// (e.g. `intel-rapl:0:0`) are already accounted in them.
+#[cfg(all(feature = "rapl", target_os = "linux"))]
+fn package_domains() -> io::Result<Vec<Domain>> {
+    let mut domains = Vec::new();
+    for entry in fs::read_dir(POWERCAP_PATH)? {
+        let path = entry?.path();
+        let is_package = path.file_name().and_then(|name| name.to_str()).map_or(false, |name| {
+            name.starts_with("intel-rapl:") && name.matches(':').count() == 1
+        });
+        if is_package {
+            let energy_path = path.join("energy_uj");
+            let max_energy_range = read_counter(&path.join("max_energy_range_uj"))?;
+            let start = read_counter(&energy_path)?;
+            domains.push(Domain { energy_path, max_energy_range, start });
+        }
+    }
+    Ok(domains)
+}
+
+#[cfg(all(feature = "rapl", target_os = "linux"))]
+fn read_counter(path: &Path) -> io::Result<u64> {
+    let content = fs::read_to_string(path)?;
+    content.trim().parse().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(all(feature = "rapl", target_os = "linux"))]
+fn warn_once(message: &str) {
+    static WARNING: std::sync::Once = std::sync::Once::new();
+    WARNING.call_once(|| eprintln!("{}", message));
+}
@@ -0,0 +1,86 @@
+//! The identifier of an experiment, printed when a sweep starts and embedded in its
+//! reports, its long results and its streamed results, two runs with the same
+//! identifier ran the same experiment, possibly on different machines.
+//!
+//! It hashes what the measurements depend on, the keys of the dataset and the lookups,
+//! the generator of the values, the configurations of the grid, the read method, the
+//! workload, the knobs of the evaluation, the number of threads and the version. The
+//! outputs and the options that don't change the measurements, e.g. the sort keys, the
+//! retries or the checkpoints, are ignored.
+
+use std::fmt::{self, Write as _};
+
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::read_method::ReadMethod;
+use crate::{EvaluationOptions, Parameters};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExperimentId(u64);
+
+impl ExperimentId {
+    /// The identifier of the evaluation of the configurations on the dataset of
+    /// this fingerprint, the one of its keys and of the lookups.
+    pub fn new(
+        fingerprint: u64,
+        parameters: &[Parameters],
+        read_method: ReadMethod,
+        evaluation: &EvaluationOptions,
+    ) -> ExperimentId {
+        let mut hasher = HashWriter(Xxh3::new());
+        let workload = &evaluation.workload;
+        // Writing to the hasher never fails.
+        let _ = write!(
+            hasher,
+            "version {}\nthreads {}\ndataset {:016x}\nvalues {:?}\nread method {}\n\
+             huge pages {}\ndrop caches {}\nrepetitions {}\nmax variation {}\n\
+             noisy reruns {}\nseekable frame size {}\nlmdb ingestion {}\nphases {:?}\n\
+             validate {}\nslo {:?}\nwarm cold {}\ndeep validate {}\nfaults {:?}\n\
+             latency {:?}\nbandwidth {:?}\ntrace {:?}\nbackground load {:?}\n\
+             cpu limit {:?}\n",
+            env!("CARGO_PKG_VERSION"),
+            rayon::current_num_threads(),
+            fingerprint,
+            workload.values,
+            read_method,
+            evaluation.huge_pages,
+            evaluation.drop_caches,
+            evaluation.repetitions,
+            evaluation.max_variation,
+            evaluation.noisy_reruns,
+            evaluation.seekable_frame_size,
+            evaluation.lmdb_ingestion,
+            workload.phases,
+            workload.validate,
+            workload.slo,
+            workload.warm_cold,
+            workload.deep_validate,
+            workload.inject_faults,
+            workload.simulated_latency,
+            workload.simulated_bandwidth,
+            workload.trace,
+            evaluation.background_load(),
+            evaluation.cpu_limit,
+        );
+        for params in parameters {
+            let _ = writeln!(hasher, "{}", params);
+        }
+        ExperimentId(hasher.0.digest())
+    }
+}
+
+impl fmt::Display for ExperimentId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Feeds the formatted text to the hasher, without building it.
+struct HashWriter(Xxh3);
+
+impl fmt::Write for HashWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.update(s.as_bytes());
+        Ok(())
+    }
+}
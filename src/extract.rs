@@ -0,0 +1,130 @@
+//! The parallel extraction of the words of a grenad file, the extraction of a file of
+//! hundreds of millions of entries dominated the setup of the tests done on one thread.
+//!
+//! The key space is split by the first two bytes of the keys, every range is read by its
+//! own cursor from the first key of its prefix until the first key of the next one, the
+//! ranges are extracted by rayon and concatenated in the order of the keys. The empty
+//! ranges cost a seek, the prefixes are fine enough for the skewed first bytes of the
+//! words to be spread over every thread.
+
+use std::io::Cursor;
+
+use grenad::{Reader, ReaderCursor};
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+
+use crate::arena::{WordArena, Words};
+use crate::source_order::SortedKeys;
+use crate::stats::DatasetStats;
+
+/// The number of ranges of the key space, one per prefix of two bytes.
+const RANGES: usize = 1 << 16;
+
+/// The words of a file, the lengths of their values and the statistics of its entries.
+pub struct Extracted {
+    pub words: WordArena,
+    pub value_lengths: Vec<usize>,
+    pub stats: DatasetStats,
+}
+
+/// The first key of the range, the empty key for the first one, the ranges are the keys
+/// from their first key until the first key of the next range.
+fn range_start(range: usize) -> Vec<u8> {
+    match range {
+        0 => Vec::new(),
+        range => vec![(range >> 8) as u8, range as u8],
+    }
+}
+
+/// Extracts the entries of the range with the cursor of the thread, opened by its first range.
+fn extract_range(
+    bytes: &[u8],
+    cursor: &mut Option<ReaderCursor<Cursor<&[u8]>>>,
+    range: usize,
+) -> anyhow::Result<Extracted> {
+    if cursor.is_none() {
+        *cursor = Some(Reader::new(Cursor::new(bytes))?.into_cursor()?);
+    }
+    let cursor = cursor.as_mut().unwrap();
+    let start = range_start(range);
+    let end = (range + 1 < RANGES).then(|| range_start(range + 1));
+
+    let mut extracted = Extracted {
+        words: WordArena::default(),
+        value_lengths: Vec::new(),
+        stats: Default::default(),
+    };
+    let mut entry = cursor.move_on_key_greater_than_or_equal_to(&start)?;
+    while let Some((k, v)) = entry {
+        if end.as_ref().map_or(false, |end| k >= &end[..]) {
+            break;
+        }
+        extracted.words.push(k);
+        extracted.value_lengths.push(v.len());
+        extracted.stats.insert(k, v);
+        entry = cursor.move_on_next()?;
+    }
+    Ok(extracted)
+}
+
+/// Extracts the words of the file on one thread, in the order of its entries, the ranges
+/// of a file whose keys are not sorted can't be sought, the anomalies are skipped.
+pub fn extract_sorted(bytes: &[u8]) -> anyhow::Result<Extracted> {
+    let mut cursor = Reader::new(Cursor::new(bytes))?.into_cursor()?;
+    let number_of_entries = cursor.len();
+    let pb = ProgressBar::new(number_of_entries)
+        .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
+    let mut extracted = Extracted {
+        words: WordArena::with_capacity(number_of_entries as usize),
+        value_lengths: Vec::with_capacity(number_of_entries as usize),
+        stats: DatasetStats::default(),
+    };
+    let mut sorted = SortedKeys::default();
+    while let Some((k, v)) = cursor.move_on_next()? {
+        if sorted.keep(k) {
+            extracted.words.push(k);
+            extracted.value_lengths.push(v.len());
+            extracted.stats.insert(k, v);
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+    Ok(extracted)
+}
+
+/// Extracts the words of the file in parallel, in the order of the file.
+pub fn extract(bytes: &[u8]) -> anyhow::Result<Extracted> {
+    let number_of_entries = Reader::new(Cursor::new(bytes))?.into_cursor()?.len();
+    let pb = ProgressBar::new(number_of_entries)
+        .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
+    let ranges = (0..RANGES)
+        .into_par_iter()
+        .map_init(
+            || None,
+            |cursor, range| {
+                let extracted = extract_range(bytes, cursor, range)?;
+                pb.inc(extracted.value_lengths.len() as u64);
+                Ok(extracted)
+            },
+        )
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    pb.finish_and_clear();
+
+    let mut extracted = Extracted {
+        words: WordArena::with_capacity(number_of_entries as usize),
+        value_lengths: Vec::with_capacity(number_of_entries as usize),
+        stats: DatasetStats::default(),
+    };
+    for range in ranges {
+        extracted.words.append(&range.words);
+        extracted.value_lengths.extend(range.value_lengths);
+        extracted.stats.merge(range.stats);
+    }
+    anyhow::ensure!(
+        extracted.words.len() as u64 == number_of_entries,
+        "{} words extracted out of the {} entries of the file",
+        extracted.words.len(),
+        number_of_entries,
+    );
+    Ok(extracted)
+}
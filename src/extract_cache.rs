@@ -0,0 +1,135 @@
+//! The cache of the words extracted from the files, the extraction of a huge file takes
+//! minutes and the sweeps over the same file extract the same words every time. The words,
+//! or their sample, the lengths of their values and the statistics of the entries are
+//! written in the `extractions` folder and read again by the next runs.
+//!
+//! A cached extraction is keyed by the hash of the content of the file and by the sample,
+//! a file that changed is extracted again. The cache is a binary file of little-endian
+//! integers, the distributions of the statistics then every word and the length of its
+//! value, it is written to a temporary file renamed once complete.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use rayon::prelude::*;
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
+
+use crate::arena::{WordArena, Words};
+use crate::extract::Extracted;
+use crate::stats::DatasetStats;
+use crate::sweep::KeySample;
+
+const MAGIC: &[u8] = b"test-grenad extraction v1\n";
+
+/// The size of the chunks of the file hashed in parallel.
+const HASH_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+
+/// Hashes the content of the file, the hashes of its chunks are computed in parallel.
+pub fn source_hash(bytes: &[u8]) -> u64 {
+    let chunks: Vec<_> = bytes.par_chunks(HASH_CHUNK_SIZE).map(xxh3_64).collect();
+    let mut hasher = Xxh3::new();
+    for chunk in chunks {
+        hasher.update(&chunk.to_le_bytes());
+    }
+    hasher.digest()
+}
+
+/// The path of the cached extraction of the file of this hash, with this sample.
+pub fn path(cache: &Path, hash: u64, sample: Option<KeySample>) -> PathBuf {
+    match sample {
+        Some(sample) => {
+            cache.join(format!("{:016x}-sample-{}-{}.words", hash, sample.size, sample.seed))
+        }
+        None => cache.join(format!("{:016x}-all.words", hash)),
+    }
+}
+
+/// Reads the cached extraction, `None` if the file has not been extracted yet.
+pub fn read(path: &Path) -> anyhow::Result<Option<Extracted>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("while opening {}", path.display())),
+    };
+    let extracted = read_extraction(&mut BufReader::new(file))
+        .with_context(|| format!("while reading {}", path.display()))?;
+    Ok(Some(extracted))
+}
+
+fn read_extraction(input: &mut impl Read) -> anyhow::Result<Extracted> {
+    let mut magic = vec![0; MAGIC.len()];
+    input.read_exact(&mut magic)?;
+    anyhow::ensure!(magic == MAGIC, "not a cached extraction");
+
+    let key_lengths = read_distribution(input)?;
+    let value_sizes = read_distribution(input)?;
+    let stats = DatasetStats::from_distributions(key_lengths, value_sizes);
+
+    let count = read_u64(input)? as usize;
+    let mut words = WordArena::with_capacity(count);
+    let mut value_lengths = Vec::with_capacity(count);
+    let mut word = Vec::new();
+    for _ in 0..count {
+        word.resize(read_u64(input)? as usize, 0);
+        input.read_exact(&mut word)?;
+        words.push(&word);
+        value_lengths.push(read_u64(input)? as usize);
+    }
+    Ok(Extracted { words, value_lengths, stats })
+}
+
+fn read_distribution(input: &mut impl Read) -> io::Result<BTreeMap<usize, u64>> {
+    let len = read_u64(input)?;
+    let mut distribution = BTreeMap::new();
+    for _ in 0..len {
+        distribution.insert(read_u64(input)? as usize, read_u64(input)?);
+    }
+    Ok(distribution)
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Writes the extraction in the cache, the next runs over the same file read it.
+pub fn write(path: &Path, extracted: &Extracted) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("while creating {}", parent.display()))?;
+    }
+    let temporary = path.with_extension("words.tmp");
+    let file = File::create(&temporary)
+        .with_context(|| format!("while creating {}", temporary.display()))?;
+    let mut output = BufWriter::new(file);
+    write_extraction(&mut output, extracted)
+        .and_then(|()| output.flush())
+        .with_context(|| format!("while writing {}", temporary.display()))?;
+    drop(output);
+    fs::rename(&temporary, path)
+        .with_context(|| format!("while renaming {} to {}", temporary.display(), path.display()))
+}
+
+fn write_extraction(output: &mut impl Write, extracted: &Extracted) -> io::Result<()> {
+    output.write_all(MAGIC)?;
+    let (key_lengths, value_sizes) = extracted.stats.distributions();
+    for distribution in [key_lengths, value_sizes] {
+        output.write_all(&(distribution.len() as u64).to_le_bytes())?;
+        for (&size, &count) in distribution {
+            output.write_all(&(size as u64).to_le_bytes())?;
+            output.write_all(&count.to_le_bytes())?;
+        }
+    }
+
+    output.write_all(&(extracted.words.len() as u64).to_le_bytes())?;
+    for (word, &value_len) in extracted.words.iter().zip(&extracted.value_lengths) {
+        output.write_all(&(word.len() as u64).to_le_bytes())?;
+        output.write_all(word)?;
+        output.write_all(&(value_len as u64).to_le_bytes())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! The injection of the faults of a degraded storage in the reads of the evaluated
+//! files, e.g. `--inject-faults short-reads=10%,eintr=1%,eio=0.01%,spikes=0.1%:10ms`.
+//!
+//! The short reads and the interruptions must be retried by the reader of grenad, the
+//! evaluation fails with the I/O errors, the latency spikes only slow the reads down.
+//! The faults are drawn from a seeded generator, the same reads fail in every run.
+
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::args::parse_duration;
+use crate::sort::parse_percentage;
+
+/// The probabilities of the faults of every read, in percent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultSpec {
+    source: String,
+    short_reads: f64,
+    interrupts: f64,
+    io_errors: f64,
+    spikes: f64,
+    spike_latency: Duration,
+    seed: u64,
+}
+
+impl FromStr for FaultSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<FaultSpec, String> {
+        let mut spec = FaultSpec {
+            source: s.trim().to_string(),
+            short_reads: 0.0,
+            interrupts: 0.0,
+            io_errors: 0.0,
+            spikes: 0.0,
+            spike_latency: Duration::from_millis(10),
+            seed: 0,
+        };
+        for fault in s.split(',') {
+            let (name, value) = fault.split_once('=').ok_or_else(|| {
+                format!("`{}` must be like `short-reads=10%` or `spikes=1%:10ms`", fault.trim())
+            })?;
+            match name.trim() {
+                "short-reads" => spec.short_reads = parse_probability(value)?,
+                "eintr" => spec.interrupts = parse_probability(value)?,
+                "eio" => spec.io_errors = parse_probability(value)?,
+                "spikes" => match value.split_once(':') {
+                    Some((probability, latency)) => {
+                        spec.spikes = parse_probability(probability)?;
+                        spec.spike_latency = parse_duration(latency.trim())?;
+                    }
+                    None => spec.spikes = parse_probability(value)?,
+                },
+                "seed" => {
+                    spec.seed =
+                        value.trim().parse().map_err(|_| format!("invalid seed `{}`", value))?
+                }
+                otherwise => {
+                    return Err(format!(
+                        "unknown fault `{}`, expected short-reads, eintr, eio, spikes or seed",
+                        otherwise
+                    ))
+                }
+            }
+        }
+        Ok(spec)
+    }
+}
+
+impl fmt::Display for FaultSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn parse_probability(s: &str) -> Result<f64, String> {
+    match parse_percentage(s)? {
+        percentage if percentage <= 100.0 => Ok(percentage),
+        percentage => Err(format!("{}% is not a probability", percentage)),
+    }
+}
+
+/// Wraps a reader and injects the faults of the spec in its reads.
+pub struct FaultInjector<R> {
+    inner: R,
+    spec: FaultSpec,
+    rng: StdRng,
+}
+
+impl<R> FaultInjector<R> {
+    pub fn new(inner: R, spec: &FaultSpec) -> FaultInjector<R> {
+        FaultInjector { inner, spec: spec.clone(), rng: StdRng::seed_from_u64(spec.seed) }
+    }
+
+    fn happens(&mut self, percentage: f64) -> bool {
+        percentage > 0.0 && self.rng.gen::<f64>() * 100.0 < percentage
+    }
+}
+
+impl<R: Read> Read for FaultInjector<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.happens(self.spec.spikes) {
+            thread::sleep(self.spec.spike_latency);
+        }
+        if self.happens(self.spec.interrupts) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "injected interruption"));
+        }
+        if self.happens(self.spec.io_errors) {
+            return Err(io::Error::new(io::ErrorKind::Other, "injected I/O error"));
+        }
+        if buf.len() > 1 && self.happens(self.spec.short_reads) {
+            let len = self.rng.gen_range(1..buf.len());
+            return self.inner.read(&mut buf[..len]);
+        }
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Seek> Seek for FaultInjector<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
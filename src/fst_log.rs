@@ -0,0 +1,239 @@
+//! An experimental backend storing the keys in an `fst::Map` of the offsets of the
+//! values in a separate value log, a layout frequently suggested for milli.
+//!
+//! Every value of the log is compressed on its own, with the compression of the grenad
+//! configurations, and prefixed by its compressed length. The backend is compiled in
+//! with the `fst-value-log` feature.
+
+use std::path::Path;
+use std::time::Duration;
+
+use grenad::CompressionType;
+
+use crate::datagen::ValueGenerator;
+use crate::progress::PhaseProgress;
+use crate::{PhaseTimings, WorkloadOptions};
+
+/// The measurements of the fst backend, the sizes are the ones of both files.
+pub struct FstResults {
+    pub write_time: Duration,
+    pub keys_size: u64,
+    pub values_size: u64,
+    pub timings: PhaseTimings,
+}
+
+/// Writes the keys and the value log of the dataset and executes the workload on them.
+#[cfg(feature = "fst-value-log")]
+pub fn evaluate(
+    folder: &Path,
+    values: ValueGenerator,
+    words: &[Vec<u8>],
+    lookups: &[usize],
+    compression: CompressionType,
+    workload: &WorkloadOptions,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<FstResults> {
+    imp::evaluate(folder, values, words, lookups, compression, workload, progress)
+}
+
+#[cfg(not(feature = "fst-value-log"))]
+pub fn evaluate(
+    _folder: &Path,
+    _values: ValueGenerator,
+    _words: &[Vec<u8>],
+    _lookups: &[usize],
+    _compression: CompressionType,
+    _workload: &WorkloadOptions,
+    _progress: &mut PhaseProgress,
+) -> anyhow::Result<FstResults> {
+    anyhow::bail!("the fst backend is not compiled in, enable the `fst-value-log` feature")
+}
+
+#[cfg(feature = "fst-value-log")]
+mod imp {
+    use std::fs::{self, File};
+    use std::io::{BufWriter, Write};
+    use std::path::Path;
+    use std::time::Instant;
+
+    use anyhow::Context;
+    use fst::{IntoStreamer, Map, MapBuilder, Streamer};
+    use grenad::CompressionType;
+    use memmap2::Mmap;
+
+    use super::FstResults;
+    use crate::datagen::ValueGenerator;
+    use crate::manifest::Manifest;
+    use crate::progress::{PhaseProgress, PROGRESS_STEP};
+    use crate::value_compression::{compress, decompress};
+    use crate::{check_value, platform, Phase, PhaseCpuTimes, PhaseTimings, WorkloadOptions};
+
+    pub fn evaluate(
+        folder: &Path,
+        values: ValueGenerator,
+        words: &[Vec<u8>],
+        lookups: &[usize],
+        compression: CompressionType,
+        workload: &WorkloadOptions,
+        progress: &mut PhaseProgress,
+    ) -> anyhow::Result<FstResults> {
+        let name = format!("{:?}-fst", compression).to_lowercase();
+        let keys_path = folder.join(&name).with_extension("fst");
+        let values_path = folder.join(&name).with_extension("values");
+
+        let before_write = Instant::now();
+        write(&keys_path, &values_path, values, words, compression)?;
+        let write_time = before_write.elapsed();
+
+        let open = |path: &Path| -> anyhow::Result<Mmap> {
+            let file =
+                File::open(path).with_context(|| format!("while opening {}", path.display()))?;
+            Ok(unsafe { Mmap::map(&file)? })
+        };
+        let keys = Map::new(open(&keys_path)?)?;
+        let log = open(&values_path)?;
+
+        let max_bitmap_len = Manifest::of_values(&values).max_bitmap_len;
+        let timings =
+            test_fst(&keys, &log, compression, words, lookups, workload, max_bitmap_len, progress)?;
+
+        Ok(FstResults {
+            write_time,
+            keys_size: fs::metadata(&keys_path)?.len(),
+            values_size: log.len() as u64,
+            timings,
+        })
+    }
+
+    fn write(
+        keys_path: &Path,
+        values_path: &Path,
+        values: ValueGenerator,
+        words: &[Vec<u8>],
+        compression: CompressionType,
+    ) -> anyhow::Result<()> {
+        let keys = File::create(keys_path)
+            .with_context(|| format!("while creating {}", keys_path.display()))?;
+        let mut keys = MapBuilder::new(BufWriter::new(keys))?;
+        let log = File::create(values_path)
+            .with_context(|| format!("while creating {}", values_path.display()))?;
+        let mut log = BufWriter::new(log);
+
+        let (mut value, mut compressed) = (Vec::new(), Vec::new());
+        let mut offset = 0;
+        for (i, word) in words.iter().enumerate() {
+            values.generate(i as u64, &mut value);
+            compress(compression, &value, &mut compressed)?;
+            keys.insert(word, offset)?;
+            log.write_all(&(compressed.len() as u32).to_le_bytes())?;
+            log.write_all(&compressed)?;
+            offset += 4 + compressed.len() as u64;
+        }
+
+        keys.finish()?;
+        log.flush()?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn test_fst(
+        keys: &Map<Mmap>,
+        log: &[u8],
+        compression: CompressionType,
+        words: &[Vec<u8>],
+        lookups: &[usize],
+        workload: &WorkloadOptions,
+        max_bitmap_len: Option<u64>,
+        progress: &mut PhaseProgress,
+    ) -> anyhow::Result<PhaseTimings> {
+        let (mut value, mut expected) = (Vec::new(), Vec::new());
+
+        let mut cpu = PhaseCpuTimes::default();
+        let mut iter_elapsed = None;
+        if workload.phases.contains(&Phase::Iter) {
+            progress.start_phase("iterating", keys.len() as u64);
+            let cpu_before = platform::thread_cpu_time();
+            let before_iter = Instant::now();
+            let mut stream = keys.stream();
+            let mut i = 0;
+            while let Some((k, offset)) = stream.next() {
+                decompress(compression, record(log, offset), &mut value)?;
+                if workload.validate.should_validate(i as u64) {
+                    assert_eq!(k, &words[i][..]);
+                    check_value(workload, max_bitmap_len, i, &value, &mut expected);
+                }
+                i += 1;
+                if i as u64 % PROGRESS_STEP == 0 {
+                    progress.set_position(i as u64);
+                }
+            }
+            iter_elapsed = Some(before_iter.elapsed());
+            cpu.iter = platform::thread_cpu_time_since(cpu_before);
+        }
+
+        let mut keys_iter_elapsed = None;
+        if workload.phases.contains(&Phase::KeysIter) {
+            // The value log is never read.
+            progress.start_phase("iterating over keys", keys.len() as u64);
+            let cpu_before = platform::thread_cpu_time();
+            let before_iter = Instant::now();
+            let mut stream = keys.stream();
+            let mut i = 0;
+            while let Some((k, _)) = stream.next() {
+                if workload.validate.should_validate(i as u64) {
+                    assert_eq!(k, &words[i][..]);
+                }
+                i += 1;
+                if i as u64 % PROGRESS_STEP == 0 {
+                    progress.set_position(i as u64);
+                }
+            }
+            keys_iter_elapsed = Some(before_iter.elapsed());
+            cpu.keys_iter = platform::thread_cpu_time_since(cpu_before);
+        }
+
+        let mut jump_elapsed = None;
+        if workload.phases.contains(&Phase::Jump) {
+            progress.start_phase("jumping", lookups.len() as u64);
+            let cpu_before = platform::thread_cpu_time();
+            let before_jump = Instant::now();
+            for (n, &index) in (0..).zip(lookups) {
+                if n % PROGRESS_STEP == 0 {
+                    progress.set_position(n);
+                }
+                let word = &words[index];
+                let mut stream = keys.range().ge(word).into_stream();
+                let (k, offset) = stream.next().unwrap();
+                decompress(compression, record(log, offset), &mut value)?;
+                if workload.validate.should_validate(n) {
+                    assert_eq!(k, &word[..]);
+                    check_value(workload, max_bitmap_len, index, &value, &mut expected);
+                }
+            }
+            jump_elapsed = Some(before_jump.elapsed());
+            cpu.jump = platform::thread_cpu_time_since(cpu_before);
+        }
+
+        progress.finish();
+
+        Ok(PhaseTimings {
+            iter_time: iter_elapsed,
+            keys_iter_time: keys_iter_elapsed,
+            jump_time: jump_elapsed,
+            exact_get_time: None,
+            slo: None,
+            warm_cold: None,
+            seeks: None,
+            cpu,
+        })
+    }
+
+    /// Returns the compressed value recorded at `offset` in the log.
+    fn record(log: &[u8], offset: u64) -> &[u8] {
+        let offset = offset as usize;
+        let mut len = [0; 4];
+        len.copy_from_slice(&log[offset..offset + 4]);
+        let len = u32::from_le_bytes(len) as usize;
+        &log[offset + 4..offset + 4 + len]
+    }
+}
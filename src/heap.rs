@@ -0,0 +1,260 @@
+//! The in-memory baseline of the sweeps, the entries are held on the heap, in a B-tree
+//! map or in a sorted vector searched by dichotomy, and subjected to the same workloads.
+//!
+//! No file is read and no block is decoded, the timings are the ceiling of the hardware
+//! and the grenad configurations are reported as multiples of them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Bound;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::arena::Words;
+use crate::progress::{PhaseProgress, PROGRESS_STEP};
+use crate::{
+    check_value, platform, stats, Outcome, Parameters, Phase, PhaseCpuTimes, PhaseTimings,
+    WorkloadOptions,
+};
+
+pub const POSSIBLE_HEAP_BACKENDS: &[&str] = &["btreemap", "vec-binary-search"];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HeapBackendKind {
+    BTreeMap,
+    VecBinarySearch,
+}
+
+impl FromStr for HeapBackendKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<HeapBackendKind, String> {
+        match s {
+            "btreemap" => Ok(HeapBackendKind::BTreeMap),
+            "vec-binary-search" => Ok(HeapBackendKind::VecBinarySearch),
+            otherwise => Err(format!(
+                "invalid heap backend {:?}, expected btreemap or vec-binary-search",
+                otherwise
+            )),
+        }
+    }
+}
+
+impl fmt::Display for HeapBackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeapBackendKind::BTreeMap => f.write_str("btreemap"),
+            HeapBackendKind::VecBinarySearch => f.write_str("vec-binary-search"),
+        }
+    }
+}
+
+/// The entries of the dataset, sorted by key.
+pub enum HeapBackend {
+    BTreeMap(BTreeMap<Vec<u8>, Vec<u8>>),
+    VecBinarySearch(Vec<(Vec<u8>, Vec<u8>)>),
+}
+
+impl HeapBackend {
+    pub fn build(
+        kind: HeapBackendKind,
+        entries: impl IntoIterator<Item = (Vec<u8>, Vec<u8>)>,
+    ) -> HeapBackend {
+        match kind {
+            HeapBackendKind::BTreeMap => HeapBackend::BTreeMap(entries.into_iter().collect()),
+            HeapBackendKind::VecBinarySearch => {
+                let mut entries: Vec<_> = entries.into_iter().collect();
+                entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+                entries.dedup_by(|(a, _), (b, _)| a == b);
+                HeapBackend::VecBinarySearch(entries)
+            }
+        }
+    }
+
+    pub fn kind(&self) -> HeapBackendKind {
+        match self {
+            HeapBackend::BTreeMap(_) => HeapBackendKind::BTreeMap,
+            HeapBackend::VecBinarySearch(_) => HeapBackendKind::VecBinarySearch,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            HeapBackend::BTreeMap(map) => map.len(),
+            HeapBackend::VecBinarySearch(entries) => entries.len(),
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (&[u8], &[u8])> + '_> {
+        match self {
+            HeapBackend::BTreeMap(map) => Box::new(map.iter().map(|(k, v)| (&k[..], &v[..]))),
+            HeapBackend::VecBinarySearch(entries) => {
+                Box::new(entries.iter().map(|(k, v)| (&k[..], &v[..])))
+            }
+        }
+    }
+
+    fn get_greater_than_or_equal_to(&self, key: &[u8]) -> Option<(&[u8], &[u8])> {
+        match self {
+            HeapBackend::BTreeMap(map) => map
+                .range::<[u8], _>((Bound::Included(key), Bound::Unbounded))
+                .next()
+                .map(|(k, v)| (&k[..], &v[..])),
+            HeapBackend::VecBinarySearch(entries) => {
+                let index = entries.partition_point(|(k, _)| &k[..] < key);
+                entries.get(index).map(|(k, v)| (&k[..], &v[..]))
+            }
+        }
+    }
+}
+
+/// The median timings of the heap backend over the repetitions.
+#[derive(Debug, Copy, Clone)]
+pub struct HeapResults {
+    pub kind: HeapBackendKind,
+    pub iter_time: Option<Duration>,
+    pub keys_iter_time: Option<Duration>,
+    pub jump_time: Option<Duration>,
+}
+
+impl HeapResults {
+    /// How many times slower than the heap backend the configuration iterates and jumps.
+    pub fn multiples(&self, results: &crate::Results) -> (Option<f64>, Option<f64>) {
+        (multiple(results.iter_time, self.iter_time), multiple(results.jump_time, self.jump_time))
+    }
+}
+
+fn multiple(time: Option<Duration>, baseline: Option<Duration>) -> Option<f64> {
+    match (time, baseline) {
+        (Some(time), Some(baseline)) if !baseline.is_zero() => {
+            Some(time.as_secs_f64() / baseline.as_secs_f64())
+        }
+        _ => None,
+    }
+}
+
+/// Executes the workload on the heap backend as many times as requested.
+pub fn evaluate<W: Words + ?Sized>(
+    backend: &HeapBackend,
+    words: &W,
+    lookups: &[usize],
+    workload: &WorkloadOptions,
+    repetitions: usize,
+    max_bitmap_len: Option<u64>,
+) -> HeapResults {
+    let mut progress = PhaseProgress::bar();
+    let samples: Vec<_> = (0..repetitions)
+        .map(|_| test_heap(backend, words, lookups, workload, max_bitmap_len, &mut progress))
+        .collect();
+    HeapResults {
+        kind: backend.kind(),
+        iter_time: stats::median(samples.iter().filter_map(|t| t.iter_time).collect()),
+        keys_iter_time: stats::median(samples.iter().filter_map(|t| t.keys_iter_time).collect()),
+        jump_time: stats::median(samples.iter().filter_map(|t| t.jump_time).collect()),
+    }
+}
+
+fn test_heap<W: Words + ?Sized>(
+    backend: &HeapBackend,
+    words: &W,
+    lookups: &[usize],
+    workload: &WorkloadOptions,
+    max_bitmap_len: Option<u64>,
+    progress: &mut PhaseProgress,
+) -> PhaseTimings {
+    let mut expected = Vec::new();
+    let mut cpu = PhaseCpuTimes::default();
+    let mut iter_elapsed = None;
+    if workload.phases.contains(&Phase::Iter) {
+        progress.start_phase("iterating", backend.len() as u64);
+        let cpu_before = platform::thread_cpu_time();
+        let before_iter = Instant::now();
+        for (i, (k, v)) in backend.iter().enumerate() {
+            if workload.validate.should_validate(i as u64) {
+                assert_eq!(k, words.word(i));
+                check_value(workload, max_bitmap_len, i, v, &mut expected);
+            }
+            if (i as u64 + 1) % PROGRESS_STEP == 0 {
+                progress.set_position(i as u64 + 1);
+            }
+        }
+        iter_elapsed = Some(before_iter.elapsed());
+        cpu.iter = platform::thread_cpu_time_since(cpu_before);
+    }
+
+    let mut keys_iter_elapsed = None;
+    if workload.phases.contains(&Phase::KeysIter) {
+        progress.start_phase("iterating over keys", backend.len() as u64);
+        let cpu_before = platform::thread_cpu_time();
+        let before_iter = Instant::now();
+        for (i, (k, _)) in backend.iter().enumerate() {
+            if workload.validate.should_validate(i as u64) {
+                assert_eq!(k, words.word(i));
+            }
+            if (i as u64 + 1) % PROGRESS_STEP == 0 {
+                progress.set_position(i as u64 + 1);
+            }
+        }
+        keys_iter_elapsed = Some(before_iter.elapsed());
+        cpu.keys_iter = platform::thread_cpu_time_since(cpu_before);
+    }
+
+    let mut jump_elapsed = None;
+    if workload.phases.contains(&Phase::Jump) {
+        progress.start_phase("jumping", lookups.len() as u64);
+        let cpu_before = platform::thread_cpu_time();
+        let before_jump = Instant::now();
+        for (n, &index) in (0..).zip(lookups) {
+            if n % PROGRESS_STEP == 0 {
+                progress.set_position(n);
+            }
+            let word = words.word(index);
+            let (k, v) = backend.get_greater_than_or_equal_to(word).unwrap();
+            if workload.validate.should_validate(n) {
+                assert_eq!(k, word);
+                check_value(workload, max_bitmap_len, index, v, &mut expected);
+            }
+        }
+        jump_elapsed = Some(before_jump.elapsed());
+        cpu.jump = platform::thread_cpu_time_since(cpu_before);
+    }
+
+    progress.finish();
+
+    PhaseTimings {
+        iter_time: iter_elapsed,
+        keys_iter_time: keys_iter_elapsed,
+        jump_time: jump_elapsed,
+        exact_get_time: None,
+        slo: None,
+        warm_cold: None,
+        seeks: None,
+        cpu,
+    }
+}
+
+/// Prints the timings of the heap backend and the multiples of them of every configuration.
+pub fn print_heap_report(heap: &HeapResults, results: &[(Parameters, Outcome)]) {
+    println!("in-memory baseline ({}):", heap.kind);
+    let format_time = |time: Option<Duration>| {
+        time.map_or_else(|| "skipped".to_string(), |time| format!("{:.02?}", time))
+    };
+    println!("iter: {}", format_time(heap.iter_time));
+    println!("keys iter: {}", format_time(heap.keys_iter_time));
+    println!("jump: {}", format_time(heap.jump_time));
+
+    let format_multiple =
+        |m: Option<f64>| m.map_or_else(|| "-".to_string(), |m| format!("{:.02}x", m));
+    for (params, outcome) in results {
+        if let Ok(results) = outcome {
+            let (iter, jump) = heap.multiples(results);
+            println!(
+                "{}: iter {}, jump {}",
+                params.file_name(),
+                format_multiple(iter),
+                format_multiple(jump),
+            );
+        }
+    }
+    println!();
+}
@@ -0,0 +1,44 @@
+//! The ingestion of a grenad file into LMDB, the final write step of milli, every entry
+//! of the file is appended into a fresh LMDB environment.
+//!
+//! The blocks of the file are decompressed during the ingestion, the compression of a
+//! configuration costs at this step too and the read-only phases don't measure it. The
+//! environment is created next to the file and removed once measured, the time includes
+//! the commit, but not the page cache of the environment written back to disk.
+
+use std::fs::File;
+use std::io::Cursor;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use grenad::Reader;
+use heed::EnvOpenOptions;
+
+use crate::FIVE_GIB;
+
+/// Appends the entries of the file into a fresh LMDB environment, returns the time it took.
+pub fn ingest_into_lmdb(filepath: &Path) -> anyhow::Result<Duration> {
+    let folder = filepath.parent().unwrap_or_else(|| Path::new("."));
+    let env_dir = tempfile::Builder::new().prefix("ingestion-").suffix(".mdb").tempdir_in(folder);
+    let env_dir = env_dir
+        .with_context(|| format!("while creating an environment in {}", folder.display()))?;
+    let file =
+        File::open(filepath).with_context(|| format!("while opening {}", filepath.display()))?;
+    let map = unsafe { memmap2::Mmap::map(&file)? };
+
+    let before_ingestion = Instant::now();
+    let mut cursor = Reader::new(Cursor::new(&map[..]))?.into_cursor()?;
+    let env = EnvOpenOptions::new().map_size(FIVE_GIB).open(env_dir.path())?;
+    let database = env.create_database(None)?;
+    let mut wtxn = env.write_txn()?;
+    while let Some((k, v)) = cursor.move_on_next()? {
+        database.append(&mut wtxn, k, v)?;
+    }
+    wtxn.commit()?;
+    let elapsed = before_ingestion.elapsed();
+
+    drop(env);
+    env_dir.close()?;
+    Ok(elapsed)
+}
@@ -0,0 +1,36 @@
+//! The serialization of the few JSON values of the exported files, they are
+//! small enough that writing them by hand is simpler than a dependency.
+
+use std::fmt::Write as _;
+use std::time::Duration;
+
+/// Serializes a JSON string, escaping the quotes, backslashes and control characters.
+pub fn string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Serializes a duration in nanoseconds, `null` when it wasn't measured.
+pub fn nanos(duration: Option<Duration>) -> String {
+    duration.map_or_else(|| "null".to_string(), |d| d.as_nanos().to_string())
+}
+
+/// Serializes a number, `null` when it wasn't measured or isn't finite.
+pub fn number(number: Option<f64>) -> String {
+    match number {
+        Some(number) if number.is_finite() => number.to_string(),
+        _ => "null".to_string(),
+    }
+}
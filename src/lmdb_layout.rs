@@ -0,0 +1,270 @@
+//! The layouts of the LMDB backend, `--lmdb-layout composite-keys` stores every document
+//! id of a word as its own entry instead of one serialized bitmap per word, an approach of
+//! the duplicate u32 values of a DUPSORT database milli occasionally considers.
+//!
+//! It is not a DUPSORT database, heed doesn't open the databases with `MDB_DUPSORT`. The
+//! keys are the escaped word followed by the big-endian document id and the values are
+//! empty, the word is repeated in every key where LMDB would store it once per word, the
+//! pages and the cursors of a DUPSORT database differ, only the order of magnitude applies.
+//!
+//! The workload is adapted, the iterations read every posting, the jumps read the postings
+//! of their word, the bitmaps are rebuilt from the postings to be validated. The keys
+//! iteration reads the postings too, the words are not stored on their own.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Instant;
+
+use grenad::CompressionType;
+use heed::{Database, RoTxn, RwTxn};
+use roaring::RoaringBitmap;
+
+use crate::progress::{PhaseProgress, PROGRESS_STEP};
+use crate::{
+    check_value, platform, slo, value_compression, LmdbOptions, Phase, PhaseCpuTimes, PhaseTimings,
+    WorkloadOptions,
+};
+
+pub const POSSIBLE_LAYOUTS: &[&str] = &["bitmaps", "composite-keys"];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LmdbLayout {
+    /// One entry per word, its bitmap serialized as the value.
+    Bitmaps,
+    /// One entry per document id of a word, the values are empty, not a DUPSORT database.
+    CompositeKeys,
+}
+
+impl FromStr for LmdbLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<LmdbLayout, String> {
+        match s {
+            "bitmaps" => Ok(LmdbLayout::Bitmaps),
+            "composite-keys" => Ok(LmdbLayout::CompositeKeys),
+            otherwise => Err(format!(
+                "invalid LMDB layout {:?}, expected bitmaps or composite-keys",
+                otherwise
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LmdbLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LmdbLayout::Bitmaps => f.write_str("bitmaps"),
+            LmdbLayout::CompositeKeys => f.write_str("composite-keys"),
+        }
+    }
+}
+
+/// The prefix of the postings of a word, the zero bytes are escaped as `0x00 0xFF`
+/// and the word is terminated by `0x00 0x01`, the prefixes of two words are never
+/// the prefix of one another and they are sorted like the words.
+fn word_prefix(word: &[u8]) -> Vec<u8> {
+    let mut prefix = Vec::with_capacity(word.len() + 2);
+    for &byte in word {
+        prefix.push(byte);
+        if byte == 0 {
+            prefix.push(0xFF);
+        }
+    }
+    prefix.extend_from_slice(&[0x00, 0x01]);
+    prefix
+}
+
+/// The document id of a posting, at the end of its key.
+fn posting_docid(key: &[u8]) -> u32 {
+    let (_, docid) = key.split_at(key.len() - 4);
+    u32::from_be_bytes(docid.try_into().unwrap())
+}
+
+/// Appends the entry of a word in the layout, the words must be appended in order,
+/// the bitmaps are compressed in the buffer first.
+pub fn append(
+    options: &LmdbOptions,
+    database: Database,
+    wtxn: &mut RwTxn,
+    word: &[u8],
+    bitmap: &[u8],
+    buffer: &mut Vec<u8>,
+) -> anyhow::Result<()> {
+    match options.lmdb_layout {
+        LmdbLayout::Bitmaps => match options.lmdb_value_compression {
+            CompressionType::None => database.append(wtxn, word, bitmap)?,
+            compression => {
+                value_compression::compress(compression, bitmap, buffer)?;
+                database.append(wtxn, word, buffer)?;
+            }
+        },
+        LmdbLayout::CompositeKeys => {
+            let mut key = word_prefix(word);
+            let len = key.len();
+            for docid in RoaringBitmap::deserialize_from(bitmap)? {
+                key.truncate(len);
+                key.extend_from_slice(&docid.to_be_bytes());
+                database.append(wtxn, &key, &[])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Evaluates the composite keys layout, the phases are the ones of the bitmaps layout.
+pub fn test_composite_keys(
+    rtxn: &RoTxn,
+    database: Database,
+    words: &[Vec<u8>],
+    lookups: &[usize],
+    workload: &WorkloadOptions,
+    max_bitmap_len: Option<u64>,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<PhaseTimings> {
+    let mut cpu = PhaseCpuTimes::default();
+    let mut iter_elapsed = None;
+    if workload.phases.contains(&Phase::Iter) {
+        progress.start_phase("iterating", words.len() as u64);
+        let cpu_before = platform::thread_cpu_time();
+        let before_iter = Instant::now();
+        iterate(rtxn, database, words, workload, max_bitmap_len, true, progress)?;
+        iter_elapsed = Some(before_iter.elapsed());
+        cpu.iter = platform::thread_cpu_time_since(cpu_before);
+    }
+
+    let mut keys_iter_elapsed = None;
+    if workload.phases.contains(&Phase::KeysIter) {
+        progress.start_phase("iterating over keys", words.len() as u64);
+        let cpu_before = platform::thread_cpu_time();
+        let before_iter = Instant::now();
+        iterate(rtxn, database, words, workload, max_bitmap_len, false, progress)?;
+        keys_iter_elapsed = Some(before_iter.elapsed());
+        cpu.keys_iter = platform::thread_cpu_time_since(cpu_before);
+    }
+
+    let mut jump_elapsed = None;
+    let mut slo = None;
+    if workload.phases.contains(&Phase::Jump) {
+        progress.start_phase("jumping", lookups.len() as u64);
+        let timed_seeks = !workload.slo.is_empty();
+        let mut latencies = Vec::with_capacity(if timed_seeks { lookups.len() } else { 0 });
+        let (mut bitmap, mut buffer, mut expected) = (RoaringBitmap::new(), Vec::new(), Vec::new());
+        let cpu_before = platform::thread_cpu_time();
+        let before_jump = Instant::now();
+        for (n, &index) in (0..).zip(lookups) {
+            if n % PROGRESS_STEP == 0 {
+                progress.set_position(n);
+            }
+            let before_seek = timed_seeks.then(Instant::now);
+            bitmap.clear();
+            for result in database.prefix_iter(rtxn, &word_prefix(&words[index]))? {
+                let (k, _) = result?;
+                bitmap.insert(posting_docid(k));
+            }
+            if let Some(before_seek) = before_seek {
+                latencies.push(before_seek.elapsed());
+            }
+            if workload.validate.should_validate(n) {
+                buffer.clear();
+                bitmap.serialize_into(&mut buffer)?;
+                check_value(workload, max_bitmap_len, index, &buffer, &mut expected);
+            }
+        }
+        jump_elapsed = Some(before_jump.elapsed());
+        cpu.jump = platform::thread_cpu_time_since(cpu_before);
+        slo = slo::evaluate(&workload.slo, &mut latencies);
+    }
+
+    progress.finish();
+
+    Ok(PhaseTimings {
+        iter_time: iter_elapsed,
+        keys_iter_time: keys_iter_elapsed,
+        jump_time: jump_elapsed,
+        exact_get_time: None,
+        slo,
+        warm_cold: None,
+        seeks: None,
+        cpu,
+    })
+}
+
+/// Iterates over every posting, the postings are grouped by word and the bitmaps
+/// of the words are rebuilt and validated when asked to.
+fn iterate(
+    rtxn: &RoTxn,
+    database: Database,
+    words: &[Vec<u8>],
+    workload: &WorkloadOptions,
+    max_bitmap_len: Option<u64>,
+    rebuild: bool,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<()> {
+    let (mut bitmap, mut buffer, mut expected) = (RoaringBitmap::new(), Vec::new(), Vec::new());
+    let mut finish_word = |index: usize, bitmap: &mut RoaringBitmap| -> anyhow::Result<()> {
+        if rebuild && workload.validate.should_validate(index as u64) {
+            buffer.clear();
+            bitmap.serialize_into(&mut buffer)?;
+            check_value(workload, max_bitmap_len, index, &buffer, &mut expected);
+        }
+        bitmap.clear();
+        if index as u64 % PROGRESS_STEP == 0 {
+            progress.set_position(index as u64);
+        }
+        Ok(())
+    };
+
+    let mut index = 0;
+    let mut prefix = words.first().map(|word| word_prefix(word)).unwrap_or_default();
+    for result in database.iter(rtxn)? {
+        let (k, _) = result?;
+        // The words without postings have no entry, they are skipped.
+        while !k.starts_with(&prefix) {
+            finish_word(index, &mut bitmap)?;
+            index += 1;
+            assert!(index < words.len(), "the posting {:?} is not one of a word", k);
+            prefix = word_prefix(&words[index]);
+        }
+        if rebuild {
+            bitmap.insert(posting_docid(k));
+        }
+    }
+    for index in index..words.len() {
+        finish_word(index, &mut bitmap)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_prefixes_are_sorted_like_the_words() {
+        let mut words = vec![
+            &b""[..],
+            b"\x00",
+            b"\x00\x00",
+            b"\x00\x01",
+            b"a",
+            b"a\x00",
+            b"a\x00b",
+            b"a\x01",
+            b"ab",
+        ];
+        words.sort_unstable();
+        let prefixes: Vec<_> = words.iter().map(|word| word_prefix(word)).collect();
+        for (window, words) in prefixes.windows(2).zip(words.windows(2)) {
+            assert!(window[0] < window[1], "{:?} >= {:?}", words[0], words[1]);
+            assert!(!window[1].starts_with(&window[0]), "{:?} prefixes {:?}", words[0], words[1]);
+        }
+    }
+
+    #[test]
+    fn composite_keys_keep_their_docid() {
+        let mut key = word_prefix(b"a\x00b");
+        key.extend_from_slice(&42u32.to_be_bytes());
+        assert!(key.starts_with(&word_prefix(b"a\x00b")));
+        assert_eq!(posting_docid(&key), 42);
+    }
+}
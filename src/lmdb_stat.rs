@@ -0,0 +1,97 @@
+//! The statistics of an LMDB environment read from the meta pages of its `data.mdb`,
+//! heed doesn't expose them.
+//!
+//! LMDB uses the page size of the operating system, it can't be configured, the page
+//! size is recorded with the LMDB results to compare them with the grenad files of the
+//! same block size. The meta pages are read with the layout of the 64-bit platforms.
+//!
+//! The size of an environment is the size of its used pages, up to the last page of its
+//! latest transaction, not the size of its map nor of its `data.mdb`, the overhead of the
+//! B-tree is compared with the immutable blocks of the grenad file of the same entries.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use anyhow::Context;
+
+/// The magic number of the LMDB meta pages.
+const MAGIC: u32 = 0xBEEF_C0DE;
+/// The size of the page header, before the meta.
+const PAGE_HEADER_SIZE: usize = 16;
+/// The size of the meta, up to its transaction id.
+const META_SIZE: usize = PAGE_HEADER_SIZE + 136;
+/// The offset of the page size, stored in the padding of the free pages database.
+const PAGE_SIZE_OFFSET: usize = PAGE_HEADER_SIZE + 24;
+/// The offset of the main database, the unnamed one.
+const MAIN_DB_OFFSET: usize = PAGE_SIZE_OFFSET + 48;
+/// The offset of the last page used, after the free pages and the main databases.
+const LAST_PAGE_OFFSET: usize = MAIN_DB_OFFSET + 48;
+const TXN_ID_OFFSET: usize = LAST_PAGE_OFFSET + 8;
+
+#[derive(Debug, Copy, Clone)]
+pub struct LmdbStat {
+    pub page_size: u32,
+    /// The depth of the B-tree of the main database.
+    pub depth: u16,
+    pub branch_pages: u64,
+    pub leaf_pages: u64,
+    pub overflow_pages: u64,
+    /// The last page used by the environment, the free pages included.
+    pub last_page: u64,
+}
+
+impl LmdbStat {
+    /// The size of the used pages, the meta pages included.
+    pub fn used_size(&self) -> u64 {
+        (self.last_page + 1) * self.page_size as u64
+    }
+}
+
+/// Reads the statistics of the environment from the latest of its two meta pages.
+pub fn read(env_path: &Path) -> anyhow::Result<LmdbStat> {
+    let path = env_path.join("data.mdb");
+    let mut file =
+        File::open(&path).with_context(|| format!("while opening {}", path.display()))?;
+    let (first, txn_id) = read_meta(&mut file, 0).with_context(|| path.display().to_string())?;
+    let (second, second_txn_id) =
+        read_meta(&mut file, first.page_size as u64).with_context(|| path.display().to_string())?;
+    Ok(if second_txn_id > txn_id { second } else { first })
+}
+
+fn read_meta(file: &mut File, offset: u64) -> anyhow::Result<(LmdbStat, u64)> {
+    let mut page = [0; META_SIZE];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut page)?;
+    let u16_at = |offset: usize| u16::from_ne_bytes([page[offset], page[offset + 1]]);
+    let u32_at = |offset: usize| u32::from_ne_bytes(page[offset..offset + 4].try_into().unwrap());
+    let u64_at = |offset: usize| u64::from_ne_bytes(page[offset..offset + 8].try_into().unwrap());
+
+    anyhow::ensure!(u32_at(PAGE_HEADER_SIZE) == MAGIC, "the meta page at {} is invalid", offset);
+    let stat = LmdbStat {
+        page_size: u32_at(PAGE_SIZE_OFFSET),
+        depth: u16_at(MAIN_DB_OFFSET + 6),
+        branch_pages: u64_at(MAIN_DB_OFFSET + 8),
+        leaf_pages: u64_at(MAIN_DB_OFFSET + 16),
+        overflow_pages: u64_at(MAIN_DB_OFFSET + 24),
+        last_page: u64_at(LAST_PAGE_OFFSET),
+    };
+    Ok((stat, u64_at(TXN_ID_OFFSET)))
+}
+
+impl fmt::Display for LmdbStat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "LMDB page size: {} bytes, depth: {}, {} branch, {} leaf and {} overflow pages, \
+             {} bytes used",
+            self.page_size,
+            self.depth,
+            self.branch_pages,
+            self.leaf_pages,
+            self.overflow_pages,
+            self.used_size()
+        )
+    }
+}
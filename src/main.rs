@@ -1,27 +1,136 @@
-use std::fs::{self, File, OpenOptions};
-use std::io::{self, BufReader, BufWriter, Cursor, ErrorKind, Read};
-use std::num::NonZeroUsize;
+use std::cmp::Ordering;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Cursor, ErrorKind};
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::time::{Duration, Instant};
-use std::{iter, str};
+use std::{fmt, thread};
 
 use anyhow::Context;
-use clap::Parser;
-use gabble::Gabble;
-use grenad::{CompressionType, Reader, ReaderCursor, WriterBuilder};
+use clap::{AppSettings, IntoApp, Parser};
+use clap_generate::generators::{Bash, Elvish, Fish, PowerShell, Zsh};
+use grenad::{CompressionType, Reader, ReaderCursor, Writer, WriterBuilder};
 use heed::{Database, Env, EnvOpenOptions, RoTxn};
 use indicatif::{ParallelProgressIterator, ProgressBar, ProgressIterator, ProgressStyle};
-use rand::rngs::StdRng;
-use rand::seq::SliceRandom;
-use rand::{Rng, SeedableRng};
 use rayon::prelude::*;
-use roaring::RoaringBitmap;
+
+use crate::arena::Words;
+use crate::args::IndexLevels;
+use crate::assertion::Assertion;
+use crate::at_rest::StudyRow;
+use crate::background::{BackgroundLoad, BackgroundWorkload};
+use crate::boundary::Profile;
+use crate::checksum::{ChecksumKind, ChecksumWriter};
+use crate::compressed::InputFile;
+use crate::cpu_limit::CpuLimit;
+use crate::datagen::{Charset, KeyProfile, ValueGenerator, WordShape};
+use crate::experiment::ExperimentId;
+use crate::extract::{self, Extracted};
+use crate::faults::{FaultInjector, FaultSpec};
+use crate::heap::HeapBackendKind;
+use crate::lmdb_layout::LmdbLayout;
+use crate::manifest::Manifest;
+use crate::merge::MergeFunction;
+use crate::metrics::Metric;
+use crate::multi_db::DatabaseSpec;
+use crate::params::Parameters;
+use crate::platform::{CpuTime, Telemetry};
+use crate::progress::{PhaseProgress, PROGRESS_STEP};
+use crate::read_method::{OpenOptions, ReadMethod, ReadSeek};
+use crate::recommend::Observation;
+use crate::reference::ReferenceDataset;
+use crate::retention::KeepFiles;
+use crate::sampling::GridSampling;
+use crate::scaling::ScalingPoint;
+use crate::seeks::{SeekCounter, SeekCounts, SeekLog};
+use crate::sentinel::SentinelRun;
+use crate::significance::Comparison;
+use crate::slo::{Slo, SloOutcome};
+use crate::soak::SoakOptions;
+use crate::sort::SortExpression;
+use crate::sorter::SorterParameters;
+use crate::source_order::{SortedKeys, SourceOrder};
+use crate::stats::DatasetStats;
+use crate::sweep::{Dataset, FileDataset, KeySample, RandomDataset};
+use crate::throttle::{Throttle, ThrottledReader};
+use crate::touch::{TouchLog, TouchTracker, WarmColdSplit};
+use crate::trace::Trace;
+
+mod arena;
+mod args;
+mod assertion;
+mod async_test;
+mod at_rest;
+mod background;
+mod boundary;
+mod budget;
+mod cache_budget;
+mod checkpoint;
+mod checksum;
+mod compressed;
+mod config_list;
+mod cpu_limit;
+mod critcmp;
+mod cross;
+mod datagen;
+mod download;
+mod energy;
+mod experiment;
+mod extract;
+mod extract_cache;
+mod faults;
+mod fst_log;
+mod heap;
+mod ingestion;
+mod json;
+mod lmdb_layout;
+mod lmdb_stat;
+mod manifest;
+mod merge;
+mod metrics;
+mod multi_db;
+mod multi_process;
+mod params;
+mod parquet_backend;
+mod platform;
+mod prefix;
+mod progress;
+mod read_method;
+mod recommend;
+mod reference;
+mod refine;
+mod report;
+mod retention;
+mod sampling;
+mod scaling;
+mod schema;
+mod seekable;
+mod seeks;
+mod sentinel;
+mod significance;
+mod slo;
+mod soak;
+mod sort;
+mod sorter;
+mod source_order;
+mod stats;
+mod stream;
+mod summary;
+mod support;
+mod sweep;
+mod throttle;
+mod tidy;
+mod touch;
+mod trace;
+mod value_compression;
+mod versions;
 
 const FIVE_GIB: usize = 5 * 1024 * 1024 * 1024;
-const MAX_BITMAP_LEN: usize = 116_000_000;
-const POSSIBLE_READ_METHODS: &[&str] =
-    &["direct", "read-to-vec", "bufreader", "memory-mapped", "memory-mapped-bufreader"];
-const POSSIBLE_SORT_METHODS: &[&str] = &["iter-only", "iter-and-jump", "jump-only"];
+const RETRY_BASE_BACKOFF: Duration = Duration::from_millis(100);
+const POSSIBLE_PHASES: &[&str] = &["iter", "keys-iter", "jump", "exact-get"];
+const POSSIBLE_SHELLS: &[&str] = &["bash", "elvish", "fish", "powershell", "zsh"];
 
 #[derive(Parser)]
 #[clap(version = "1.0", author = "Kevin K. <kbknapp@gmail.com>")]
@@ -34,6 +143,22 @@ struct Opts {
     #[clap(long)]
     folder: PathBuf,
 
+    /// The maximum cardinality of the generated bitmaps, it is recorded in
+    /// the manifest of the generated files and checked when reading them.
+    #[clap(long, default_value = "116000000")]
+    max_bitmap_len: u64,
+
+    /// The folder where the datasets downloaded by URL are cached,
+    /// the `downloads` folder of the `--folder` by default.
+    #[clap(long)]
+    download_cache: Option<PathBuf>,
+
+    #[clap(flatten)]
+    generation: GenerationOptions,
+
+    #[clap(flatten)]
+    words: WordOptions,
+
     #[clap(subcommand)]
     subcommand: SubCommand,
 }
@@ -52,19 +177,16 @@ enum SubCommand {
         #[clap(long, default_value = "10000")]
         entry_count: u64,
 
-        #[clap(
-            long,
-            default_value = "direct",
-            possible_values = POSSIBLE_READ_METHODS,
-        )]
-        read_method: String,
+        #[clap(flatten)]
+        keys: KeyOptions,
 
-        #[clap(
-            long,
-            default_value = "jump-only",
-            possible_values = POSSIBLE_SORT_METHODS,
-        )]
-        sort_by: String,
+        /// Generate a reference dataset, the same on every machine, it replaces the
+        /// seed, the entry count, the key profile, the words and the values.
+        #[clap(long, possible_values = reference::POSSIBLE_DATASETS)]
+        dataset: Option<ReferenceDataset>,
+
+        #[clap(flatten)]
+        sweep: SweepOptions,
     },
     /// Run the extended test suite which consist in retrieving a big list of
     /// key-value pairs from a provided file, storing them with different parameters
@@ -75,50 +197,112 @@ enum SubCommand {
         #[clap(long, default_value = "42")]
         seed: u64,
 
-        /// The grenad file to read entries from to execute the extended suite of tests.
+        /// The grenad file to read entries from to execute the extended suite of tests,
+        /// a `.grd.gz` or `.grd.zst` file is decompressed first, a URL is downloaded.
         #[clap(long)]
         file: PathBuf,
 
+        /// The parameters the file was written with, e.g. `Snappy.2.4096.16`, added to
+        /// the grid and marked as current in the reports, read from the name of the file
+        /// when it is named like the generated ones, grenad doesn't record them in it.
+        #[clap(long)]
+        input_params: Option<Parameters>,
+
+        /// Look up this many keys sampled from the file in place of extracting all of
+        /// them, for the files too large for their keys to be held in memory, the files
+        /// are still generated with every entry. The keys of the iterations are then
+        /// not compared, the exact gets and the heap baseline need every key.
+        #[clap(long)]
+        lookup_sample_size: Option<NonZeroUsize>,
+
+        /// Extract the words of the file again in place of reading them from the cache of
+        /// the previous runs, in the `extractions` folder, keyed by the hash of the file.
+        #[clap(long)]
+        no_extraction_cache: bool,
+
+        #[clap(flatten)]
+        source: SourceOptions,
+
+        #[clap(flatten)]
+        sweep: SweepOptions,
+    },
+    /// Evaluate the grenad files of the folder generated by a previous run, or on
+    /// another machine, the parameters of the files are read from their names.
+    ///
+    /// The files must contain the same entries, the words of the first one are
+    /// extracted and the values of the files are only validated against their manifest.
+    EvaluateFolder {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
         #[clap(
             long,
             default_value = "direct",
-            possible_values = POSSIBLE_READ_METHODS,
+            possible_values = read_method::POSSIBLE_READ_METHODS,
+            validator = platform::supported_read_method,
         )]
-        read_method: String,
+        read_method: ReadMethod,
 
-        #[clap(
-            long,
-            default_value = "jump-only",
-            possible_values = POSSIBLE_SORT_METHODS,
-        )]
-        sort_by: String,
+        #[clap(flatten)]
+        sort: SortOptions,
+
+        /// Also write the results of the evaluations as a self-contained HTML report.
+        #[clap(long)]
+        html_report: Option<PathBuf>,
+
+        /// Print every configuration as a JSON line on the standard output as soon as
+        /// it is evaluated, the `stream-results` schema describes the lines.
+        #[clap(long)]
+        stream_results: bool,
+
+        #[clap(flatten)]
+        evaluation: EvaluationOptions,
     },
     OneTest {
         #[clap(long, default_value = "42")]
         seed: u64,
 
-        /// The grenad file to read entries from to execute the extended suite of tests.
+        /// The grenad file to read entries from to execute the extended suite of tests,
+        /// a `.grd.gz` or `.grd.zst` file is decompressed first, a URL is downloaded.
         #[clap(long)]
         file: PathBuf,
 
+        /// The read method, `all` evaluates every supported one on the same file.
         #[clap(
             long,
             default_value = "direct",
-            possible_values = POSSIBLE_READ_METHODS,
+            possible_values = read_method::POSSIBLE_READ_METHODS_OR_ALL,
+            validator = platform::supported_read_method,
         )]
         read_method: String,
 
+        /// The parameters named like the generated files, e.g. `Snappy.2.4096.16`,
+        /// instead of the compression, index levels, block size and key interval.
+        #[clap(
+            long,
+            parse(try_from_str = parse_params_name),
+            conflicts_with_all = &["compression", "index-levels", "block-size", "index-key-interval"],
+        )]
+        params: Option<Parameters>,
+
         #[clap(long)]
         compression: Option<CompressionType>,
 
         #[clap(long)]
-        index_levels: u8,
+        index_levels: Option<u8>,
 
-        #[clap(long)]
-        block_size: usize,
+        /// The size of the blocks, e.g. `4096` or `4KiB`.
+        #[clap(long, parse(try_from_str = args::parse_size))]
+        block_size: Option<usize>,
 
         #[clap(long)]
-        index_key_interval: NonZeroUsize,
+        index_key_interval: Option<NonZeroUsize>,
+
+        #[clap(flatten)]
+        source: SourceOptions,
+
+        #[clap(flatten)]
+        evaluation: EvaluationOptions,
     },
     OneRandomTest {
         #[clap(long, default_value = "42")]
@@ -127,12 +311,16 @@ enum SubCommand {
         #[clap(long, default_value = "10000")]
         entry_count: u64,
 
+        #[clap(flatten)]
+        keys: KeyOptions,
+
         #[clap(
             long,
             default_value = "direct",
-            possible_values = POSSIBLE_READ_METHODS,
+            possible_values = read_method::POSSIBLE_READ_METHODS,
+            validator = platform::supported_read_method,
         )]
-        read_method: String,
+        read_method: ReadMethod,
 
         #[clap(long)]
         compression: Option<CompressionType>,
@@ -140,435 +328,2594 @@ enum SubCommand {
         #[clap(long)]
         index_levels: u8,
 
-        #[clap(long)]
+        /// The size of the blocks, e.g. `4096` or `4KiB`.
+        #[clap(long, parse(try_from_str = args::parse_size))]
         block_size: usize,
 
         #[clap(long)]
         index_key_interval: NonZeroUsize,
-    },
-    OneLmdbTest {
-        #[clap(long, default_value = "42")]
-        seed: u64,
 
-        /// The grenad file to read entries from to execute the extended suite of tests.
-        #[clap(long)]
-        file: PathBuf,
+        #[clap(flatten)]
+        evaluation: EvaluationOptions,
     },
-    OneRandomLmdbTest {
+    /// Evaluate the same parameters with a geometric series of entry counts,
+    /// to see how the timings and the file size scale with the dataset.
+    ScalingStudy {
         #[clap(long, default_value = "42")]
         seed: u64,
 
         #[clap(long, default_value = "10000")]
-        entry_count: u64,
-    },
-}
-
-#[derive(Debug, Copy, Clone)]
-struct Parameters {
-    compression: CompressionType,
-    index_levels: u8,
-    block_size: usize,
-    index_key_interval: NonZeroUsize,
-}
+        min_entry_count: NonZeroU64,
 
-#[derive(Debug, Copy, Clone)]
-struct Results {
-    iter_time: Duration,
-    jump_time: Duration,
-}
+        #[clap(long, default_value = "100000000")]
+        max_entry_count: u64,
 
-fn main() -> anyhow::Result<()> {
-    let Opts { verbose, folder, subcommand } = Opts::try_parse()?;
+        /// The ratio between two successive entry counts.
+        #[clap(long, default_value = "10", validator = scaling::validate_growth_factor)]
+        growth_factor: u64,
 
-    match subcommand {
-        SubCommand::ExtendedRandomTests { seed, entry_count, read_method, sort_by } => {
-            println!("generating random words...");
-            let mut rng = StdRng::seed_from_u64(seed);
-            let pb = ProgressBar::new(entry_count)
-                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
-            let mut words: Vec<_> = iter::repeat_with(|| {
-                Gabble::new().with_length(rng.gen_range(3..=15)).generate(&mut rng)
-            })
-            .take(entry_count as usize)
-            .progress_with(pb)
-            .collect();
-            words.sort_unstable();
-            words.dedup();
-            println!("{} unique words generated!", words.len());
+        #[clap(flatten)]
+        keys: KeyOptions,
 
-            let compressions =
-                vec![CompressionType::None, CompressionType::Snappy, CompressionType::Lz4];
-            let index_levels = vec![0, 1, 2, 3];
-            let block_sizes = vec![8 * 1024, 4 * 1024, 2 * 1024, 1 * 1024, 512];
-            let index_key_intervals: Vec<_> =
-                vec![32, 24, 16, 12, 8, 4, 2].into_iter().filter_map(NonZeroUsize::new).collect();
-
-            let mut parameters = Vec::new();
-            for &compression in &compressions {
-                for &index_levels in &index_levels {
-                    for &block_size in &block_sizes {
-                        for &index_key_interval in &index_key_intervals {
-                            parameters.push(Parameters {
-                                compression,
-                                index_levels,
-                                block_size,
-                                index_key_interval,
-                            });
-                        }
-                    }
-                }
-            }
+        #[clap(
+            long,
+            default_value = "direct",
+            possible_values = read_method::POSSIBLE_READ_METHODS,
+            validator = platform::supported_read_method,
+        )]
+        read_method: ReadMethod,
 
-            println!("generating the test files...");
-            let pb = ProgressBar::new(parameters.len() as u64)
-                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
-            let params_files = parameters
-                .into_par_iter()
-                .map(|params| {
-                    let mut rng = StdRng::seed_from_u64(seed);
-                    random_generate_from_params(&mut rng, &folder, &words, &params)
-                        .map(|file| (params, file))
-                })
-                .progress_with(pb)
-                .collect::<anyhow::Result<Vec<_>, _>>()?;
+        #[clap(long)]
+        compression: Option<CompressionType>,
 
-            println!("evaluating the test files...");
-            let pb = ProgressBar::new(params_files.len() as u64)
-                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
-            let mut results = params_files
-                .into_par_iter()
-                .map(|(params, mut file)| {
-                    let mut rng = StdRng::seed_from_u64(seed);
-                    let (iter_elapsed, jump_elapsed) = match read_method.as_str() {
-                        "direct" => test_cursor(&mut rng, file, &words, entry_count)?,
-                        "read-to-vec" => {
-                            let mut bytes = Vec::new();
-                            file.read_to_end(&mut bytes)?;
-                            test_cursor(&mut rng, Cursor::new(bytes), &words, entry_count)?
-                        }
-                        "bufreader" => {
-                            test_cursor(&mut rng, BufReader::new(file), &words, entry_count)?
-                        }
-                        "memory-mapped" => {
-                            let map = unsafe { memmap2::Mmap::map(&file)? };
-                            test_cursor(&mut rng, Cursor::new(map), &words, entry_count)?
-                        }
-                        "memory-mapped-bufreader" => {
-                            let map = unsafe { memmap2::Mmap::map(&file)? };
-                            test_cursor(
-                                &mut rng,
-                                BufReader::new(Cursor::new(map)),
-                                &words,
-                                entry_count,
-                            )?
-                        }
-                        _ => unreachable!(),
-                    };
+        #[clap(long)]
+        index_levels: u8,
 
-                    Ok((params, iter_elapsed, jump_elapsed))
-                })
-                .progress_with(pb)
-                .collect::<anyhow::Result<Vec<_>>>()?;
+        /// The size of the blocks, e.g. `4096` or `4KiB`.
+        #[clap(long, parse(try_from_str = args::parse_size))]
+        block_size: usize,
 
-            match sort_by.as_str() {
-                "iter-only" => results.sort_unstable_by_key(|(_, iter, _)| *iter),
-                "iter-and-jump" => results.sort_unstable_by_key(|(_, iter, jump)| *iter + *jump),
-                "jump-only" => results.sort_unstable_by_key(|(_, _, jump)| *jump),
-                _ => unreachable!(),
-            }
+        #[clap(long)]
+        index_key_interval: NonZeroUsize,
 
-            for (params, iter_elapsed, jump_elapsed) in results {
-                println!("{:#?}", params);
-                println!("took {:.02?} to iterate over values", iter_elapsed);
-                println!("took {:.02?} to jump over values", jump_elapsed);
-                println!();
-            }
-        }
-        SubCommand::ExtendedTests { seed, file, read_method, sort_by } => {
-            let file =
-                File::open(&file).with_context(|| format!("while opening {}", file.display()))?;
-            let map = unsafe { memmap2::Mmap::map(&file)? };
-            let map = Cursor::new(&map);
-            let mut cursor = Reader::new(map)?.into_cursor()?;
+        #[clap(flatten)]
+        evaluation: EvaluationOptions,
+    },
+    /// Evaluate several block sizes with the same keys and a geometric series of
+    /// mean value sizes, to see where the best block size changes.
+    ValueSizeStudy {
+        #[clap(long, default_value = "42")]
+        seed: u64,
 
-            println!("extracting the words...");
-            let number_of_entries = cursor.len();
-            let pb = ProgressBar::new(number_of_entries)
-                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
-            let mut words = Vec::with_capacity(number_of_entries as usize);
-            while let Some((k, _)) = cursor.move_on_next()? {
-                let word = str::from_utf8(k)?.to_owned();
-                words.push(word);
-                pb.inc(1);
-            }
-            pb.finish_and_clear();
-            println!("{} unique words extracted!", words.len());
+        /// The number of entries, the largest values make the files huge.
+        #[clap(long, default_value = "1000")]
+        entry_count: u64,
 
-            let compressions =
-                vec![CompressionType::None, CompressionType::Snappy, CompressionType::Lz4];
-            let index_levels = vec![0, 1, 2, 3];
-            let block_sizes = vec![8 * 1024, 4 * 1024, 2 * 1024, 1 * 1024, 512];
-            let index_key_intervals: Vec<_> =
-                vec![32, 24, 16, 12, 8, 4, 2].into_iter().filter_map(NonZeroUsize::new).collect();
-
-            let mut parameters = Vec::new();
-            for &compression in &compressions {
-                for &index_levels in &index_levels {
-                    for &block_size in &block_sizes {
-                        for &index_key_interval in &index_key_intervals {
-                            parameters.push(Parameters {
-                                compression,
-                                index_levels,
-                                block_size,
-                                index_key_interval,
-                            });
-                        }
-                    }
-                }
-            }
+        #[clap(flatten)]
+        keys: KeyOptions,
 
-            println!("generating the test files...");
-            let pb = ProgressBar::new(parameters.len() as u64)
-                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
-            let params_files = parameters
-                .into_par_iter()
-                .map_with(cursor, |cursor, params| {
-                    generate_from_params(&folder, cursor, &params).map(|file| (params, file))
-                })
-                .progress_with(pb)
-                .collect::<anyhow::Result<Vec<_>, _>>()?;
+        /// The smallest mean size of the values, e.g. `16` or `1KiB`.
+        #[clap(long, default_value = "16", parse(try_from_str = args::parse_size))]
+        min_value_size: NonZeroU64,
 
-            println!("evaluating the test files...");
-            let pb = ProgressBar::new(params_files.len() as u64)
-                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
-            let mut results = params_files
-                .into_par_iter()
-                .map(|(params, mut file)| {
-                    let mut rng = StdRng::seed_from_u64(seed);
-                    let (iter_elapsed, jump_elapsed) = match read_method.as_str() {
-                        "direct" => test_cursor(&mut rng, file, &words, number_of_entries)?,
-                        "read-to-vec" => {
-                            let mut bytes = Vec::new();
-                            file.read_to_end(&mut bytes)?;
-                            test_cursor(&mut rng, Cursor::new(bytes), &words, number_of_entries)?
-                        }
-                        "bufreader" => {
-                            test_cursor(&mut rng, BufReader::new(file), &words, number_of_entries)?
-                        }
-                        "memory-mapped" => {
-                            let map = unsafe { memmap2::Mmap::map(&file)? };
-                            test_cursor(&mut rng, Cursor::new(map), &words, number_of_entries)?
-                        }
-                        "memory-mapped-bufreader" => {
-                            let map = unsafe { memmap2::Mmap::map(&file)? };
-                            test_cursor(
-                                &mut rng,
-                                BufReader::new(Cursor::new(map)),
-                                &words,
-                                number_of_entries,
-                            )?
-                        }
-                        _ => unreachable!(),
-                    };
+        /// The largest mean size of the values, e.g. `1MiB`.
+        #[clap(long, default_value = "1MiB", parse(try_from_str = args::parse_size))]
+        max_value_size: u64,
 
-                    Ok((params, iter_elapsed, jump_elapsed))
-                })
-                .progress_with(pb)
-                .collect::<anyhow::Result<Vec<_>>>()?;
+        /// The ratio between two successive mean value sizes.
+        #[clap(long, default_value = "4", validator = scaling::validate_growth_factor)]
+        growth_factor: u64,
 
-            match sort_by.as_str() {
-                "iter-only" => results.sort_unstable_by_key(|(_, iter, _)| *iter),
-                "iter-and-jump" => results.sort_unstable_by_key(|(_, iter, jump)| *iter + *jump),
-                "jump-only" => results.sort_unstable_by_key(|(_, _, jump)| *jump),
-                _ => unreachable!(),
-            }
+        #[clap(
+            long,
+            default_value = "direct",
+            possible_values = read_method::POSSIBLE_READ_METHODS,
+            validator = platform::supported_read_method,
+        )]
+        read_method: ReadMethod,
 
-            for (params, iter_elapsed, jump_elapsed) in results {
-                println!("{:#?}", params);
-                println!("took {:.02?} to iterate over values", iter_elapsed);
-                println!("took {:.02?} to jump over values", jump_elapsed);
-                println!();
-            }
-        }
-        SubCommand::OneTest {
-            seed,
-            file,
-            read_method,
-            compression,
-            index_levels,
-            block_size,
-            index_key_interval,
-        } => {
-            let file =
-                File::open(&file).with_context(|| format!("while opening {}", file.display()))?;
-            let map = unsafe { memmap2::Mmap::map(&file)? };
-            let map = Cursor::new(&map);
-            let mut cursor = Reader::new(map)?.into_cursor()?;
+        #[clap(long)]
+        compression: Option<CompressionType>,
 
-            println!("extracting the words...");
-            let number_of_entries = cursor.len();
-            let pb = ProgressBar::new(number_of_entries)
-                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
-            let mut words = Vec::with_capacity(number_of_entries as usize);
-            while let Some((k, _)) = cursor.move_on_next()? {
-                let word = str::from_utf8(k)?.to_owned();
-                words.push(word);
-                pb.inc(1);
-            }
-            pb.finish_and_clear();
-            println!("{} unique words extracted!", words.len());
+        #[clap(long)]
+        index_levels: u8,
 
-            let params = Parameters {
-                compression: compression.unwrap_or_default(),
-                index_levels,
-                block_size,
-                index_key_interval,
-            };
-            let mut file = generate_from_params(&folder, &mut cursor, &params)?;
-
-            let mut rng = StdRng::seed_from_u64(seed);
-            let (iter_elapsed, jump_elapsed) = match read_method.as_str() {
-                "direct" => test_cursor(&mut rng, file, &words, number_of_entries)?,
-                "read-to-vec" => {
-                    let mut bytes = Vec::new();
-                    file.read_to_end(&mut bytes)?;
-                    test_cursor(&mut rng, Cursor::new(bytes), &words, number_of_entries)?
-                }
-                "bufreader" => {
-                    test_cursor(&mut rng, BufReader::new(file), &words, number_of_entries)?
-                }
-                "memory-mapped" => {
-                    let map = unsafe { memmap2::Mmap::map(&file)? };
-                    test_cursor(&mut rng, Cursor::new(map), &words, number_of_entries)?
-                }
-                "memory-mapped-bufreader" => {
-                    let map = unsafe { memmap2::Mmap::map(&file)? };
-                    test_cursor(
-                        &mut rng,
-                        BufReader::new(Cursor::new(map)),
-                        &words,
-                        number_of_entries,
-                    )?
-                }
-                _ => unreachable!(),
-            };
+        /// The comma-separated block sizes evaluated for every value size.
+        #[clap(
+            long,
+            default_value = "512,1KiB,2KiB,4KiB,8KiB",
+            use_delimiter = true,
+            parse(try_from_str = args::parse_size),
+        )]
+        block_sizes: Vec<usize>,
 
-            println!("{:#?}", params);
-            println!("took {:.02?} to iterate over values", iter_elapsed);
-            println!("took {:.02?} to jump over values", jump_elapsed);
-            println!();
-        }
-        SubCommand::OneRandomTest {
-            seed,
-            entry_count,
-            read_method,
-            compression,
-            index_levels,
-            block_size,
-            index_key_interval,
-        } => {
-            println!("generating random words...");
-            let mut rng = StdRng::seed_from_u64(seed);
-            let pb = ProgressBar::new(entry_count)
-                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
-            let mut words: Vec<_> = iter::repeat_with(|| {
-                Gabble::new().with_length(rng.gen_range(3..=15)).generate(&mut rng)
-            })
-            .take(entry_count as usize)
-            .progress_with(pb)
-            .collect();
-            words.sort_unstable();
-            words.dedup();
-            println!("{} unique words generated!", words.len());
+        #[clap(long)]
+        index_key_interval: NonZeroUsize,
 
-            let params = Parameters {
-                compression: compression.unwrap_or_default(),
-                index_levels,
-                block_size,
-                index_key_interval,
-            };
-            let mut file = random_generate_from_params(&mut rng, &folder, &words, &params)?;
-
-            let (iter_elapsed, jump_elapsed) = match read_method.as_str() {
-                "direct" => test_cursor(&mut rng, file, &words, entry_count)?,
-                "read-to-vec" => {
-                    let mut bytes = Vec::new();
-                    file.read_to_end(&mut bytes)?;
-                    test_cursor(&mut rng, Cursor::new(bytes), &words, entry_count)?
-                }
-                "bufreader" => test_cursor(&mut rng, BufReader::new(file), &words, entry_count)?,
-                "memory-mapped" => {
-                    let map = unsafe { memmap2::Mmap::map(&file)? };
-                    test_cursor(&mut rng, Cursor::new(map), &words, entry_count)?
-                }
-                "memory-mapped-bufreader" => {
-                    let map = unsafe { memmap2::Mmap::map(&file)? };
-                    test_cursor(&mut rng, BufReader::new(Cursor::new(map)), &words, entry_count)?
-                }
-                _ => unreachable!(),
-            };
+        #[clap(flatten)]
+        evaluation: EvaluationOptions,
+    },
+    /// Run the jumps of several configurations limited by a memory cgroup, for a geometric
+    /// series of memory budgets, to see how much page cache a configuration needs to be fast.
+    CacheBudgetStudy {
+        #[clap(long, default_value = "42")]
+        seed: u64,
 
-            println!("{:#?}", params);
-            println!("took {:.02?} to iterate over values", iter_elapsed);
-            println!("took {:.02?} to jump over values", jump_elapsed);
-            println!();
-        }
-        SubCommand::OneLmdbTest { seed, file } => {
-            let file =
-                File::open(&file).with_context(|| format!("while opening {}", file.display()))?;
-            let map = unsafe { memmap2::Mmap::map(&file)? };
-            let map = Cursor::new(&map);
-            let mut cursor = Reader::new(map)?.into_cursor()?;
+        #[clap(long, default_value = "1000000")]
+        entry_count: u64,
 
-            println!("extracting the words...");
-            let number_of_entries = cursor.len();
-            let pb = ProgressBar::new(number_of_entries)
-                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
-            let mut words = Vec::with_capacity(number_of_entries as usize);
-            while let Some((k, _)) = cursor.move_on_next()? {
-                let word = str::from_utf8(k)?.to_owned();
-                words.push(word);
-                pb.inc(1);
-            }
-            pb.finish_and_clear();
-            println!("{} unique words extracted!", words.len());
+        #[clap(flatten)]
+        keys: KeyOptions,
 
-            let env = generate_lmdb(&folder, &mut cursor)?;
-            let database = env.open_database(None)?.unwrap();
-            let rtxn = env.read_txn()?;
+        /// The comma-separated configurations, e.g. `Snappy.2.4096.16,None.1.8192.32`.
+        #[clap(long, required = true, use_delimiter = true)]
+        configurations: Vec<Parameters>,
 
-            let mut rng = StdRng::seed_from_u64(seed);
-            let (iter_elapsed, jump_elapsed) =
-                test_lmdb(&mut rng, &rtxn, database, &words, number_of_entries)?;
+        /// The cgroup v2 the budgeted cgroups are created in, e.g. `/sys/fs/cgroup/grenad`,
+        /// the memory controller must be enabled in its `cgroup.subtree_control`.
+        #[clap(long)]
+        cgroup: PathBuf,
 
-            println!("took {:.02?} to iterate over values", iter_elapsed);
-            println!("took {:.02?} to jump over values", jump_elapsed);
-            println!();
-        }
-        SubCommand::OneRandomLmdbTest { seed, entry_count } => {
-            println!("generating random words...");
-            let mut rng = StdRng::seed_from_u64(seed);
-            let pb = ProgressBar::new(entry_count)
-                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
-            let mut words: Vec<_> = iter::repeat_with(|| {
-                Gabble::new().with_length(rng.gen_range(3..=15)).generate(&mut rng)
-            })
-            .take(entry_count as usize)
-            .progress_with(pb)
-            .collect();
-            words.sort_unstable();
-            words.dedup();
-            println!("{} unique words generated!", words.len());
+        /// The smallest memory budget, e.g. `16MiB`.
+        #[clap(long, default_value = "16MiB", parse(try_from_str = args::parse_size))]
+        min_budget: NonZeroU64,
 
-            let env = random_generate_lmdb(&mut rng, &folder, &words)?;
-            let database = env.open_database(None)?.unwrap();
-            let rtxn = env.read_txn()?;
+        /// The largest memory budget, the reference of the smaller ones, e.g. `1GiB`.
+        #[clap(long, default_value = "1GiB", parse(try_from_str = args::parse_size))]
+        max_budget: u64,
 
-            let (iter_elapsed, jump_elapsed) =
-                test_lmdb(&mut rng, &rtxn, database, &words, entry_count)?;
+        /// The ratio between two successive memory budgets.
+        #[clap(long, default_value = "2", validator = scaling::validate_growth_factor)]
+        growth_factor: u64,
 
-            println!("took {:.02?} to iterate over values", iter_elapsed);
-            println!("took {:.02?} to jump over values", jump_elapsed);
+        /// The slowdown tolerated over the latency with the largest budget, in percent,
+        /// the budget a configuration needs is the smallest one within it.
+        #[clap(long, default_value = "10")]
+        tolerance: f64,
+    },
+    /// Compare the compression of the blocks by grenad with the uncompressed file
+    /// compressed at rest, by the filesystem of the folder when it compresses the
+    /// files (btrfs, ZFS) and through the zstd seekable format.
+    CompressionStudy {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        #[clap(long, default_value = "10000")]
+        entry_count: u64,
+
+        #[clap(flatten)]
+        keys: KeyOptions,
+
+        /// The read method of the grenad files, the seekable copy is read through its own.
+        #[clap(
+            long,
+            default_value = "memory-mapped",
+            possible_values = read_method::POSSIBLE_READ_METHODS,
+            validator = platform::supported_read_method,
+        )]
+        read_method: ReadMethod,
+
+        /// The comma-separated compressions of the blocks compared with the uncompressed file.
+        #[clap(long, default_value = "snappy,lz4,zstd", use_delimiter = true)]
+        compressions: Vec<CompressionType>,
+
+        #[clap(long, default_value = "0")]
+        index_levels: u8,
+
+        /// The size of the blocks, e.g. `4096` or `4KiB`.
+        #[clap(long, default_value = "8KiB", parse(try_from_str = args::parse_size))]
+        block_size: usize,
+
+        #[clap(long, default_value = "16")]
+        index_key_interval: NonZeroUsize,
+
+        #[clap(flatten)]
+        evaluation: EvaluationOptions,
+    },
+    /// Execute the random jumps on a single configuration continuously for a long time,
+    /// the throughput, the latency percentiles and the resident memory are logged
+    /// periodically, the run exits with the code 2 when they drifted.
+    Soak {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        #[clap(long, default_value = "10000")]
+        entry_count: u64,
+
+        #[clap(
+            long,
+            default_value = "memory-mapped",
+            possible_values = &["direct", "bufreader", "memory-mapped"],
+        )]
+        read_method: ReadMethod,
+
+        #[clap(long)]
+        compression: Option<CompressionType>,
+
+        #[clap(long)]
+        index_levels: u8,
+
+        /// The size of the blocks, e.g. `4096` or `4KiB`.
+        #[clap(long, parse(try_from_str = args::parse_size))]
+        block_size: usize,
+
+        #[clap(long)]
+        index_key_interval: NonZeroUsize,
+
+        /// How long the jumps are executed, e.g. `90s`, `30m` or `8h`.
+        #[clap(long, parse(try_from_str = args::parse_duration))]
+        duration: Duration,
+
+        /// The interval between two log lines, every interval is compared with the first one.
+        #[clap(long, default_value = "1m", parse(try_from_str = args::parse_duration))]
+        log_interval: Duration,
+
+        /// The change of throughput above which the run drifted (e.g. `10%`).
+        #[clap(long, default_value = "10%", parse(try_from_str = sort::parse_percentage))]
+        max_drift: f64,
+
+        /// The growth of the resident memory above which the run leaked (e.g. `10%`).
+        #[clap(long, default_value = "10%", parse(try_from_str = sort::parse_percentage))]
+        max_rss_growth: f64,
+    },
+    /// Check the seeks at the boundaries of the files with every configuration of the
+    /// grid, before the first key, on and after the last one, and the empty files.
+    ///
+    /// The files are written in memory, the run fails if any configuration failed.
+    /// The `sanity` profile also reads back every entry of the degenerate datasets,
+    /// a sweep of the whole grid completing in seconds while developing grenad.
+    BoundaryChecks {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        #[clap(long, default_value = "1000")]
+        entry_count: u64,
+
+        #[clap(flatten)]
+        keys: KeyOptions,
+
+        /// The checked datasets, `boundaries` of the generated one and of the empty file,
+        /// or `sanity`, the datasets of 0, 1, 2 and around a block of entries too.
+        #[clap(long, default_value = "boundaries", possible_values = boundary::POSSIBLE_PROFILES)]
+        profile: Profile,
+
+        #[clap(flatten)]
+        grid: GridOptions,
+    },
+    /// Run a single test where the random lookups are driven by a tokio runtime,
+    /// each lookup being executed on the blocking thread pool of the runtime.
+    ///
+    /// The generated file is memory-mapped and shared between the tasks.
+    OneRandomAsyncTest {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        #[clap(long, default_value = "10000")]
+        entry_count: u64,
+
+        #[clap(long)]
+        compression: Option<CompressionType>,
+
+        #[clap(long)]
+        index_levels: u8,
+
+        /// The size of the blocks, e.g. `4096` or `4KiB`.
+        #[clap(long, parse(try_from_str = args::parse_size))]
+        block_size: usize,
+
+        #[clap(long)]
+        index_key_interval: NonZeroUsize,
+
+        /// The number of worker threads of the tokio runtime.
+        #[clap(long, default_value = "4")]
+        worker_threads: NonZeroUsize,
+
+        /// The number of lookups in flight at the same time.
+        #[clap(long, default_value = "64")]
+        concurrency: NonZeroUsize,
+    },
+    /// Insert duplicated keys in random order into a grenad sorter, the values of the
+    /// same key being merged with the given merge function, and write the result.
+    SorterRandomTest {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        /// The number of insertions into the sorter.
+        #[clap(long, default_value = "10000")]
+        entry_count: u64,
+
+        /// The average number of times each key is inserted.
+        #[clap(long, default_value = "4")]
+        duplicates: NonZeroU64,
+
+        /// The merge function, the generated values have the shape it expects.
+        #[clap(long, default_value = "union", possible_values = merge::POSSIBLE_MERGE_FUNCTIONS)]
+        merge_fn: MergeFunction,
+
+        /// The amount of memory after which the entries are dumped into a chunk, e.g. `100MiB`.
+        #[clap(long, default_value = "100MiB", parse(try_from_str = args::parse_size))]
+        dump_threshold: usize,
+
+        /// The number of chunks after which they are merged into a single one.
+        #[clap(long, default_value = "200")]
+        max_nb_chunks: usize,
+
+        #[clap(long)]
+        chunk_compression: Option<CompressionType>,
+
+        /// The folder where the sorter creates its temporary chunks, a tmpfs or another
+        /// disk than the final file, the system temporary folder by default.
+        #[clap(long)]
+        temp_folder: Option<PathBuf>,
+    },
+    /// Sweep the memory budget of the sorter, the dump threshold, the maximum number
+    /// of chunks and their compression, to relate it to the I/O it causes.
+    ExtendedSorterRandomTests {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        /// The number of insertions into the sorter.
+        #[clap(long, default_value = "10000")]
+        entry_count: u64,
+
+        /// The average number of times each key is inserted.
+        #[clap(long, default_value = "4")]
+        duplicates: NonZeroU64,
+
+        /// The merge function, the generated values have the shape it expects.
+        #[clap(long, default_value = "union", possible_values = merge::POSSIBLE_MERGE_FUNCTIONS)]
+        merge_fn: MergeFunction,
+
+        /// The folder where the sorter creates its temporary chunks, a tmpfs or another
+        /// disk than the final file, the system temporary folder by default.
+        #[clap(long)]
+        temp_folder: Option<PathBuf>,
+    },
+    /// Recommend parameters for a dataset from the model recorded by the previous
+    /// sweeps with `--model`, without running any benchmark.
+    SuggestParams {
+        /// The model file recorded by the sweeps.
+        #[clap(long)]
+        model: PathBuf,
+
+        #[clap(long)]
+        entry_count: u64,
+
+        /// The mean size of the values, in bytes.
+        #[clap(long)]
+        mean_value_size: u64,
+    },
+    /// Generate and evaluate the same parameters with every version of grenad
+    /// compiled in with the `grenad_0_4` and `grenad_main` features.
+    CompareVersions {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        #[clap(long, default_value = "10000")]
+        entry_count: u64,
+
+        #[clap(long)]
+        compression: Option<CompressionType>,
+
+        #[clap(long)]
+        index_levels: u8,
+
+        /// The size of the blocks, e.g. `4096` or `4KiB`.
+        #[clap(long, parse(try_from_str = args::parse_size))]
+        block_size: usize,
+
+        #[clap(long)]
+        index_key_interval: NonZeroUsize,
+    },
+    /// Run the jump workload from several processes at the same time, all of them
+    /// memory-mapping the same generated file, to measure the aggregated throughput
+    /// and verify that the pages of the mapping are shared between the processes.
+    MultiProcessRandomTest {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        #[clap(long, default_value = "10000")]
+        entry_count: u64,
+
+        #[clap(long)]
+        compression: Option<CompressionType>,
+
+        #[clap(long)]
+        index_levels: u8,
+
+        /// The size of the blocks, e.g. `4096` or `4KiB`.
+        #[clap(long, parse(try_from_str = args::parse_size))]
+        block_size: usize,
+
+        #[clap(long)]
+        index_key_interval: NonZeroUsize,
+
+        /// The number of processes running the jump workload, at least two to share the pages.
+        #[clap(long, default_value = "4")]
+        processes: usize,
+    },
+    /// Generate several files of different shapes and parameters and query them together,
+    /// every query does a lookup in each of them, to see whether the parameters of a file
+    /// slow the lookups of the others down through the page cache they share.
+    MultiDatabaseTest {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        /// The number of entries of every database, and of queries.
+        #[clap(long, default_value = "10000")]
+        entry_count: u64,
+
+        /// The comma-separated databases, the profile of their keys and their parameters,
+        /// e.g. `words:Snappy.2.4096.16,word-pair-proximity:None.1.8192.32`.
+        #[clap(long, required = true, use_delimiter = true)]
+        databases: Vec<DatabaseSpec>,
+
+        #[clap(
+            long,
+            default_value = "memory-mapped",
+            possible_values = read_method::POSSIBLE_READ_METHODS,
+            validator = platform::supported_read_method,
+        )]
+        read_method: ReadMethod,
+
+        /// Evict the files from the page cache before the databases alone and interleaved,
+        /// both workloads start from a cold cache.
+        #[clap(long)]
+        drop_caches: bool,
+    },
+    /// Compare the rankings of the configurations on two datasets, from the
+    /// lines of two sweeps recorded with `--stream-results`.
+    CrossDataset {
+        /// The streamed results of the sweep over the first dataset.
+        first: PathBuf,
+
+        /// The streamed results of the sweep over the second dataset.
+        second: PathBuf,
+
+        /// The metric the configurations are ranked on, lower is better.
+        #[clap(long, default_value = "jump", possible_values = cross::POSSIBLE_METRICS)]
+        metric: String,
+
+        /// The number of configurations listed.
+        #[clap(long, default_value = "10")]
+        top: usize,
+    },
+    /// Remove the temporary files left in the folder by the interrupted generations and
+    /// the lock files of the generations, while no other run is using the folder.
+    Clean,
+    /// Print the completion script of the command line for a shell.
+    Completions {
+        #[clap(possible_values = POSSIBLE_SHELLS)]
+        shell: String,
+    },
+    /// Print the JSON schema of a file format written by the subcommands.
+    Schema {
+        #[clap(possible_values = schema::POSSIBLE_FORMATS)]
+        format: String,
+    },
+    /// The process spawned by the `MultiProcessRandomTest` subcommand.
+    #[clap(setting = AppSettings::Hidden)]
+    JumpWorker {
+        #[clap(long)]
+        file: PathBuf,
+
+        #[clap(long)]
+        seed: u64,
+
+        #[clap(long)]
+        lookups: u64,
+
+        #[clap(long)]
+        cold: bool,
+    },
+    OneLmdbTest {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        /// The grenad file to read entries from to execute the extended suite of tests,
+        /// a `.grd.gz` or `.grd.zst` file is decompressed first, a URL is downloaded.
+        #[clap(long)]
+        file: PathBuf,
+
+        #[clap(flatten)]
+        lmdb: LmdbOptions,
+
+        #[clap(flatten)]
+        workload: WorkloadOptions,
+    },
+    OneRandomLmdbTest {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        #[clap(long, default_value = "10000")]
+        entry_count: u64,
+
+        #[clap(flatten)]
+        lmdb: LmdbOptions,
+
+        #[clap(flatten)]
+        workload: WorkloadOptions,
+    },
+    /// Evaluate an experimental backend storing the keys in an fst map of the offsets
+    /// of their values in a separate value log, compiled in with the `fst-value-log` feature.
+    OneRandomFstTest {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        #[clap(long, default_value = "10000")]
+        entry_count: u64,
+
+        /// The compression of every value of the log, `none`, `snappy`, `lz4` or `zstd`.
+        #[clap(
+            long,
+            default_value = "none",
+            possible_values = value_compression::POSSIBLE_COMPRESSIONS,
+        )]
+        compression: CompressionType,
+
+        #[clap(flatten)]
+        workload: WorkloadOptions,
+    },
+    /// Evaluate a Parquet file of the entries sorted by key, the lookups prune the
+    /// row groups with their key statistics, compiled in with the `arrow` feature.
+    OneRandomParquetTest {
+        #[clap(long, default_value = "42")]
+        seed: u64,
+
+        #[clap(long, default_value = "10000")]
+        entry_count: u64,
+
+        /// The compression of the column chunks.
+        #[clap(long, default_value = "none")]
+        compression: CompressionType,
+
+        /// The maximum number of rows of a row group.
+        #[clap(long, default_value = "8192")]
+        row_group_size: NonZeroUsize,
+
+        #[clap(flatten)]
+        workload: WorkloadOptions,
+    },
+}
+
+impl SubCommand {
+    /// Rejects the arguments that are inconsistent with each other,
+    /// before any work is started.
+    fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            SubCommand::ScalingStudy { min_entry_count, max_entry_count, .. }
+                if min_entry_count.get() > *max_entry_count =>
+            {
+                anyhow::bail!(
+                    "--min-entry-count ({}) is larger than --max-entry-count ({}), \
+                     nothing would be evaluated, swap them or lower --min-entry-count",
+                    min_entry_count,
+                    max_entry_count,
+                );
+            }
+            SubCommand::ValueSizeStudy { min_value_size, max_value_size, .. }
+                if min_value_size.get() > *max_value_size =>
+            {
+                anyhow::bail!(
+                    "--min-value-size ({} bytes) is larger than --max-value-size ({} bytes), \
+                     nothing would be evaluated, swap them or lower --min-value-size",
+                    min_value_size,
+                    max_value_size,
+                );
+            }
+            SubCommand::MultiProcessRandomTest { processes, .. } if *processes < 2 => {
+                anyhow::bail!(
+                    "--processes must be at least 2, the sharing of the pages of the file \
+                     can't be observed from a single process"
+                );
+            }
+            SubCommand::MultiDatabaseTest { databases, .. } if databases.len() < 2 => {
+                anyhow::bail!("--databases must list at least two databases to interleave");
+            }
+            SubCommand::MultiDatabaseTest { drop_caches: true, .. } if !platform::DROP_CACHES => {
+                anyhow::bail!("--drop-caches is not supported on {}", std::env::consts::OS);
+            }
+            SubCommand::CacheBudgetStudy { min_budget, max_budget, .. }
+                if min_budget.get() > *max_budget =>
+            {
+                anyhow::bail!(
+                    "--min-budget ({} bytes) is larger than --max-budget ({} bytes), \
+                     nothing would be evaluated, swap them or lower --min-budget",
+                    min_budget,
+                    max_budget,
+                );
+            }
+            SubCommand::CacheBudgetStudy { .. } if !platform::MEMORY_CGROUPS => {
+                anyhow::bail!(
+                    "the cache budget study is not supported on {}, it needs the memory cgroups",
+                    std::env::consts::OS
+                );
+            }
+            SubCommand::SorterRandomTest { dump_threshold, max_nb_chunks, .. } => {
+                if *max_nb_chunks < 2 {
+                    anyhow::bail!(
+                        "--max-nb-chunks ({}) must be at least 2, the sorter merges the chunks \
+                         when there are that many of them",
+                        max_nb_chunks,
+                    );
+                }
+                if *dump_threshold == 0 {
+                    anyhow::bail!("--dump-threshold must not be zero, e.g. `100MiB`");
+                }
+            }
+            SubCommand::OneLmdbTest { workload, .. }
+            | SubCommand::OneRandomLmdbTest { workload, .. }
+            | SubCommand::OneRandomFstTest { workload, .. }
+            | SubCommand::OneRandomParquetTest { workload, .. }
+                if workload.trace.is_some() =>
+            {
+                anyhow::bail!(
+                    "--trace is not supported by the LMDB, fst and Parquet tests, \
+                     only grenad replays traces"
+                );
+            }
+            SubCommand::OneLmdbTest { workload, .. }
+            | SubCommand::OneRandomLmdbTest { workload, .. }
+            | SubCommand::OneRandomFstTest { workload, .. }
+            | SubCommand::OneRandomParquetTest { workload, .. }
+                if workload.warm_cold =>
+            {
+                anyhow::bail!(
+                    "--warm-cold is not supported by the LMDB, fst and Parquet tests, \
+                     they read no grenad blocks"
+                );
+            }
+            SubCommand::OneLmdbTest { workload, .. }
+            | SubCommand::OneRandomLmdbTest { workload, .. }
+            | SubCommand::OneRandomFstTest { workload, .. }
+            | SubCommand::OneRandomParquetTest { workload, .. }
+                if workload.inject_faults.is_some() || workload.throttle().is_some() =>
+            {
+                anyhow::bail!(
+                    "--inject-faults and the simulated storage are not supported by the LMDB, \
+                     fst and Parquet tests, only the reads of the grenad files are wrapped"
+                );
+            }
+            SubCommand::OneLmdbTest { workload, .. }
+            | SubCommand::OneRandomLmdbTest { workload, .. }
+            | SubCommand::OneRandomFstTest { workload, .. }
+            | SubCommand::OneRandomParquetTest { workload, .. }
+                if workload.phases.contains(&Phase::ExactGet) =>
+            {
+                anyhow::bail!(
+                    "the exact-get phase is not supported by the LMDB, fst and Parquet tests, \
+                     only the cursor of grenad is evaluated"
+                );
+            }
+            SubCommand::OneRandomFstTest { workload, .. }
+            | SubCommand::OneRandomParquetTest { workload, .. }
+                if !workload.slo.is_empty() =>
+            {
+                anyhow::bail!(
+                    "--slo is not supported by the fst and Parquet tests, their seeks are not timed"
+                );
+            }
+            SubCommand::ExtendedTests { sweep: SweepOptions { evaluation, .. }, .. }
+            | SubCommand::EvaluateFolder { evaluation, .. }
+            | SubCommand::OneTest { evaluation, .. }
+                if evaluation.workload.deep_validate =>
+            {
+                anyhow::bail!(
+                    "--deep-validate needs a generated dataset, the values of --file are unknown"
+                );
+            }
+            SubCommand::OneLmdbTest { lmdb, .. } | SubCommand::OneRandomLmdbTest { lmdb, .. }
+                if lmdb.lmdb_layout == LmdbLayout::CompositeKeys
+                    && lmdb.lmdb_value_compression != CompressionType::None =>
+            {
+                anyhow::bail!(
+                    "--lmdb-value-compression is not supported by the composite-keys layout, \
+                     its values are empty"
+                );
+            }
+            SubCommand::OneLmdbTest { workload, .. } if workload.deep_validate => {
+                anyhow::bail!(
+                    "--deep-validate needs a generated dataset, the values of --file are unknown"
+                );
+            }
+            SubCommand::ExtendedRandomTests { sweep, .. }
+            | SubCommand::ExtendedTests { sweep, .. }
+                if sweep.heap_baseline.is_some() && sweep.evaluation.workload.trace.is_some() =>
+            {
+                anyhow::bail!(
+                    "--heap-baseline is not supported with --trace, only grenad replays traces"
+                );
+            }
+            SubCommand::ExtendedTests { lookup_sample_size: Some(_), sweep, .. }
+                if sweep.evaluation.workload.phases.contains(&Phase::ExactGet)
+                    || sweep.heap_baseline.is_some() =>
+            {
+                anyhow::bail!(
+                    "--lookup-sample-size is not supported by the exact-get phase and \
+                     --heap-baseline, they need every key of the file"
+                );
+            }
+            SubCommand::ExtendedRandomTests { sweep, .. }
+            | SubCommand::ExtendedTests { sweep, .. } => sweep.grid.validate()?,
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn evaluation_options(&self) -> Option<&EvaluationOptions> {
+        match self {
+            SubCommand::ExtendedRandomTests { sweep, .. }
+            | SubCommand::ExtendedTests { sweep, .. } => Some(&sweep.evaluation),
+            SubCommand::EvaluateFolder { evaluation, .. }
+            | SubCommand::OneTest { evaluation, .. }
+            | SubCommand::OneRandomTest { evaluation, .. }
+            | SubCommand::ScalingStudy { evaluation, .. }
+            | SubCommand::ValueSizeStudy { evaluation, .. }
+            | SubCommand::CompressionStudy { evaluation, .. } => Some(evaluation),
+            _ => None,
+        }
+    }
+}
+
+/// How the grenad files are generated, whatever their parameters.
+#[derive(Parser, Copy, Clone)]
+struct GenerationOptions {
+    /// Wait for the files generated by another run sharing the folder instead of
+    /// failing, the generation of every file is locked for the runs not to race.
+    #[clap(long)]
+    wait_for_lock: bool,
+
+    /// Compute the checksum of every generated file, it is recorded
+    /// in the manifest and verified before every evaluation of the file.
+    #[clap(long, possible_values = checksum::POSSIBLE_CHECKSUMS)]
+    checksum: Option<ChecksumKind>,
+}
+
+/// How the subcommands generating their dataset generate its keys.
+#[derive(Parser)]
+struct KeyOptions {
+    /// The shape of the generated keys, `words`, `word-pair-proximity` or `near-identical`.
+    #[clap(long, default_value = "words", possible_values = datagen::POSSIBLE_KEY_PROFILES)]
+    key_profile: KeyProfile,
+}
+
+/// How the subcommands reading a source file validate it.
+#[derive(Parser)]
+struct SourceOptions {
+    /// What an unsorted file does, `fail`, `warn` to skip its out-of-order entries or `off`,
+    /// the keys are first read in order and the duplicate and out-of-order ones are reported.
+    #[clap(long, default_value = "fail", possible_values = source_order::POSSIBLE_SOURCE_ORDERS)]
+    source_order: SourceOrder,
+}
+
+/// The shape of the random words, the keys of the word pairs are made of them too.
+#[derive(Parser)]
+struct WordOptions {
+    #[clap(long, default_value = "3")]
+    word_length_min: usize,
+
+    #[clap(long, default_value = "15")]
+    word_length_max: usize,
+
+    /// The characters of the words, `ascii-lower` are the pronounceable words of Gabble,
+    /// `alnum` are uniformly drawn letters and digits and `bytes` are any bytes.
+    #[clap(long, default_value = "ascii-lower", possible_values = datagen::POSSIBLE_CHARSETS)]
+    charset: Charset,
+}
+
+impl WordOptions {
+    fn shape(&self) -> anyhow::Result<WordShape> {
+        if self.word_length_min == 0 {
+            anyhow::bail!("--word-length-min must be at least 1, the keys must not be empty");
+        }
+        if self.word_length_min > self.word_length_max {
+            anyhow::bail!(
+                "--word-length-min ({}) is greater than --word-length-max ({})",
+                self.word_length_min,
+                self.word_length_max
+            );
+        }
+        Ok(WordShape {
+            min_len: self.word_length_min,
+            max_len: self.word_length_max,
+            charset: self.charset,
+        })
+    }
+}
+
+#[derive(Parser)]
+struct EvaluationOptions {
+    /// Advise the kernel to back the memory-mapped read methods with transparent
+    /// huge pages, whether the advice was honored is reported with the results.
+    ///
+    /// `MAP_HUGETLB` is only supported by hugetlbfs so we rely on `madvise` instead.
+    #[clap(long)]
+    huge_pages: bool,
+
+    /// Evict the generated file from the page cache before evaluating it.
+    #[clap(long)]
+    drop_caches: bool,
+
+    /// The number of times a configuration is generated or evaluated again when it
+    /// fails because of a transient condition (no space left, too many open files...).
+    #[clap(long, default_value = "0")]
+    retries: u32,
+
+    /// The number of times every configuration is evaluated, the medians are reported
+    /// and the best configurations of a sweep are tested against the defaults.
+    #[clap(long, default_value = "1")]
+    repetitions: NonZeroUsize,
+
+    /// The coefficient of variation between the repetitions above which a configuration
+    /// is flagged as noisy, the machine was probably busy doing something else (e.g. `10%`).
+    #[clap(long, default_value = "10%", parse(try_from_str = sort::parse_percentage))]
+    max_variation: f64,
+
+    /// The number of times the repetitions of a noisy configuration are executed
+    /// again, the least noisy execution is kept.
+    #[clap(long, default_value = "0")]
+    noisy_reruns: u32,
+
+    /// Evaluate the baseline configuration again every time this number of
+    /// configurations have been evaluated, and at the beginning and the end of
+    /// the sweeps, a drift of its timings reveals a throttled or busy machine.
+    #[clap(long)]
+    sentinel_interval: Option<NonZeroUsize>,
+
+    /// The size of the decompressed frames of the `zstd-seekable` read method,
+    /// the whole file is compressed in frames of this size (e.g. `64KiB`).
+    #[clap(long, default_value = "64KiB", parse(try_from_str = args::parse_size))]
+    seekable_frame_size: NonZeroUsize,
+
+    /// Also append the entries of every evaluated file into a fresh LMDB environment,
+    /// the final write step of milli, and measure the time of the ingestion.
+    #[clap(long)]
+    lmdb_ingestion: bool,
+
+    /// Score every configuration with the cost of the whole indexing and search pipeline,
+    /// a weighted sum of metrics like the `--sort-by` keys, reported as its pipeline cost,
+    /// e.g. `write+ingestion+100*jump` for a file queried a hundred times per build. The
+    /// ingestion is measured with `--lmdb-ingestion`, the missing metrics are ignored.
+    #[clap(long)]
+    pipeline_cost: Option<SortExpression>,
+
+    /// Run this number of threads of load competing for the CPU while every configuration
+    /// is evaluated, to measure how much the configurations suffer from the contention.
+    #[clap(long)]
+    background_load: Option<NonZeroUsize>,
+
+    /// The workload of the threads of the background load, `spin` hashes a small buffer,
+    /// `iterate` iterates over the evaluated file again and again.
+    #[clap(long, default_value = "spin", possible_values = background::POSSIBLE_WORKLOADS)]
+    background_workload: BackgroundWorkload,
+
+    /// Confine the run to a single core, `one-core`, and/or lower its priority, `nice:<n>`,
+    /// e.g. `one-core,nice:10`, to approximate a small machine, the setup is confined too.
+    #[clap(long, use_delimiter = true)]
+    cpu_limit: Vec<CpuLimit>,
+
+    #[clap(flatten)]
+    workload: WorkloadOptions,
+}
+
+/// The options of the workloads that are common to all the backends.
+#[derive(Parser)]
+struct WorkloadOptions {
+    /// The phases to execute, the full iteration, the full iteration over the keys
+    /// only, the random jumps and/or the exact gets of present and absent keys,
+    /// the skipped phases are reported as such.
+    #[clap(
+        long,
+        default_value = "iter,jump",
+        use_delimiter = true,
+        possible_values = POSSIBLE_PHASES,
+    )]
+    phases: Vec<Phase>,
+
+    /// How much of the retrieved entries are checked, either `off`, `full` or
+    /// `sampled:<pct>`, the checks are part of the measured timings.
+    #[clap(long, default_value = "full")]
+    validate: Validation,
+
+    /// Replay the operations of this trace file in place of the random jumps,
+    /// a captured sequence of seeks, iterations and prefix iterations.
+    #[clap(long, parse(try_from_str = trace::parse_trace))]
+    trace: Option<Trace>,
+
+    /// A latency objective of the random jumps, e.g. `p99_seek < 500us`, it can be
+    /// repeated, the seeks are then timed individually and reported against it.
+    #[clap(long, number_of_values = 1)]
+    slo: Vec<Slo>,
+
+    /// Report the latency of the seeks that read a block for the first time in
+    /// the run apart from the ones that only read already touched blocks.
+    #[clap(long)]
+    warm_cold: bool,
+
+    /// Compare every validated value with the one generated for its key, byte for byte,
+    /// only the subcommands that generate their dataset support it.
+    #[clap(long)]
+    deep_validate: bool,
+
+    /// Inject the faults of a degraded storage in the reads of the grenad files, e.g.
+    /// `short-reads=10%,eintr=1%,eio=0.01%,spikes=0.1%:10ms`, drawn with `seed=<n>`.
+    #[clap(long)]
+    inject_faults: Option<FaultSpec>,
+
+    /// Simulate a remote storage, every read of grenad that doesn't continue the
+    /// previous one is a round trip of this latency (e.g. `500us`).
+    #[clap(long, parse(try_from_str = args::parse_duration))]
+    simulated_latency: Option<Duration>,
+
+    /// Simulate a remote storage transferring the reads of grenad at this
+    /// bandwidth (e.g. `100MBps`).
+    #[clap(long, parse(try_from_str = args::parse_bandwidth))]
+    simulated_bandwidth: Option<u64>,
+
+    /// How the values of the dataset were generated, set by the subcommands generating it.
+    #[clap(skip)]
+    values: Option<ValueGenerator>,
+
+    /// The number of entries of the dataset when the words are a sample of its keys,
+    /// set by the subcommands sampling them.
+    #[clap(skip)]
+    entry_count: Option<u64>,
+}
+
+/// How the entries are stored in LMDB.
+#[derive(Parser)]
+struct LmdbOptions {
+    /// The layout of the entries, `bitmaps` stores a serialized bitmap per word,
+    /// `composite-keys` stores every document id of a word in its own key, the word
+    /// followed by the id, it is not a DUPSORT database of duplicate u32 values.
+    #[clap(long, default_value = "bitmaps", possible_values = lmdb_layout::POSSIBLE_LAYOUTS)]
+    lmdb_layout: LmdbLayout,
+
+    /// The compression of every value of the bitmaps layout, decompressed when read,
+    /// to compare LMDB with the grenad files of the same compression.
+    #[clap(
+        long,
+        default_value = "none",
+        possible_values = value_compression::POSSIBLE_COMPRESSIONS,
+    )]
+    lmdb_value_compression: CompressionType,
+}
+
+/// The options of the sweeps of the extended tests, whatever their dataset.
+#[derive(Parser)]
+struct SweepOptions {
+    #[clap(
+        long,
+        default_value = "direct",
+        possible_values = read_method::POSSIBLE_READ_METHODS,
+        validator = platform::supported_read_method,
+    )]
+    read_method: ReadMethod,
+
+    #[clap(flatten)]
+    grid: GridOptions,
+
+    #[clap(flatten)]
+    sort: SortOptions,
+
+    /// Also write the results of the sweep as a self-contained HTML report.
+    #[clap(long)]
+    html_report: Option<PathBuf>,
+
+    /// Also write a short Markdown summary of the sweep, the best configurations
+    /// of every sort key and their difference to the default parameters.
+    #[clap(long)]
+    summary_markdown: Option<PathBuf>,
+
+    /// Exit with the code 2 when this assertion on the results holds, it can be
+    /// repeated, e.g. `jump_time(default_params) > 1.2 * jump_time(best)`.
+    #[clap(long, number_of_values = 1)]
+    fail_if: Vec<Assertion>,
+
+    /// Also export the results in the format of `critcmp --export`, the baseline
+    /// is named after the file, e.g. `critcmp before.json after.json`.
+    #[clap(long)]
+    critcmp_export: Option<PathBuf>,
+
+    /// Also export the results as a CSV of one row per metric, keyed by the UUID
+    /// of the run and the id of the configuration, to join several runs, compressed
+    /// with gzip or zstd when the path ends with `.gz` or `.zst`.
+    #[clap(long)]
+    long_results: Option<PathBuf>,
+
+    /// Also execute the workloads on the entries held in memory, in a `btreemap` or a
+    /// `vec-binary-search`, the configurations are reported as multiples of its timings.
+    #[clap(long, possible_values = heap::POSSIBLE_HEAP_BACKENDS)]
+    heap_baseline: Option<HeapBackendKind>,
+
+    /// Also report the shared-prefix redundancy of the keys in the blocks,
+    /// what front-coding them would save, for every block size.
+    #[clap(long)]
+    prefix_report: bool,
+
+    /// Record the best configuration and the shape of the dataset in this
+    /// model file, the `suggest-params` subcommand recommends parameters from it.
+    #[clap(long)]
+    model: Option<PathBuf>,
+
+    /// Print every configuration as a JSON line on the standard output as soon as
+    /// it is evaluated, the `stream-results` schema describes the lines.
+    #[clap(long)]
+    stream_results: bool,
+
+    /// The generated files kept once the sweep is evaluated, `none`, the ones of
+    /// the `best:<n>` configurations according to the sort keys, or `all`.
+    #[clap(long, default_value = "all")]
+    keep_files: KeepFiles,
+
+    #[clap(flatten)]
+    checkpoint: CheckpointOptions,
+
+    /// Only evaluate the configurations of the grid that fit in this duration, e.g. `2h`,
+    /// a few of them estimate the cost of the others, the evaluated configurations are
+    /// spread across the grid and the skipped ones are reported.
+    #[clap(long, parse(try_from_str = args::parse_duration))]
+    time_budget: Option<Duration>,
+
+    /// The number of refinement rounds, every round evaluates the intermediate block
+    /// sizes and index key intervals around the best configurations of the rounds
+    /// before it, e.g. 3072 and 6144 around 4096.
+    #[clap(long, default_value = "0")]
+    refine: u32,
+
+    #[clap(flatten)]
+    evaluation: EvaluationOptions,
+}
+
+/// How the progress of a sweep survives a crash or a reboot.
+#[derive(Parser)]
+struct CheckpointOptions {
+    /// Save the outcomes of the evaluated configurations in the checkpoint
+    /// of the folder every this many evaluations.
+    #[clap(long, default_value = "10")]
+    checkpoint_every: NonZeroUsize,
+
+    /// Resume the interrupted sweep of the checkpoint of the folder, only the
+    /// configurations it doesn't record are evaluated, on the same dataset.
+    #[clap(long)]
+    resume: bool,
+}
+
+/// How the configurations of a sweep are ranked.
+#[derive(Parser)]
+struct SortOptions {
+    /// The comma-separated sort keys, each one is a sum of optionally weighted metrics
+    /// among iter, keys-iter, jump, write, ingestion, pipeline-cost, file-size and energy
+    /// (e.g. `jump+0.5*iter,file-size`). The next keys only order the configurations
+    /// tied on the previous ones, the remaining ties are broken by the parameters, in
+    /// declaration order.
+    #[clap(long, default_value = "jump-only", use_delimiter = true)]
+    sort_by: Vec<SortExpression>,
+
+    /// The configurations within this percentage of the best one of their bucket
+    /// are considered tied on a key and ordered by the next keys (e.g. `2%`).
+    #[clap(long, default_value = "0", parse(try_from_str = sort::parse_percentage))]
+    sort_tolerance: f64,
+}
+
+/// The parameters evaluated by a sweep, every combination of them is a configuration.
+#[derive(Parser)]
+struct GridOptions {
+    /// The comma-separated compression types.
+    #[clap(long, default_value = "none,snappy,lz4", use_delimiter = true)]
+    compressions: Vec<CompressionType>,
+
+    /// The index levels, a comma-separated list of levels and ranges (e.g. `0..=3`), any
+    /// level supported by grenad, `recommended` adds the levels recommended for the
+    /// number of entries with every index key interval (e.g. `0..=3,recommended`).
+    #[clap(long, default_value = "0..=3")]
+    index_levels: IndexLevels,
+
+    /// The comma-separated block sizes, in bytes or with a unit (e.g. `4KiB`).
+    #[clap(
+        long,
+        default_value = "8KiB,4KiB,2KiB,1KiB,512",
+        use_delimiter = true,
+        parse(try_from_str = args::parse_size),
+    )]
+    block_sizes: Vec<usize>,
+
+    /// The comma-separated index key intervals.
+    #[clap(long, default_value = "32,24,16,12,8,4,2", use_delimiter = true)]
+    index_key_intervals: Vec<NonZeroUsize>,
+
+    /// The configurations of the grid evaluated, `full`, `latin-hypercube:<n>` draws `n`
+    /// of them spread across every dimension or `fractional` keeps a fraction where every
+    /// pair of values of two dimensions is still evaluated.
+    #[clap(long, default_value = "full")]
+    grid_sampling: GridSampling,
+
+    /// Only evaluate the configurations of the grid listed in this file, one per line in
+    /// the format of the file names, e.g. the ones that failed in a previous run.
+    #[clap(long)]
+    only_configs: Option<PathBuf>,
+
+    /// Skip the configurations of the grid listed in this file, one per line.
+    #[clap(long)]
+    exclude_configs: Option<PathBuf>,
+}
+
+impl GridOptions {
+    fn validate(&self) -> anyhow::Result<()> {
+        let empty = [
+            ("--compressions", self.compressions.is_empty()),
+            ("--block-sizes", self.block_sizes.is_empty()),
+            ("--index-key-intervals", self.index_key_intervals.is_empty()),
+        ];
+        if let Some((flag, _)) = empty.iter().find(|(_, empty)| *empty) {
+            anyhow::bail!("{} must list at least one value, the sweep would be empty", flag);
+        }
+        if self.block_sizes.contains(&0) {
+            anyhow::bail!("--block-sizes must not contain zero, e.g. `512,4KiB`");
+        }
+        Ok(())
+    }
+
+    /// The configurations of the grid, the recommended index levels are the ones of
+    /// the given number of entries, only with the index key intervals recommending them.
+    /// The listed configurations are selected before the grid is sampled.
+    fn parameters(&self, entry_count: u64) -> anyhow::Result<Vec<Parameters>> {
+        let listed = &self.index_levels.levels;
+        let recommended = |interval| {
+            let levels = || recommend::recommended_index_levels(entry_count, interval);
+            self.index_levels.recommended.then(levels)
+        };
+        let mut levels = listed.clone();
+        for &index_key_interval in &self.index_key_intervals {
+            if let Some(level) = recommended(index_key_interval) {
+                if !levels.contains(&level) {
+                    levels.push(level);
+                }
+            }
+        }
+
+        let mut parameters = Vec::new();
+        for &compression in &self.compressions {
+            for &index_levels in &levels {
+                for &block_size in &self.block_sizes {
+                    for &index_key_interval in &self.index_key_intervals {
+                        if !listed.contains(&index_levels)
+                            && recommended(index_key_interval) != Some(index_levels)
+                        {
+                            continue;
+                        }
+                        parameters.push(Parameters {
+                            compression,
+                            index_levels,
+                            block_size,
+                            index_key_interval,
+                        });
+                    }
+                }
+            }
+        }
+        if let Some(path) = &self.only_configs {
+            let only = config_list::read(path)?;
+            let missing = only.iter().filter(|listed| !config_list::contains(&parameters, listed));
+            let missing = missing.count();
+            if missing != 0 {
+                eprintln!(
+                    "{} configurations listed in {} are not in the grid and are not evaluated",
+                    missing,
+                    path.display()
+                );
+            }
+            parameters.retain(|params| config_list::contains(&only, params));
+        }
+        if let Some(path) = &self.exclude_configs {
+            let excluded = config_list::read(path)?;
+            parameters.retain(|params| !config_list::contains(&excluded, params));
+        }
+        if parameters.is_empty() {
+            anyhow::bail!("the listed configurations leave no configuration of the grid");
+        }
+        Ok(self.grid_sampling.sample(parameters))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Phase {
+    Iter,
+    /// A full iteration that never reads the values, the cost of
+    /// traversing the structure, e.g. to enumerate the word prefixes.
+    KeysIter,
+    Jump,
+    /// The lookups of the exact keys, half of them absent from the file,
+    /// the gets of milli whose cost may differ from the jumps.
+    ExactGet,
+}
+
+impl FromStr for Phase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Phase, String> {
+        match s {
+            "iter" => Ok(Phase::Iter),
+            "keys-iter" => Ok(Phase::KeysIter),
+            "jump" => Ok(Phase::Jump),
+            "exact-get" => Ok(Phase::ExactGet),
+            otherwise => Err(format!("invalid phase {:?}", otherwise)),
+        }
+    }
+}
+
+impl WorkloadOptions {
+    /// The simulated storage, `None` if the storage is not simulated.
+    fn throttle(&self) -> Option<Throttle> {
+        match (self.simulated_latency, self.simulated_bandwidth) {
+            (None, None) => None,
+            (latency, bandwidth) => Some(Throttle { latency, bandwidth }),
+        }
+    }
+}
+
+impl EvaluationOptions {
+    /// Rejects the options that the current platform doesn't support.
+    fn check_platform_support(&self) -> anyhow::Result<()> {
+        let os = std::env::consts::OS;
+        if self.huge_pages && !platform::HUGE_PAGES {
+            anyhow::bail!("--huge-pages is not supported on {}", os);
+        }
+        if self.drop_caches && !platform::DROP_CACHES {
+            anyhow::bail!("--drop-caches is not supported on {}", os);
+        }
+        if self.cpu_limit.contains(&CpuLimit::OneCore) && !platform::CPU_AFFINITY {
+            anyhow::bail!("--cpu-limit one-core is not supported on {}", os);
+        }
+        let nice = self.cpu_limit.iter().any(|limit| matches!(limit, CpuLimit::Nice(_)));
+        if nice && !platform::PRIORITY {
+            anyhow::bail!("--cpu-limit nice is not supported on {}", os);
+        }
+        Ok(())
+    }
+
+    /// The load competing with the evaluations, `None` if the machine is left alone.
+    fn background_load(&self) -> Option<BackgroundLoad> {
+        self.background_load
+            .map(|threads| BackgroundLoad { threads, workload: self.background_workload })
+    }
+
+    fn open_options(&self) -> OpenOptions {
+        OpenOptions {
+            huge_pages: self.huge_pages,
+            drop_caches: self.drop_caches,
+            seekable_frame_size: self.seekable_frame_size,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Validation {
+    Off,
+    /// Validates one entry every `100 / percentage` entries.
+    Sampled {
+        percentage: f64,
+    },
+    Full,
+}
+
+impl Validation {
+    fn should_validate(&self, n: u64) -> bool {
+        match *self {
+            Validation::Off => false,
+            Validation::Sampled { percentage } => {
+                let stride = (100.0 / percentage).round().max(1.0) as u64;
+                n % stride == 0
+            }
+            Validation::Full => true,
+        }
+    }
+}
+
+impl FromStr for Validation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Validation, String> {
+        match s {
+            "off" => Ok(Validation::Off),
+            "full" => Ok(Validation::Full),
+            otherwise => {
+                let percentage = otherwise
+                    .strip_prefix("sampled:")
+                    .map(|pct| pct.trim_end_matches('%'))
+                    .and_then(|pct| pct.parse::<f64>().ok())
+                    .filter(|pct| *pct > 0.0 && *pct <= 100.0)
+                    .ok_or_else(|| {
+                        format!("invalid validation {:?}, expected off, full or sampled:<pct>", s)
+                    })?;
+                Ok(Validation::Sampled { percentage })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Validation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Validation::Off => f.write_str("off"),
+            Validation::Sampled { percentage } => write!(f, "sampled:{}%", percentage),
+            Validation::Full => f.write_str("full"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Results {
+    /// The median time taken by the full iteration, `None` if the phase was skipped.
+    iter_time: Option<Duration>,
+    /// The median time taken by the full iteration over the keys only.
+    keys_iter_time: Option<Duration>,
+    /// The median time taken by the random jumps, `None` if the phase was skipped.
+    jump_time: Option<Duration>,
+    /// The median time taken by the exact gets, `None` if the phase was skipped.
+    exact_get_time: Option<Duration>,
+    /// The median CPU times of the phases.
+    cpu: PhaseCpuTimes,
+    /// The median margin to the latency objectives, they are passed
+    /// if they were met by every repetition.
+    slo: Option<SloOutcome>,
+    /// The median latencies of the cold and warm seeks, `None` if not requested.
+    warm_cold: Option<WarmColdSplit>,
+    /// The seeks per lookup of the jump phase, the same in every repetition.
+    seeks: Option<SeekCounts>,
+    /// Whether the huge pages advice was honored, `None` if not requested.
+    huge_pages: Option<bool>,
+    validation: Validation,
+    /// The time taken to write the file, `None` if it was reused from a previous run.
+    write_time: Option<Duration>,
+    /// The time taken to append the entries of the file into LMDB, `None` if not requested.
+    ingestion_time: Option<Duration>,
+    /// The score of the `--pipeline-cost` expression, `None` if not requested.
+    pipeline_cost: Option<f64>,
+    /// The size of the evaluated file, in bytes.
+    file_size: u64,
+    /// The number of entries of the file and of lookups, or replayed
+    /// operations, of the jump phase, to derive the throughputs.
+    entry_count: u64,
+    lookup_count: u64,
+    /// The median joules consumed by the CPU packages during the evaluation,
+    /// `None` if the `rapl` feature is disabled or the counters are unreadable.
+    energy: Option<f64>,
+    /// The measurements of every repetition.
+    samples: Vec<Sample>,
+    /// Whether the variation between the repetitions is above `--max-variation`.
+    noisy: bool,
+    /// The state of the CPU before the first repetition and after the last one.
+    telemetry_before: Telemetry,
+    telemetry_after: Telemetry,
+}
+
+impl Results {
+    /// Returns the results of every repetition, the file related fields are shared.
+    fn repetitions(&self) -> impl Iterator<Item = Results> + '_ {
+        self.samples.iter().map(move |sample| Results {
+            iter_time: sample.timings.iter_time,
+            keys_iter_time: sample.timings.keys_iter_time,
+            jump_time: sample.timings.jump_time,
+            exact_get_time: sample.timings.exact_get_time,
+            cpu: sample.timings.cpu,
+            slo: sample.timings.slo,
+            warm_cold: sample.timings.warm_cold,
+            seeks: sample.timings.seeks,
+            energy: sample.energy,
+            samples: Vec::new(),
+            ..*self
+        })
+    }
+
+    /// Returns the highest coefficient of variation of the phases
+    /// timings, `None` if the configuration was evaluated once.
+    fn variation(&self) -> Option<f64> {
+        let phases: [fn(&PhaseTimings) -> Option<Duration>; 4] =
+            [|t| t.iter_time, |t| t.keys_iter_time, |t| t.jump_time, |t| t.exact_get_time];
+        phases
+            .iter()
+            .filter_map(|phase| {
+                let times: Vec<_> = self
+                    .samples
+                    .iter()
+                    .filter_map(|s| phase(&s.timings))
+                    .map(|t| t.as_secs_f64())
+                    .collect();
+                stats::coefficient_of_variation(&times)
+            })
+            .reduce(f64::max)
+    }
+
+    /// The entries iterated over per second by the full iteration.
+    fn iter_throughput(&self) -> Option<f64> {
+        per_second(self.entry_count as f64, self.iter_time)
+    }
+
+    /// The lookups executed per second by the jump phase.
+    fn jump_throughput(&self) -> Option<f64> {
+        per_second(self.lookup_count as f64, self.jump_time)
+    }
+
+    /// The megabytes of the file read per second by the full iteration.
+    fn read_bandwidth(&self) -> Option<f64> {
+        per_second(self.file_size as f64 / 1_000_000.0, self.iter_time)
+    }
+
+    /// The measured metrics by the names of the exported columns.
+    fn metrics(&self) -> Vec<Metric> {
+        let warm_cold = self.warm_cold;
+        vec![
+            Metric::time("write_time_ns", self.write_time),
+            Metric::time("lmdb_ingestion_time_ns", self.ingestion_time),
+            Metric::number("pipeline_cost", self.pipeline_cost),
+            Metric::time("iter_time_ns", self.iter_time),
+            Metric::time("keys_iter_time_ns", self.keys_iter_time),
+            Metric::time("jump_time_ns", self.jump_time),
+            Metric::time("exact_get_time_ns", self.exact_get_time),
+            Metric::time("iter_user_time_ns", self.cpu.iter.map(|cpu| cpu.user)),
+            Metric::time("iter_system_time_ns", self.cpu.iter.map(|cpu| cpu.system)),
+            Metric::time("keys_iter_user_time_ns", self.cpu.keys_iter.map(|cpu| cpu.user)),
+            Metric::time("keys_iter_system_time_ns", self.cpu.keys_iter.map(|cpu| cpu.system)),
+            Metric::time("jump_user_time_ns", self.cpu.jump.map(|cpu| cpu.user)),
+            Metric::time("jump_system_time_ns", self.cpu.jump.map(|cpu| cpu.system)),
+            Metric::time("exact_get_user_time_ns", self.cpu.exact_get.map(|cpu| cpu.user)),
+            Metric::time("exact_get_system_time_ns", self.cpu.exact_get.map(|cpu| cpu.system)),
+            Metric::number("iter_entries_per_sec", self.iter_throughput()),
+            Metric::number("jump_lookups_per_sec", self.jump_throughput()),
+            Metric::number("read_mb_per_sec", self.read_bandwidth()),
+            Metric::count("file_size", Some(self.file_size)),
+            Metric::number("energy_joules", self.energy),
+            Metric::number("variation", self.variation()),
+            Metric::number("slo_margin", self.slo.map(|slo| slo.margin)),
+            Metric::time("cold_seek_ns", warm_cold.and_then(|split| split.cold_median)),
+            Metric::time("warm_seek_ns", warm_cold.and_then(|split| split.warm_median)),
+            Metric::number("avg_seeks_per_lookup", self.seeks.map(|seeks| seeks.average)),
+            Metric::count("max_seeks_per_lookup", self.seeks.map(|seeks| seeks.max)),
+        ]
+    }
+
+    fn timings(&self) -> PhaseTimings {
+        PhaseTimings {
+            iter_time: self.iter_time,
+            keys_iter_time: self.keys_iter_time,
+            jump_time: self.jump_time,
+            exact_get_time: self.exact_get_time,
+            slo: self.slo,
+            warm_cold: self.warm_cold,
+            seeks: self.seeks,
+            cpu: self.cpu,
+        }
+    }
+}
+
+fn per_second(amount: f64, elapsed: Option<Duration>) -> Option<f64> {
+    elapsed.filter(|elapsed| !elapsed.is_zero()).map(|elapsed| amount / elapsed.as_secs_f64())
+}
+
+/// The CPU time of a phase as a percentage of its wall time, a CPU-bound phase is
+/// close to 100%, a phase waiting for its reads is far below.
+fn cpu_share(cpu: Option<CpuTime>, elapsed: Option<Duration>) -> Option<f64> {
+    per_second(cpu?.total().as_secs_f64() * 100.0, elapsed)
+}
+
+/// The measurements of a single repetition of an evaluation.
+#[derive(Debug, Copy, Clone)]
+struct Sample {
+    timings: PhaseTimings,
+    energy: Option<f64>,
+    /// Whether the huge pages advice was honored, `None` if not requested.
+    huge_pages: Option<bool>,
+}
+
+/// The time taken by every phase of a workload, `None` if the phase was skipped.
+#[derive(Debug, Default, Copy, Clone)]
+struct PhaseTimings {
+    iter_time: Option<Duration>,
+    keys_iter_time: Option<Duration>,
+    jump_time: Option<Duration>,
+    exact_get_time: Option<Duration>,
+    /// How the seeks of the jump phase compare to the latency objectives.
+    slo: Option<SloOutcome>,
+    warm_cold: Option<WarmColdSplit>,
+    /// The seeks per lookup of the jump phase, `None` for the other backends or a trace.
+    seeks: Option<SeekCounts>,
+    cpu: PhaseCpuTimes,
+}
+
+/// The CPU time spent by every phase of a workload, `None` if the phase was skipped or the
+/// time is unmeasured, only the grenad backend measures it. A phase that used much less CPU
+/// time than wall time was waiting for its reads.
+#[derive(Debug, Default, Copy, Clone)]
+struct PhaseCpuTimes {
+    iter: Option<CpuTime>,
+    keys_iter: Option<CpuTime>,
+    jump: Option<CpuTime>,
+    exact_get: Option<CpuTime>,
+}
+
+impl PhaseCpuTimes {
+    /// The median user and system times of every phase of the repetitions.
+    fn median(samples: &[Sample]) -> PhaseCpuTimes {
+        let median = |phase: fn(&PhaseCpuTimes) -> Option<CpuTime>| {
+            let times: Vec<_> = samples.iter().filter_map(|s| phase(&s.timings.cpu)).collect();
+            Some(CpuTime {
+                user: stats::median(times.iter().map(|time| time.user).collect())?,
+                system: stats::median(times.iter().map(|time| time.system).collect())?,
+            })
+        };
+        PhaseCpuTimes {
+            iter: median(|cpu| cpu.iter),
+            keys_iter: median(|cpu| cpu.keys_iter),
+            jump: median(|cpu| cpu.jump),
+            exact_get: median(|cpu| cpu.exact_get),
+        }
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let Opts { verbose, folder, max_bitmap_len, download_cache, generation, words, subcommand } =
+        Opts::try_parse()?;
+    let shape = words.shape()?;
+    let download_cache = download_cache.unwrap_or_else(|| folder.join("downloads"));
+    subcommand.validate()?;
+    if let Some(evaluation) = subcommand.evaluation_options() {
+        evaluation.check_platform_support()?;
+        cpu_limit::apply(&evaluation.cpu_limit)?;
+    }
+    if let Err(e) = platform::raise_fd_limit() {
+        eprintln!("could not raise the open files limit: {}", e);
+    }
+
+    match subcommand {
+        SubCommand::ExtendedRandomTests {
+            seed,
+            entry_count,
+            keys: KeyOptions { key_profile },
+            dataset,
+            sweep: mut options,
+        } => {
+            let (name, reference::Generation { seed, entry_count, key_profile, shape, values }) =
+                match dataset {
+                    Some(dataset) => (dataset.to_string(), dataset.generation()),
+                    None => (
+                        format!("{:?}-{}-{}", key_profile, entry_count, seed),
+                        reference::Generation {
+                            seed,
+                            entry_count,
+                            key_profile,
+                            shape,
+                            values: ValueGenerator::Random { seed, max_bitmap_len },
+                        },
+                    ),
+                };
+            options.evaluation.workload.values = Some(values);
+            println!("generating random {:?} keys...", key_profile);
+            let words = datagen::random_keys(seed, entry_count, key_profile, shape);
+            println!("{} unique keys generated!", words.len());
+
+            let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+            let dataset = RandomDataset { name, words: words.into_iter().collect(), values };
+            let run = sweep::Run {
+                subcommand: "extended-random-tests",
+                seed,
+                folder: &folder,
+                generation,
+            };
+            sweep::run(run, options, &dataset, &lookups)?;
+        }
+        SubCommand::ExtendedTests {
+            seed,
+            file,
+            input_params,
+            lookup_sample_size,
+            no_extraction_cache,
+            source: SourceOptions { source_order },
+            sweep: mut options,
+        } => {
+            let sample = lookup_sample_size.map(|size| KeySample { size, seed });
+            let extraction_cache = folder.join("extractions");
+            let dataset = FileDataset::open(
+                &file,
+                &download_cache,
+                input_params,
+                sample,
+                (!no_extraction_cache).then(|| extraction_cache.as_path()),
+                source_order,
+            )?;
+            if sample.is_some() {
+                options.evaluation.workload.entry_count = Some(dataset.entry_count());
+            }
+            let entry_count = dataset.words().len();
+            let lookups = datagen::lookup_sequence(seed, entry_count, entry_count as u64);
+            let run =
+                sweep::Run { subcommand: "extended-tests", seed, folder: &folder, generation };
+            sweep::run(run, options, &dataset, &lookups)?;
+        }
+        SubCommand::EvaluateFolder {
+            seed,
+            read_method,
+            sort,
+            html_report,
+            stream_results,
+            evaluation,
+        } => {
+            let mut params_files = Vec::new();
+            let entries = fs::read_dir(&folder)
+                .with_context(|| format!("while reading {}", folder.display()))?;
+            for entry in entries {
+                let path = entry?.path();
+                match path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(Parameters::from_file_name)
+                {
+                    Some(params) => {
+                        params_files.push((params, GeneratedFile { path, write_time: None }))
+                    }
+                    None if path.extension().map_or(false, |ext| ext == "grd") => {
+                        eprintln!(
+                            "ignoring {}, its name doesn't give its parameters",
+                            path.display()
+                        )
+                    }
+                    None => (),
+                }
+            }
+            params_files.sort_by_key(|(params, _)| params.file_name());
+            let first = match params_files.first() {
+                Some((_, generated)) => &generated.path,
+                None => anyhow::bail!("no generated file found in {}", folder.display()),
+            };
+            println!("{} generated files found", params_files.len());
+
+            let file =
+                File::open(first).with_context(|| format!("while opening {}", first.display()))?;
+            let map = unsafe { memmap2::Mmap::map(&file)? };
+
+            println!("extracting the words of {}...", first.display());
+            let Extracted { words, stats: dataset, .. } = extract::extract(&map)?;
+            println!("{} unique words extracted!", words.len());
+            println!("{}", dataset);
+
+            let lookups = datagen::lookup_sequence(seed, words.len(), dataset.entries);
+            let fingerprint = checkpoint::fingerprint(&words, &lookups);
+            let parameters: Vec<_> = params_files.iter().map(|(params, _)| *params).collect();
+            let experiment = ExperimentId::new(fingerprint, &parameters, read_method, &evaluation);
+            println!("experiment {}", experiment);
+
+            println!("evaluating the generated files...");
+            let pb = ProgressBar::new(params_files.len() as u64)
+                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
+            let output = pb.clone();
+            let mut results = params_files
+                .into_par_iter()
+                .map(|(params, generated)| {
+                    let mut progress = PhaseProgress::log(output.clone(), params.file_name());
+                    let outcome = capture_failure(|| {
+                        with_retries(evaluation.retries, || {
+                            evaluate_file(
+                                read_method,
+                                &generated,
+                                &words,
+                                &lookups,
+                                &evaluation,
+                                &mut progress,
+                            )
+                        })
+                    });
+                    if stream_results {
+                        stream::emit_result(experiment, &params, &outcome);
+                    }
+                    (params, outcome)
+                })
+                .progress_with(pb)
+                .collect::<Vec<_>>();
+
+            sort::sort_results(&mut results, &sort.sort_by, sort.sort_tolerance);
+            print_sweep_results(&results, Some(&dataset));
+
+            if let Some(path) = html_report {
+                let environment = report::Environment {
+                    subcommand: "evaluate-folder",
+                    seed,
+                    experiment,
+                    read_method,
+                    sort: &sort,
+                    input_file: None,
+                    current: None,
+                    background_load: evaluation.background_load(),
+                    cpu_limit: &evaluation.cpu_limit,
+                    skipped: &[],
+                    heap: None,
+                };
+                report::write_html_report(
+                    &path,
+                    &environment,
+                    &results,
+                    Some(&dataset),
+                    None,
+                    None,
+                )?;
+                println!("HTML report written to {}", path.display());
+            }
+        }
+        SubCommand::OneTest {
+            seed,
+            file,
+            read_method,
+            params,
+            compression,
+            index_levels,
+            block_size,
+            index_key_interval,
+            source: SourceOptions { source_order },
+            evaluation,
+        } => {
+            let file = InputFile::open(&file, &download_cache)?;
+            let mut cursor = Reader::new(Cursor::new(file.bytes()))?.into_cursor()?;
+            let sorted = source_order::check(file.bytes(), source_order)?;
+
+            println!("extracting the words...");
+            let number_of_entries = cursor.len();
+            let Extracted { words, stats: dataset, .. } = match sorted {
+                true => extract::extract(file.bytes())?,
+                false => extract::extract_sorted(file.bytes())?,
+            };
+            println!("{} unique words extracted!", words.len());
+            println!("{}", dataset);
+
+            let params = match params {
+                Some(params) => params,
+                None => Parameters {
+                    compression: compression.unwrap_or_default(),
+                    index_levels: index_levels.context("--index-levels or --params is required")?,
+                    block_size: block_size.context("--block-size or --params is required")?,
+                    index_key_interval: index_key_interval
+                        .context("--index-key-interval or --params is required")?,
+                },
+            };
+            let generated = generate_from_params(&folder, &mut cursor, &params, generation)?;
+
+            let lookups = datagen::lookup_sequence(seed, words.len(), number_of_entries);
+            let read_methods = match read_method.as_str() {
+                "all" => ReadMethod::supported(),
+                method => vec![method.parse().map_err(anyhow::Error::msg)?],
+            };
+
+            let mut methods_results = Vec::new();
+            for read_method in read_methods {
+                println!("evaluating with the {} read method...", read_method);
+                let results = evaluate_file(
+                    read_method,
+                    &generated,
+                    &words,
+                    &lookups,
+                    &evaluation,
+                    &mut PhaseProgress::bar(),
+                )?;
+                print_results(&params, &results);
+                methods_results.push((read_method, results));
+            }
+            if methods_results.len() > 1 {
+                print_read_methods_table(&methods_results);
+            }
+        }
+        SubCommand::OneRandomTest {
+            seed,
+            entry_count,
+            keys: KeyOptions { key_profile },
+            read_method,
+            compression,
+            index_levels,
+            block_size,
+            index_key_interval,
+            mut evaluation,
+        } => {
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            evaluation.workload.values = Some(values);
+            println!("generating random {:?} keys...", key_profile);
+            let words = datagen::random_keys(seed, entry_count, key_profile, shape);
+            println!("{} unique keys generated!", words.len());
+
+            let params = Parameters {
+                compression: compression.unwrap_or_default(),
+                index_levels,
+                block_size,
+                index_key_interval,
+            };
+            let generated = generate_with_values(values, &folder, &words, &params, generation)?;
+
+            let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+            let results = evaluate_file(
+                read_method,
+                &generated,
+                &words,
+                &lookups,
+                &evaluation,
+                &mut PhaseProgress::bar(),
+            )?;
+            print_results(&params, &results);
+        }
+        SubCommand::Soak {
+            seed,
+            entry_count,
+            read_method,
+            compression,
+            index_levels,
+            block_size,
+            index_key_interval,
+            duration,
+            log_interval,
+            max_drift,
+            max_rss_growth,
+        } => {
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            println!("generating random words...");
+            let words = datagen::random_words(seed, entry_count, shape);
+            println!("{} unique words generated!", words.len());
+
+            let params = Parameters {
+                compression: compression.unwrap_or_default(),
+                index_levels,
+                block_size,
+                index_key_interval,
+            };
+            let generated = generate_with_values(values, &folder, &words, &params, generation)?;
+            let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+
+            let options = SoakOptions { duration, log_interval, max_drift, max_rss_growth };
+            let max_bitmap_len = Manifest::max_bitmap_len_of(&generated.path)?;
+            println!("soaking {} for {:.0?}...", params.file_name(), duration);
+            let reader = read_method.open(&generated.path, &OpenOptions::default())?.reader;
+            let report = soak::soak(reader, &words, &lookups, max_bitmap_len, &options)?;
+
+            soak::print_soak_report(&report);
+            if report.drifted || report.leaked {
+                std::process::exit(assertion::FAILED_ASSERTION_EXIT_CODE);
+            }
+        }
+        SubCommand::BoundaryChecks {
+            seed,
+            entry_count,
+            keys: KeyOptions { key_profile },
+            profile,
+            grid,
+        } => {
+            println!("generating random {:?} keys...", key_profile);
+            let mut words = datagen::random_keys(seed, entry_count, key_profile, shape);
+            println!("{} unique keys generated!", words.len());
+
+            let (parameters, skipped) =
+                support::partition_supported(grid.parameters(words.len() as u64)?);
+            support::print_skipped(&skipped);
+            if profile == Profile::Sanity {
+                // The largest degenerate datasets are around the capacity of the largest blocks.
+                let needed = parameters
+                    .iter()
+                    .flat_map(|params| boundary::sanity_entry_counts(params, &words))
+                    .max()
+                    .unwrap_or(0);
+                if needed > words.len() {
+                    println!("generating {} random {:?} keys...", needed, key_profile);
+                    words = datagen::random_keys(seed, needed as u64, key_profile, shape);
+                }
+            }
+
+            println!(
+                "checking {} configurations with the {} profile...",
+                parameters.len(),
+                profile
+            );
+            let results: Vec<_> = parameters
+                .into_par_iter()
+                .map(|params| {
+                    let result = match profile {
+                        Profile::Boundaries => boundary::check(&params, &words),
+                        Profile::Sanity => boundary::check_sanity(&params, &words),
+                    };
+                    (params, result)
+                })
+                .collect();
+
+            boundary::print_boundary_report(&results);
+            if results.iter().any(|(_, result)| result.is_err()) {
+                anyhow::bail!("some configurations failed the boundary checks");
+            }
+        }
+        SubCommand::ScalingStudy {
+            seed,
+            min_entry_count,
+            max_entry_count,
+            growth_factor,
+            keys: KeyOptions { key_profile },
+            read_method,
+            compression,
+            index_levels,
+            block_size,
+            index_key_interval,
+            mut evaluation,
+        } => {
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            evaluation.workload.values = Some(values);
+            let params = Parameters {
+                compression: compression.unwrap_or_default(),
+                index_levels,
+                block_size,
+                index_key_interval,
+            };
+
+            let mut points = Vec::new();
+            for entry_count in
+                scaling::geometric_series(min_entry_count.get(), max_entry_count, growth_factor)
+            {
+                println!("generating {} random {:?} keys...", entry_count, key_profile);
+                let words = datagen::random_keys(seed, entry_count, key_profile, shape);
+                println!("{} unique keys generated!", words.len());
+
+                // The files of the different entry counts have the same name.
+                let folder = folder.join(format!("scaling-{}", entry_count));
+                fs::create_dir_all(&folder)
+                    .with_context(|| format!("while creating {}", folder.display()))?;
+
+                let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+                let outcome = capture_failure(|| {
+                    let generated =
+                        generate_with_values(values, &folder, &words, &params, generation)?;
+                    evaluate_file(
+                        read_method,
+                        &generated,
+                        &words,
+                        &lookups,
+                        &evaluation,
+                        &mut PhaseProgress::bar(),
+                    )
+                });
+                points.push(ScalingPoint { x: entry_count, outcome });
+            }
+
+            println!("{:#?}", params);
+            scaling::print_scaling_results("entries", &points);
+        }
+        SubCommand::ValueSizeStudy {
+            seed,
+            entry_count,
+            keys: KeyOptions { key_profile },
+            min_value_size,
+            max_value_size,
+            growth_factor,
+            read_method,
+            compression,
+            index_levels,
+            block_sizes,
+            index_key_interval,
+            mut evaluation,
+        } => {
+            println!("generating random {:?} keys...", key_profile);
+            let words = datagen::random_keys(seed, entry_count, key_profile, shape);
+            println!("{} unique keys generated!", words.len());
+            let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+
+            let value_sizes =
+                scaling::geometric_series(min_value_size.get(), max_value_size, growth_factor);
+            let mut series = Vec::new();
+            for &block_size in &block_sizes {
+                let params = Parameters {
+                    compression: compression.unwrap_or_default(),
+                    index_levels,
+                    block_size,
+                    index_key_interval,
+                };
+
+                let mut points = Vec::new();
+                for &value_size in &value_sizes {
+                    println!(
+                        "evaluating {} with values of {} bytes...",
+                        params.file_name(),
+                        value_size
+                    );
+                    // The files of the different value sizes have the same name.
+                    let folder = folder.join(format!("value-size-{}", value_size));
+                    fs::create_dir_all(&folder)
+                        .with_context(|| format!("while creating {}", folder.display()))?;
+
+                    let values = ValueGenerator::Sized { seed, mean_size: value_size };
+                    evaluation.workload.values = Some(values);
+                    let outcome = capture_failure(|| {
+                        let generated =
+                            generate_with_values(values, &folder, &words, &params, generation)?;
+                        evaluate_file(
+                            read_method,
+                            &generated,
+                            &words,
+                            &lookups,
+                            &evaluation,
+                            &mut PhaseProgress::bar(),
+                        )
+                    });
+                    points.push(ScalingPoint { x: value_size, outcome });
+                }
+
+                println!("{:#?}", params);
+                scaling::print_scaling_results("value size", &points);
+                series.push((format!("block size {}", block_size), points));
+            }
+
+            scaling::print_best_series("value size", &series);
+        }
+        SubCommand::CacheBudgetStudy {
+            seed,
+            entry_count,
+            keys: KeyOptions { key_profile },
+            configurations,
+            cgroup,
+            min_budget,
+            max_budget,
+            growth_factor,
+            tolerance,
+        } => {
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            println!("generating random {:?} keys...", key_profile);
+            let words = datagen::random_keys(seed, entry_count, key_profile, shape);
+            println!("{} unique keys generated!", words.len());
+
+            let budgets = scaling::geometric_series(min_budget.get(), max_budget, growth_factor);
+            let mut series = Vec::with_capacity(configurations.len());
+            for params in configurations {
+                let generated = generate_with_values(values, &folder, &words, &params, generation)?;
+                println!("running the jumps of {} with {} budgets...", params, budgets.len());
+                let points = cache_budget::study(
+                    &folder,
+                    &generated.path,
+                    seed,
+                    entry_count,
+                    &cgroup,
+                    &budgets,
+                );
+                series.push((params, points));
+            }
+            cache_budget::print_budget_results(&series, tolerance);
+        }
+        SubCommand::CompressionStudy {
+            seed,
+            entry_count,
+            keys: KeyOptions { key_profile },
+            read_method,
+            compressions,
+            index_levels,
+            block_size,
+            index_key_interval,
+            mut evaluation,
+        } => {
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            evaluation.workload.values = Some(values);
+            println!("generating random {:?} keys...", key_profile);
+            let words = datagen::random_keys(seed, entry_count, key_profile, shape);
+            println!("{} unique keys generated!", words.len());
+            let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+
+            let filesystem = platform::filesystem_compression(&folder);
+            let at_rest = match &filesystem {
+                Some(platform::FilesystemCompression {
+                    filesystem,
+                    algorithm: Some(algorithm),
+                }) => {
+                    format!("none on {} ({})", filesystem, algorithm)
+                }
+                _ => "none".to_string(),
+            };
+
+            let params = |compression| Parameters {
+                compression,
+                index_levels,
+                block_size,
+                index_key_interval,
+            };
+            let evaluate = |read_method: ReadMethod, compression: CompressionType| {
+                println!("evaluating {:?} with the {} read method...", compression, read_method);
+                let mut allocated_size = None;
+                let outcome = capture_failure(|| {
+                    let generated = generate_with_values(
+                        values,
+                        &folder,
+                        &words,
+                        &params(compression),
+                        generation,
+                    )?;
+                    let results = evaluate_file(
+                        read_method,
+                        &generated,
+                        &words,
+                        &lookups,
+                        &evaluation,
+                        &mut PhaseProgress::bar(),
+                    )?;
+                    let frame_size = evaluation.seekable_frame_size;
+                    let path = read_method.read_file(&generated.path, frame_size)?;
+                    allocated_size = platform::allocated_size(&path);
+                    Ok(results)
+                });
+                (outcome, allocated_size)
+            };
+
+            let mut rows = Vec::new();
+            let (outcome, allocated_size) = evaluate(read_method, CompressionType::None);
+            rows.push(StudyRow { label: at_rest, outcome, allocated_size });
+            let (outcome, allocated_size) =
+                evaluate(ReadMethod::ZstdSeekable, CompressionType::None);
+            let label = "none through zstd-seekable".to_string();
+            rows.push(StudyRow { label, outcome, allocated_size });
+            for compression in compressions {
+                let (outcome, allocated_size) = evaluate(read_method, compression);
+                let label = format!("{:?}", compression).to_lowercase();
+                rows.push(StudyRow { label, outcome, allocated_size });
+            }
+
+            at_rest::print_compression_study(filesystem.as_ref(), &rows);
+        }
+        SubCommand::OneRandomAsyncTest {
+            seed,
+            entry_count,
+            compression,
+            index_levels,
+            block_size,
+            index_key_interval,
+            worker_threads,
+            concurrency,
+        } => {
+            println!("generating random words...");
+            let words = datagen::random_words(seed, entry_count, shape);
+            println!("{} unique words generated!", words.len());
+
+            let params = Parameters {
+                compression: compression.unwrap_or_default(),
+                index_levels,
+                block_size,
+                index_key_interval,
+            };
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            let generated = generate_with_values(values, &folder, &words, &params, generation)?;
+
+            let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+            let results = async_test::test_cursor_async(
+                &generated.path,
+                &words,
+                &lookups,
+                worker_threads,
+                concurrency,
+            )?;
+
+            println!("{:#?}", params);
+            println!(
+                "took {:.02?} to do {} lookups ({:.0} lookups/s) with {} worker threads and {} tasks",
+                results.elapsed,
+                results.lookups,
+                stats::throughput(results.lookups, results.elapsed),
+                worker_threads,
+                concurrency,
+            );
+            println!(
+                "lookup latency p50 {:.02?}, p99 {:.02?}, max {:.02?}",
+                results.p50_latency, results.p99_latency, results.max_latency,
+            );
+            println!();
+        }
+        SubCommand::SorterRandomTest {
+            seed,
+            entry_count,
+            duplicates,
+            merge_fn,
+            dump_threshold,
+            max_nb_chunks,
+            chunk_compression,
+            temp_folder,
+        } => {
+            println!("generating random words...");
+            let key_count = (entry_count / duplicates.get()).max(1);
+            let words = datagen::random_words(seed, key_count, shape);
+            println!("{} unique words generated!", words.len());
+
+            let params = SorterParameters {
+                merge_function: merge_fn,
+                dump_threshold,
+                max_nb_chunks,
+                chunk_compression: chunk_compression.unwrap_or_default(),
+            };
+            let insertions = datagen::insertion_sequence(seed, words.len(), entry_count);
+
+            println!("inserting into the sorter...");
+            let results = sorter::test_sorter(
+                &folder,
+                temp_folder.as_deref(),
+                seed,
+                &words,
+                &insertions,
+                &params,
+            )?;
+            sorter::print_sorter_results(&params, &results);
+        }
+        SubCommand::ExtendedSorterRandomTests {
+            seed,
+            entry_count,
+            duplicates,
+            merge_fn,
+            temp_folder,
+        } => {
+            println!("generating random words...");
+            let key_count = (entry_count / duplicates.get()).max(1);
+            let words = datagen::random_words(seed, key_count, shape);
+            println!("{} unique words generated!", words.len());
+            let insertions = datagen::insertion_sequence(seed, words.len(), entry_count);
+
+            let dump_thresholds = vec![4 * 1024 * 1024, 16 * 1024 * 1024, 64 * 1024 * 1024];
+            let max_nb_chunks = vec![2, 10, 50, 200];
+            let compressions =
+                vec![CompressionType::None, CompressionType::Snappy, CompressionType::Lz4];
+
+            // The configurations are evaluated one after the other,
+            // in parallel they would exceed their memory budget.
+            let mut results = Vec::new();
+            for &dump_threshold in &dump_thresholds {
+                for &max_nb_chunks in &max_nb_chunks {
+                    for &chunk_compression in &compressions {
+                        let params = SorterParameters {
+                            merge_function: merge_fn,
+                            dump_threshold,
+                            max_nb_chunks,
+                            chunk_compression,
+                        };
+                        println!("inserting into the sorter with {:?}...", params);
+                        let outcome = sorter::test_sorter(
+                            &folder,
+                            temp_folder.as_deref(),
+                            seed,
+                            &words,
+                            &insertions,
+                            &params,
+                        );
+                        results.push((params, outcome));
+                    }
+                }
+            }
+
+            results.sort_by_key(|(_, outcome)| match outcome {
+                Ok(results) => Some(results.insert_time + results.write_time),
+                Err(_) => None,
+            });
+            results.sort_by_key(|(_, outcome)| outcome.is_err());
+            for (params, outcome) in &results {
+                match outcome {
+                    Ok(results) => sorter::print_sorter_results(params, results),
+                    Err(error) => {
+                        println!("{:#?}", params);
+                        println!("status: failed");
+                        println!("error: {:#}", error);
+                        println!();
+                    }
+                }
+            }
+            sorter::print_amplification_table(&results);
+        }
+        SubCommand::SuggestParams { model, entry_count, mean_value_size } => {
+            let model = recommend::read_model(&model)?;
+            match recommend::suggest(&model, entry_count, mean_value_size) {
+                Some(observation) => {
+                    println!("closest dataset in the model: {}", observation);
+                    println!("{:#?}", observation.params);
+                }
+                None => anyhow::bail!("the model is empty, record sweeps with --model first"),
+            }
+        }
+        SubCommand::Clean => {
+            let mut removed = 0;
+            let entries = fs::read_dir(&folder)
+                .with_context(|| format!("while reading {}", folder.display()))?;
+            for entry in entries {
+                let path = entry?.path();
+                let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+                if name.ends_with(&format!(".{}", TEMPORARY_EXTENSION))
+                    || name.ends_with(&format!(".{}", LOCK_EXTENSION))
+                    || name.ends_with(seekable::TEMPORARY_SUFFIX)
+                {
+                    fs::remove_file(&path)
+                        .with_context(|| format!("while removing {}", path.display()))?;
+                    println!("removed {}", path.display());
+                    removed += 1;
+                }
+            }
+            println!("{} temporary and lock files removed from {}", removed, folder.display());
+        }
+        SubCommand::CrossDataset { first, second, metric, top } => {
+            cross::print_cross_dataset(&first, &second, &metric, top)?;
+        }
+        SubCommand::Completions { shell } => {
+            let mut app = Opts::into_app();
+            let bin_name = env!("CARGO_PKG_NAME");
+            let mut stdout = io::stdout();
+            match shell.as_str() {
+                "bash" => clap_generate::generate(Bash, &mut app, bin_name, &mut stdout),
+                "elvish" => clap_generate::generate(Elvish, &mut app, bin_name, &mut stdout),
+                "fish" => clap_generate::generate(Fish, &mut app, bin_name, &mut stdout),
+                "powershell" => {
+                    clap_generate::generate(PowerShell, &mut app, bin_name, &mut stdout)
+                }
+                "zsh" => clap_generate::generate(Zsh, &mut app, bin_name, &mut stdout),
+                otherwise => anyhow::bail!("unsupported shell {:?}", otherwise),
+            }
+        }
+        SubCommand::Schema { format } => match schema::schema(&format) {
+            Some(schema) => print!("{}", schema),
+            None => anyhow::bail!("no schema for the {:?} format", format),
+        },
+        SubCommand::CompareVersions {
+            seed,
+            entry_count,
+            compression,
+            index_levels,
+            block_size,
+            index_key_interval,
+        } => {
+            println!("generating random words...");
+            let words = datagen::random_words(seed, entry_count, shape);
+            println!("{} unique words generated!", words.len());
+
+            let params = Parameters {
+                compression: compression.unwrap_or_default(),
+                index_levels,
+                block_size,
+                index_key_interval,
+            };
+            let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+
+            println!("{:#?}", params);
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            let results = versions::evaluate_versions(&folder, values, &words, &lookups, &params);
+            if results.len() == 1 {
+                println!("only the current version is compiled in, enable the grenad_0_4");
+                println!("or grenad_main features to compare it to other versions");
+            }
+            for (version, result) in results {
+                println!("grenad {}:", version);
+                match result {
+                    Ok(results) => {
+                        println!("took {:.02?} to write the file", results.write_time);
+                        println!("took {:.02?} to iterate over values", results.iter_time);
+                        println!("took {:.02?} to jump over values", results.jump_time);
+                        println!("file size: {} bytes", results.file_size);
+                        if !results.index_parameters {
+                            println!("the index parameters are not supported and were ignored");
+                        }
+                    }
+                    Err(error) => println!("error: {:#}", error),
+                }
+                println!();
+            }
+        }
+        SubCommand::MultiProcessRandomTest {
+            seed,
+            entry_count,
+            compression,
+            index_levels,
+            block_size,
+            index_key_interval,
+            processes,
+        } => {
+            println!("generating random words...");
+            let words = datagen::random_words(seed, entry_count, shape);
+            println!("{} unique words generated!", words.len());
+
+            let params = Parameters {
+                compression: compression.unwrap_or_default(),
+                index_levels,
+                block_size,
+                index_key_interval,
+            };
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            let generated = generate_with_values(values, &folder, &words, &params, generation)?;
+
+            println!("running the jump workload from {} processes...", processes);
+            let results =
+                multi_process::run_workers(&folder, &generated.path, seed, entry_count, processes)?;
+
+            println!("{:#?}", params);
+            let slowest = results.iter().map(|r| r.elapsed).max().unwrap_or_default();
+            let total_lookups = entry_count * results.len() as u64;
+            println!(
+                "took {:.02?} to do {} lookups from {} processes ({:.0} lookups/s)",
+                slowest,
+                total_lookups,
+                results.len(),
+                stats::throughput(total_lookups, slowest),
+            );
+            for (i, result) in results.iter().enumerate() {
+                match (result.mapping_rss_kib, result.mapping_shared_kib) {
+                    (Some(rss), Some(shared)) => println!(
+                        "process {} took {:.02?}, {} KiB of the mapping resident, {} KiB shared",
+                        i, result.elapsed, rss, shared,
+                    ),
+                    _ => println!("process {} took {:.02?}", i, result.elapsed),
+                }
+            }
+            if results.iter().any(|r| r.mapping_shared_kib == Some(0)) {
+                println!("warning: some processes don't share the pages of the mapping");
+            }
+            println!();
+        }
+        SubCommand::MultiDatabaseTest {
+            seed,
+            entry_count,
+            databases: specs,
+            read_method,
+            drop_caches,
+        } => {
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            let mut databases = Vec::with_capacity(specs.len());
+            for spec in specs {
+                println!("generating random {:?} keys...", spec.key_profile);
+                let words = datagen::random_keys(seed, entry_count, spec.key_profile, shape);
+                println!("{} unique keys generated!", words.len());
+
+                // The files of the different key profiles have the same name.
+                let folder = folder.join(format!("multi-{:?}", spec.key_profile));
+                fs::create_dir_all(&folder)
+                    .with_context(|| format!("while creating {}", folder.display()))?;
+                let generated =
+                    generate_with_values(values, &folder, &words, &spec.params, generation)?;
+                let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+                databases.push(multi_db::Database { spec, path: generated.path, words, lookups });
+            }
+
+            println!("querying {} databases alone then interleaved...", databases.len());
+            let options = OpenOptions { drop_caches, ..OpenOptions::default() };
+            let timings = multi_db::run(&databases, read_method, &options)?;
+            multi_db::print_multi_database_results(&databases, &timings);
+        }
+        SubCommand::JumpWorker { file, seed, lookups, cold } => {
+            multi_process::jump_worker(&file, seed, lookups, cold)?;
+        }
+        SubCommand::OneLmdbTest { seed, file, lmdb, workload } => {
+            // The values are copied from the file, they share its bounds.
+            let file = InputFile::open(&file, &download_cache)?;
+            let max_bitmap_len = Manifest::max_bitmap_len_of(&file.dataset_path())?;
+            let mut cursor = Reader::new(Cursor::new(file.bytes()))?.into_cursor()?;
+
+            println!("extracting the words...");
+            let number_of_entries = cursor.len();
+            let pb = ProgressBar::new(number_of_entries)
+                .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
+            let mut words = Vec::with_capacity(number_of_entries as usize);
+            let mut dataset = DatasetStats::default();
+            while let Some((k, v)) = cursor.move_on_next()? {
+                words.push(k.to_vec());
+                dataset.insert(k, v);
+                pb.inc(1);
+            }
+            pb.finish_and_clear();
+            println!("{} unique words extracted!", words.len());
+            println!("{}", dataset);
+
+            let env = generate_lmdb(&folder, &mut cursor, &lmdb)?;
+            let database = env.open_database(None)?.unwrap();
+            let rtxn = env.read_txn()?;
+
+            let lookups = datagen::lookup_sequence(seed, words.len(), number_of_entries);
+            let timings = test_lmdb_layout(
+                &rtxn,
+                database,
+                &words,
+                &lookups,
+                &workload,
+                &lmdb,
+                max_bitmap_len,
+                &mut PhaseProgress::bar(),
+            )?;
+
+            print_timings(timings);
+            println!("validation: {}", workload.validate);
+            println!(
+                "layout: {}, values compression: {:?}",
+                lmdb.lmdb_layout, lmdb.lmdb_value_compression
+            );
+            let stat = lmdb_stat::read(&folder.join("lmdb").with_extension("mdb"))?;
+            println!("{}", stat);
+            let grenad_size = file.bytes().len() as u64;
+            println!(
+                "LMDB uses {} bytes, {:.02}x the {} bytes of the grenad file",
+                stat.used_size(),
+                stat.used_size() as f64 / grenad_size as f64,
+                grenad_size
+            );
+            println!();
+        }
+        SubCommand::OneRandomFstTest { seed, entry_count, compression, mut workload } => {
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            workload.values = Some(values);
+            println!("generating random words...");
+            let words = datagen::random_words(seed, entry_count, shape);
+            println!("{} unique words generated!", words.len());
+
+            let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+            let results = fst_log::evaluate(
+                &folder,
+                values,
+                &words,
+                &lookups,
+                compression,
+                &workload,
+                &mut PhaseProgress::bar(),
+            )?;
+
+            println!("took {:.02?} to write the keys and the values", results.write_time);
+            println!(
+                "file size: {} bytes of keys and {} bytes of values",
+                results.keys_size, results.values_size
+            );
+            print_timings(results.timings);
+            println!("validation: {}", workload.validate);
+            println!();
+        }
+        SubCommand::OneRandomParquetTest {
+            seed,
+            entry_count,
+            compression,
+            row_group_size,
+            mut workload,
+        } => {
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            workload.values = Some(values);
+            println!("generating random words...");
+            let words = datagen::random_words(seed, entry_count, shape);
+            println!("{} unique words generated!", words.len());
+
+            let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+            let results = parquet_backend::evaluate(
+                &folder,
+                values,
+                &words,
+                &lookups,
+                compression,
+                row_group_size.get(),
+                &workload,
+                &mut PhaseProgress::bar(),
+            )?;
+
+            println!("took {:.02?} to write the Parquet file", results.write_time);
+            println!("file size: {} bytes in {} row groups", results.file_size, results.row_groups);
+            print_timings(results.timings);
+            println!("validation: {}", workload.validate);
+            println!();
+        }
+        SubCommand::OneRandomLmdbTest { seed, entry_count, lmdb, mut workload } => {
+            let values = ValueGenerator::Random { seed, max_bitmap_len };
+            workload.values = Some(values);
+            println!("generating random words...");
+            let words = datagen::random_words(seed, entry_count, shape);
+            println!("{} unique words generated!", words.len());
+
+            let env = random_generate_lmdb(values, &folder, &words, &lmdb)?;
+            let database = env.open_database(None)?.unwrap();
+            let rtxn = env.read_txn()?;
+
+            let lookups = datagen::lookup_sequence(seed, words.len(), entry_count);
+            let max_bitmap_len = Manifest::of_values(&values).max_bitmap_len;
+            let timings = test_lmdb_layout(
+                &rtxn,
+                database,
+                &words,
+                &lookups,
+                &workload,
+                &lmdb,
+                max_bitmap_len,
+                &mut PhaseProgress::bar(),
+            )?;
+
+            print_timings(timings);
+            println!("validation: {}", workload.validate);
+            println!(
+                "layout: {}, values compression: {:?}",
+                lmdb.lmdb_layout, lmdb.lmdb_value_compression
+            );
+            println!("{}", lmdb_stat::read(&folder.join("random-lmdb").with_extension("mdb"))?);
             println!();
         }
     }
@@ -576,110 +2923,993 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn test_cursor<RN: Rng, R: io::Read + io::Seek>(
-    mut rng: RN,
+fn evaluate_file<W: Words + ?Sized>(
+    read_method: ReadMethod,
+    generated: &GeneratedFile,
+    words: &W,
+    lookups: &[usize],
+    options: &EvaluationOptions,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<Results> {
+    let filepath = &generated.path;
+    if let Some(checksum) = Manifest::read(filepath)?.and_then(|manifest| manifest.checksum) {
+        checksum::verify(filepath, checksum)?;
+    }
+    let mut results =
+        evaluate_repetitions(read_method, generated, words, lookups, options, progress)?;
+    for _ in 0..options.noisy_reruns {
+        match results.variation() {
+            Some(variation) if variation > options.max_variation => {
+                eprintln!(
+                    "{} is noisy ({:.02}% of variation), evaluating it again...",
+                    filepath.display(),
+                    variation,
+                );
+                let rerun = evaluate_repetitions(
+                    read_method,
+                    generated,
+                    words,
+                    lookups,
+                    options,
+                    progress,
+                )?;
+                if rerun.variation() < Some(variation) {
+                    results = rerun;
+                }
+            }
+            _ => break,
+        }
+    }
+
+    results.noisy = results.variation().map_or(false, |v| v > options.max_variation);
+    if options.lmdb_ingestion {
+        results.ingestion_time = Some(ingestion::ingest_into_lmdb(filepath)?);
+    }
+    results.pipeline_cost = options.pipeline_cost.as_ref().and_then(|cost| cost.score(&results));
+    Ok(results)
+}
+
+/// Evaluates the file as many times as requested, the medians are reported.
+fn evaluate_repetitions<W: Words + ?Sized>(
+    read_method: ReadMethod,
+    generated: &GeneratedFile,
+    words: &W,
+    lookups: &[usize],
+    options: &EvaluationOptions,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<Results> {
+    let filepath = &generated.path;
+    let measured_path = read_method.read_file(filepath, options.seekable_frame_size)?;
+    let file_size = fs::metadata(&measured_path)
+        .with_context(|| format!("while reading the metadata of {}", measured_path.display()))?
+        .len();
+
+    let telemetry_before = Telemetry::read();
+    let load = options.background_load().map(|load| load.start(filepath));
+    let mut samples = Vec::with_capacity(options.repetitions.get());
+    for _ in 0..options.repetitions.get() {
+        samples.push(evaluate_once(read_method, filepath, words, lookups, options, progress)?);
+    }
+    if let Some(load) = load {
+        load.stop()?;
+    }
+    let telemetry_after = Telemetry::read();
+
+    Ok(Results {
+        iter_time: stats::median(samples.iter().filter_map(|s| s.timings.iter_time).collect()),
+        keys_iter_time: stats::median(
+            samples.iter().filter_map(|s| s.timings.keys_iter_time).collect(),
+        ),
+        jump_time: stats::median(samples.iter().filter_map(|s| s.timings.jump_time).collect()),
+        exact_get_time: stats::median(
+            samples.iter().filter_map(|s| s.timings.exact_get_time).collect(),
+        ),
+        cpu: PhaseCpuTimes::median(&samples),
+        slo: median_slo_outcome(&samples),
+        warm_cold: WarmColdSplit::median(
+            &samples.iter().filter_map(|s| s.timings.warm_cold).collect::<Vec<_>>(),
+        ),
+        seeks: samples.iter().find_map(|s| s.timings.seeks),
+        huge_pages: samples.last().and_then(|s| s.huge_pages),
+        validation: options.workload.validate,
+        write_time: generated.write_time,
+        ingestion_time: None,
+        pipeline_cost: None,
+        file_size,
+        entry_count: options.workload.entry_count.unwrap_or(words.len() as u64),
+        lookup_count: options
+            .workload
+            .trace
+            .as_ref()
+            .map_or(lookups.len(), |trace| trace.operations.len()) as u64,
+        energy: stats::median(samples.iter().filter_map(|s| s.energy).collect()),
+        samples,
+        noisy: false,
+        telemetry_before,
+        telemetry_after,
+    })
+}
+
+fn median_slo_outcome(samples: &[Sample]) -> Option<SloOutcome> {
+    let outcomes: Vec<_> = samples.iter().filter_map(|s| s.timings.slo).collect();
+    let margin = stats::median(outcomes.iter().map(|outcome| outcome.margin).collect())?;
+    Some(SloOutcome { margin, passed: outcomes.iter().all(|outcome| outcome.passed) })
+}
+
+/// Evaluates the file once, returns the measurements of the repetition.
+fn evaluate_once<W: Words + ?Sized>(
+    read_method: ReadMethod,
+    filepath: &Path,
+    words: &W,
+    lookups: &[usize],
+    options: &EvaluationOptions,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<Sample> {
+    let max_bitmap_len = Manifest::max_bitmap_len_of(filepath)?;
+    // We open the file lazily, keeping every generated file open could exhaust
+    // the file descriptors on large sweeps.
+    let meter = energy::EnergyMeter::start();
+    let opened = read_method.open(filepath, &options.open_options())?;
+    let timings =
+        test_cursor(opened.reader, words, lookups, &options.workload, max_bitmap_len, progress)?;
+
+    let energy = meter.and_then(|meter| meter.joules());
+
+    // The huge pages are inspected once the workload touched the mapping.
+    let huge_pages = opened.huge_pages.map(|huge_pages| huge_pages.honored());
+    Ok(Sample { timings, energy, huge_pages })
+}
+
+/// The outcome of the evaluation of a configuration, the error message if it failed.
+type Outcome = Result<Results, String>;
+
+/// Executes the given function again, with an exponential backoff, as long as it fails
+/// because of a transient condition, deterministic failures are returned immediately.
+fn with_retries<T>(retries: u32, mut f: impl FnMut() -> anyhow::Result<T>) -> anyhow::Result<T> {
+    let mut attempt = 0;
+    loop {
+        match f() {
+            Err(error) if attempt < retries && is_transient_failure(&error) => {
+                let backoff = RETRY_BASE_BACKOFF * 2u32.pow(attempt.min(10));
+                eprintln!("transient failure ({:#}), retrying in {:.02?}...", error, backoff);
+                thread::sleep(backoff);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_transient_failure(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if let Some(error) = cause.downcast_ref::<io::Error>() {
+            platform::is_transient_io_error(error)
+        } else if let Some(grenad::Error::Io(error)) = cause.downcast_ref::<grenad::Error>() {
+            platform::is_transient_io_error(error)
+        } else {
+            false
+        }
+    })
+}
+
+/// Executes the given function and turns any error or panic into a failure message,
+/// a single failing configuration must not abort the whole sweep.
+fn capture_failure<T>(f: impl FnOnce() -> anyhow::Result<T>) -> Result<T, String> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(error)) => Err(format!("{:#}", error)),
+        Err(payload) => match payload.downcast::<String>() {
+            Ok(message) => Err(format!("panicked: {}", message)),
+            Err(payload) => match payload.downcast::<&str>() {
+                Ok(message) => Err(format!("panicked: {}", message)),
+                Err(_) => Err("panicked".to_string()),
+            },
+        },
+    }
+}
+
+fn print_sweep_results(results: &[(Parameters, Outcome)], dataset: Option<&DatasetStats>) {
+    if let Some(dataset) = dataset {
+        println!("{}", dataset);
+    }
+
+    for (params, outcome) in results {
+        match outcome {
+            Ok(results) => print_results(params, results),
+            Err(error) => {
+                println!("{:#?}", params);
+                println!("status: failed");
+                println!("error: {}", error);
+                println!();
+            }
+        }
+    }
+
+    let failed = results.iter().filter(|(_, outcome)| outcome.is_err()).count();
+    if failed != 0 {
+        println!("{} configurations out of {} failed", failed, results.len());
+    }
+    let successes = || results.iter().filter_map(|(_, outcome)| outcome.as_ref().ok());
+    let noisy = successes().filter(|results| results.noisy).count();
+    if noisy != 0 {
+        println!("{} configurations out of {} are noisy", noisy, results.len());
+    }
+    if let Some(noise) = stats::median(successes().filter_map(Results::variation).collect()) {
+        println!("machine noise estimate: {:.02}% of median variation", noise);
+    }
+}
+
+/// Prints the best configuration of the sorted results as a rule and records it in the model.
+fn record_recommendation(
+    path: &Path,
+    results: &[(Parameters, Outcome)],
+    entries: u64,
+    mean_value_size: u64,
+) -> anyhow::Result<()> {
+    match Observation::from_sweep(results, entries, mean_value_size) {
+        Some(observation) => {
+            println!("{}", observation);
+            recommend::record(path, &observation)?;
+            println!("recorded in {}", path.display());
+        }
+        None => println!("every configuration failed, nothing is recorded in the model"),
+    }
+    Ok(())
+}
+
+/// Finds the generated file of the baseline configuration, it is used as the sentinel.
+fn sentinel_file(
+    params_files: &[(Parameters, Result<GeneratedFile, String>)],
+) -> Option<GeneratedFile> {
+    let baseline = significance::baseline_parameters();
+    let found = params_files
+        .iter()
+        .find(|(params, _)| significance::is_same_configuration(params, &baseline))
+        .and_then(|(_, generated)| generated.as_ref().ok())
+        .cloned();
+    if found.is_none() {
+        eprintln!("the baseline configuration failed to be generated, no sentinel will run");
+    }
+    found
+}
+
+fn print_sentinel_runs(runs: &[SentinelRun]) {
+    println!("sentinel runs:");
+    for run in runs {
+        print!("at {:.02?}: ", run.at);
+        match (run.iter_time, run.jump_time) {
+            (Some(iter), Some(jump)) => println!("iter {:.02?}, jump {:.02?}", iter, jump),
+            (Some(iter), None) => println!("iter {:.02?}", iter),
+            (None, Some(jump)) => println!("jump {:.02?}", jump),
+            (None, None) => println!("all phases skipped"),
+        }
+    }
+    if let Some(drift) = sentinel::drift(runs) {
+        println!("sentinel drift: {:+.02}% between the first and the last runs", drift);
+    }
+    if let Some(spread) = sentinel::spread(runs) {
+        println!("sentinel spread: {:.02}% between the fastest and the slowest runs", spread);
+    }
+    println!();
+}
+
+/// Tests the best configurations against the baseline when they were evaluated
+/// repeatedly, the results must already be sorted.
+fn compare_repetitions(
+    results: &[(Parameters, Outcome)],
+    sort: &SortOptions,
+    evaluation: &EvaluationOptions,
+) -> Option<Vec<Comparison>> {
+    if evaluation.repetitions.get() == 1 {
+        return None;
+    }
+
+    let baseline = significance::baseline_parameters().file_name();
+    let key = sort.sort_by.first()?;
+    match significance::compare_to_baseline(results, key) {
+        Some(comparisons) => {
+            println!("compared to the {} baseline on {}:", baseline, key);
+            for comparison in &comparisons {
+                let verdict = match comparison.p_value {
+                    Some(p) if comparison.is_significant() => format!("significant, p = {:.03}", p),
+                    Some(p) => format!("not significant, p = {:.03}", p),
+                    None => "not testable".to_string(),
+                };
+                println!(
+                    "{}: {:+.02}% ({})",
+                    comparison.params.file_name(),
+                    comparison.difference(),
+                    verdict,
+                );
+            }
+            println!();
+            Some(comparisons)
+        }
+        None => {
+            println!("the {} baseline failed, the significance can't be tested", baseline);
+            None
+        }
+    }
+}
+
+fn print_results(params: &Parameters, results: &Results) {
+    println!("{:#?}", params);
+    print_results_body(results);
+}
+
+fn print_results_body(results: &Results) {
+    print_timings(results.timings());
+    let throughputs = [
+        results.iter_throughput().map(|t| format!("{:.0} entries/s iterating", t)),
+        results.jump_throughput().map(|t| format!("{:.0} lookups/s jumping", t)),
+        results.read_bandwidth().map(|b| format!("{:.02} MB/s read", b)),
+    ];
+    let throughputs: Vec<_> = throughputs.iter().flatten().map(String::as_str).collect();
+    if !throughputs.is_empty() {
+        println!("throughput: {}", throughputs.join(", "));
+    }
+    if results.samples.len() > 1 {
+        println!("medians of {} repetitions", results.samples.len());
+    }
+    if let Some(variation) = results.variation() {
+        let noisy = if results.noisy { " (noisy)" } else { "" };
+        println!("variation: {:.02}%{}", variation, noisy);
+    }
+    if let Some(elapsed) = results.write_time {
+        println!("took {:.02?} to write the file", elapsed);
+    }
+    if let Some(elapsed) = results.ingestion_time {
+        println!("took {:.02?} to append the entries into LMDB", elapsed);
+    }
+    if let Some(cost) = results.pipeline_cost {
+        println!("pipeline cost: {:.02}", cost);
+    }
+    println!("validation: {}", results.validation);
+    println!("file size: {} bytes", results.file_size);
+    if let Some(joules) = results.energy {
+        println!("consumed {:.02} J", joules);
+    }
+    let (before, after) = (results.telemetry_before, results.telemetry_after);
+    if let (Some(before), Some(after)) = (before.frequency_mhz, after.frequency_mhz) {
+        println!("cpu frequency: {:.0} MHz before, {:.0} MHz after", before, after);
+    }
+    if let (Some(before), Some(after)) = (before.temperature, after.temperature) {
+        println!("package temperature: {:.01}°C before, {:.01}°C after", before, after);
+    }
+    if let Some(honored) = results.huge_pages {
+        println!("huge pages {}", if honored { "honored" } else { "not honored" });
+    }
+    println!();
+}
+
+/// Prints the results of the input file evaluated as is and, for every sort key,
+/// what the best re-encoded configuration gains over it and over its current parameters.
+fn print_input_file_results(
+    input: &Outcome,
+    current: Option<Parameters>,
+    results: &[(Parameters, Outcome)],
+    sort_by: &[SortExpression],
+) {
+    println!("baseline (input file)");
+    if let Some(current) = current {
+        println!("parameters: {} (current)", current);
+    }
+    let input = match input {
+        Ok(input) => input,
+        Err(error) => {
+            println!("status: failed");
+            println!("error: {}", error);
+            println!();
+            return;
+        }
+    };
+    print_results_body(input);
+
+    for key in sort_by {
+        let best = results
+            .iter()
+            .filter_map(|(params, outcome)| Some((params, key.score(outcome.as_ref().ok()?)?)))
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        match (best, key.score(input)) {
+            (Some((params, best)), Some(baseline)) if baseline != 0.0 => println!(
+                "{}: {} scores {:+.02}% against the input file",
+                key,
+                params.file_name(),
+                (best - baseline) / baseline * 100.0,
+            ),
+            _ => println!("{}: no configuration can be compared to the input file", key),
+        }
+
+        let current_score = current.and_then(|current| {
+            let (_, outcome) = results
+                .iter()
+                .find(|(params, _)| significance::is_same_configuration(params, &current))?;
+            key.score(outcome.as_ref().ok()?)
+        });
+        match (best, current_score) {
+            (Some((params, best)), Some(current)) if current != 0.0 => println!(
+                "{}: {} scores {:+.02}% against the current parameters",
+                key,
+                params.file_name(),
+                (best - current) / current * 100.0,
+            ),
+            (_, None) if current.is_some() => {
+                println!("{}: the current parameters were not evaluated", key)
+            }
+            _ => (),
+        }
+    }
+    println!();
+}
+
+/// Prints the timings of the read methods evaluated on the same file side by side,
+/// relative to the fastest method on the total of the phases.
+fn print_read_methods_table(results: &[(ReadMethod, Results)]) {
+    let total = |results: &Results| {
+        let timings = results.timings();
+        [timings.iter_time, timings.keys_iter_time, timings.jump_time]
+            .iter()
+            .flatten()
+            .sum::<Duration>()
+    };
+    let fastest = results.iter().map(|(_, results)| total(results)).min().unwrap_or_default();
+    let cell = |time: Option<Duration>| {
+        time.map_or_else(|| "skipped".to_string(), |t| format!("{:.02?}", t))
+    };
+
+    println!(
+        "{:>24} {:>12} {:>12} {:>12} {:>12} {:>10}",
+        "read method", "iter", "keys iter", "jump", "total", "vs fastest"
+    );
+    for (method, results) in results {
+        let total = total(results);
+        let slowdown = if fastest.is_zero() {
+            0.0
+        } else {
+            (total.as_secs_f64() / fastest.as_secs_f64() - 1.0) * 100.0
+        };
+        println!(
+            "{:>24} {:>12} {:>12} {:>12} {:>12.02?} {:>+9.01}%",
+            method.name(),
+            cell(results.iter_time),
+            cell(results.keys_iter_time),
+            cell(results.jump_time),
+            total,
+            slowdown,
+        );
+    }
+    println!();
+}
+
+fn print_timings(timings: PhaseTimings) {
+    match timings.iter_time {
+        Some(elapsed) => println!("took {:.02?} to iterate over values", elapsed),
+        None => println!("iteration over values skipped"),
+    }
+    if let Some(elapsed) = timings.keys_iter_time {
+        println!("took {:.02?} to iterate over keys only", elapsed);
+    }
+    match timings.jump_time {
+        Some(elapsed) => println!("took {:.02?} to jump over values", elapsed),
+        None => println!("jumps over values skipped"),
+    }
+    if let Some(elapsed) = timings.exact_get_time {
+        println!("took {:.02?} to get the exact keys", elapsed);
+    }
+    let phases = [
+        ("iterating", timings.iter_time, timings.cpu.iter),
+        ("iterating over keys", timings.keys_iter_time, timings.cpu.keys_iter),
+        ("jumping", timings.jump_time, timings.cpu.jump),
+        ("getting the exact keys", timings.exact_get_time, timings.cpu.exact_get),
+    ];
+    for (phase, elapsed, cpu) in phases {
+        if let (Some(share), Some(cpu)) = (cpu_share(cpu, elapsed), cpu) {
+            println!(
+                "{}: {:.02?} user and {:.02?} system CPU time, {:.0}% of the wall time",
+                phase, cpu.user, cpu.system, share
+            );
+        }
+    }
+    if let Some(slo) = timings.slo {
+        let status = if slo.passed { "met" } else { "missed" };
+        println!("latency objectives {} with a margin of {:.02}", status, slo.margin);
+    }
+    if let Some(split) = timings.warm_cold {
+        if let (Some(cold), Some(warm)) = (split.cold_median, split.warm_median) {
+            println!(
+                "cold seeks: {} with a median of {:.02?}, warm seeks: {} with a median of {:.02?}",
+                split.cold_seeks, cold, split.warm_seeks, warm
+            );
+        } else {
+            println!("{} cold and {} warm seeks", split.cold_seeks, split.warm_seeks);
+        }
+    }
+    if let Some(seeks) = timings.seeks {
+        println!("{:.02} seeks per lookup on average, {} at most", seeks.average, seeks.max);
+    }
+}
+
+/// Executes the workload on the opened file, wrapped by the simulations of the storage.
+fn test_cursor<W: Words + ?Sized>(
+    mut reader: Box<dyn ReadSeek>,
+    words: &W,
+    lookups: &[usize],
+    workload: &WorkloadOptions,
+    max_bitmap_len: Option<u64>,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<PhaseTimings> {
+    if let Some(throttle) = workload.throttle() {
+        reader = Box::new(ThrottledReader::new(reader, throttle)?);
+    }
+    if let Some(spec) = &workload.inject_faults {
+        reader = Box::new(FaultInjector::new(reader, spec));
+    }
+    test_cursor_tracked(reader, words, lookups, workload, max_bitmap_len, progress)
+}
+
+/// Executes the workload, the seeks are counted and the reads are tracked
+/// when the seeks are split between the cold and the warm ones.
+fn test_cursor_tracked<R: io::Read + io::Seek, W: Words + ?Sized>(
     reader: R,
-    words: &[String],
-    entry_count: u64,
-) -> anyhow::Result<(Duration, Duration)> {
+    words: &W,
+    lookups: &[usize],
+    workload: &WorkloadOptions,
+    max_bitmap_len: Option<u64>,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<PhaseTimings> {
+    let seeks = SeekLog::default();
+    let reader = SeekCounter::new(reader, seeks.clone());
+    if workload.warm_cold {
+        let touches = TouchLog::default();
+        let reader = TouchTracker::new(reader, touches.clone())?;
+        let logs = CursorLogs { seeks: &seeks, touches: Some(&touches) };
+        test_cursor_phases(reader, words, lookups, workload, max_bitmap_len, logs, progress)
+    } else {
+        let logs = CursorLogs { seeks: &seeks, touches: None };
+        test_cursor_phases(reader, words, lookups, workload, max_bitmap_len, logs, progress)
+    }
+}
+
+/// The logs of the reads of the cursor, shared with its wrapped reader.
+#[derive(Copy, Clone)]
+struct CursorLogs<'a> {
+    seeks: &'a SeekLog,
+    /// The offsets read, `None` if the seeks are not split between cold and warm ones.
+    touches: Option<&'a TouchLog>,
+}
+
+/// Executes the phases of the workload, the seeks are split between
+/// the cold and the warm ones when the reads of the reader are logged.
+fn test_cursor_phases<R: io::Read + io::Seek, W: Words + ?Sized>(
+    reader: R,
+    words: &W,
+    lookups: &[usize],
+    workload: &WorkloadOptions,
+    max_bitmap_len: Option<u64>,
+    logs: CursorLogs,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<PhaseTimings> {
+    let touches = logs.touches;
     let mut cursor = Reader::new(reader)?.into_cursor()?;
+    let mut expected = Vec::new();
+
+    // The keys of a sample can't be compared with the ones iterated over.
+    let sampled = workload.entry_count.is_some();
+    let mut cpu = PhaseCpuTimes::default();
+    let mut iter_elapsed = None;
+    if workload.phases.contains(&Phase::Iter) {
+        progress.start_phase("iterating", cursor.len());
+        let cpu_before = platform::thread_cpu_time();
+        let before_iter = Instant::now();
+        let mut i = 0;
+        while let Some((k, v)) = cursor.move_on_next()? {
+            if workload.validate.should_validate(i as u64) {
+                if !sampled {
+                    assert_eq!(k, words.word(i));
+                }
+                check_value(workload, max_bitmap_len, i, v, &mut expected);
+            }
+            i += 1;
+            if i as u64 % PROGRESS_STEP == 0 {
+                progress.set_position(i as u64);
+            }
+        }
+        iter_elapsed = Some(before_iter.elapsed());
+        cpu.iter = platform::thread_cpu_time_since(cpu_before);
+    }
 
-    let before_iter = Instant::now();
-    let mut i = 0;
-    while let Some((k, v)) = cursor.move_on_next()? {
-        assert_eq!(k, words[i].as_bytes());
-        let bitmap = RoaringBitmap::deserialize_from(v).unwrap();
-        assert!(bitmap.len() <= MAX_BITMAP_LEN as u64);
-        i += 1;
+    let mut keys_iter_elapsed = None;
+    if workload.phases.contains(&Phase::KeysIter) {
+        // The values are returned by the cursor but never touched nor deserialized.
+        cursor.reset();
+        progress.start_phase("iterating over keys", cursor.len());
+        let cpu_before = platform::thread_cpu_time();
+        let before_iter = Instant::now();
+        let mut i = 0;
+        while let Some((k, _)) = cursor.move_on_next()? {
+            if !sampled && workload.validate.should_validate(i as u64) {
+                assert_eq!(k, words.word(i));
+            }
+            i += 1;
+            if i as u64 % PROGRESS_STEP == 0 {
+                progress.set_position(i as u64);
+            }
+        }
+        keys_iter_elapsed = Some(before_iter.elapsed());
+        cpu.keys_iter = platform::thread_cpu_time_since(cpu_before);
+    }
+
+    let mut jump_elapsed = None;
+    let mut slo = None;
+    let mut warm_cold = None;
+    let mut seeks = None;
+    let trace = workload.trace.as_ref().filter(|_| workload.phases.contains(&Phase::Jump));
+    if let Some(trace) = trace {
+        let cpu_before = platform::thread_cpu_time();
+        jump_elapsed = Some(trace.replay(&mut cursor, progress)?);
+        cpu.jump = platform::thread_cpu_time_since(cpu_before);
+    } else if workload.phases.contains(&Phase::Jump) {
+        progress.start_phase("jumping", lookups.len() as u64);
+        let timed_seeks = !workload.slo.is_empty() || touches.is_some();
+        let mut latencies = Vec::with_capacity(if timed_seeks { lookups.len() } else { 0 });
+        let (mut cold, mut warm) = (Vec::new(), Vec::new());
+        let (mut total_seeks, mut max_seeks) = (0, 0);
+        let cpu_before = platform::thread_cpu_time();
+        let before_jump = Instant::now();
+        for (n, &index) in (0..).zip(lookups) {
+            if n % PROGRESS_STEP == 0 {
+                progress.set_position(n);
+            }
+            let word = words.word(index);
+            let touched_before = touches.map(TouchLog::distinct);
+            let seeks_before = logs.seeks.count();
+            let before_seek = timed_seeks.then(Instant::now);
+            let (k, v) = cursor.move_on_key_greater_than_or_equal_to(word)?.unwrap();
+            let seek_count = logs.seeks.count() - seeks_before;
+            total_seeks += seek_count;
+            max_seeks = max_seeks.max(seek_count);
+            if let Some(before_seek) = before_seek {
+                let latency = before_seek.elapsed();
+                match (touches, touched_before) {
+                    (Some(touches), Some(before)) if touches.distinct() > before => {
+                        cold.push(latency)
+                    }
+                    (Some(_), _) => warm.push(latency),
+                    (None, _) => (),
+                }
+                latencies.push(latency);
+            }
+            if workload.validate.should_validate(n) {
+                assert_eq!(k, word);
+                check_value(workload, max_bitmap_len, index, v, &mut expected);
+            }
+        }
+        jump_elapsed = Some(before_jump.elapsed());
+        cpu.jump = platform::thread_cpu_time_since(cpu_before);
+        slo = slo::evaluate(&workload.slo, &mut latencies);
+        warm_cold = touches.map(|_| WarmColdSplit::from_latencies(cold, warm));
+        seeks = SeekCounts::new(total_seeks, max_seeks, lookups.len() as u64);
     }
-    let iter_elapsed = before_iter.elapsed();
 
-    let before_jump = Instant::now();
-    for _ in 0..entry_count {
-        let word = words.choose(&mut rng).unwrap();
-        let (k, v) = cursor.move_on_key_greater_than_or_equal_to(&word)?.unwrap();
-        assert_eq!(k, word.as_bytes());
-        let bitmap = RoaringBitmap::deserialize_from(v).unwrap();
-        assert!(bitmap.len() <= MAX_BITMAP_LEN as u64);
-        i += 1;
+    let mut exact_get_elapsed = None;
+    if workload.phases.contains(&Phase::ExactGet) {
+        // Every other key is absent, the word of the lookup followed by a zero
+        // byte, the oracle is the binary search of the key in the sorted words.
+        let keys: Vec<_> = (0..)
+            .zip(lookups)
+            .map(|(n, &index)| {
+                let mut key = words.word(index).to_vec();
+                if n % 2 == 1 {
+                    key.push(0);
+                }
+                let found = words.binary_search(&key).ok();
+                (key, found)
+            })
+            .collect();
+        progress.start_phase("getting exact keys", keys.len() as u64);
+        let cpu_before = platform::thread_cpu_time();
+        let before_get = Instant::now();
+        for (n, (key, found)) in (0..).zip(&keys) {
+            if n % PROGRESS_STEP == 0 {
+                progress.set_position(n);
+            }
+            let entry = cursor.move_on_key_equal_to(key)?;
+            if workload.validate.should_validate(n) {
+                match (entry, *found) {
+                    (Some((k, v)), Some(index)) => {
+                        assert_eq!(k, &key[..]);
+                        check_value(workload, max_bitmap_len, index, v, &mut expected);
+                    }
+                    (None, None) => (),
+                    (entry, found) => panic!(
+                        "the exact get of {:?} returned {:?} instead of the entry {:?}",
+                        key,
+                        entry.map(|(k, _)| k),
+                        found,
+                    ),
+                }
+            }
+        }
+        exact_get_elapsed = Some(before_get.elapsed());
+        cpu.exact_get = platform::thread_cpu_time_since(cpu_before);
     }
 
-    Ok((iter_elapsed, before_jump.elapsed()))
+    progress.finish();
+
+    Ok(PhaseTimings {
+        iter_time: iter_elapsed,
+        keys_iter_time: keys_iter_elapsed,
+        jump_time: jump_elapsed,
+        exact_get_time: exact_get_elapsed,
+        slo,
+        warm_cold,
+        seeks,
+        cpu,
+    })
 }
 
-fn test_lmdb<RN: Rng>(
-    mut rng: RN,
+/// Evaluates the LMDB database in its layout.
+#[allow(clippy::too_many_arguments)]
+fn test_lmdb_layout(
     rtxn: &RoTxn,
     database: Database,
-    words: &[String],
-    entry_count: u64,
-) -> anyhow::Result<(Duration, Duration)> {
-    let before_iter = Instant::now();
-    let mut i = 0;
-    for result in database.iter(rtxn)? {
-        let (k, v) = result?;
-        assert_eq!(k, words[i].as_bytes());
-        let bitmap = RoaringBitmap::deserialize_from(v).unwrap();
-        assert!(bitmap.len() <= MAX_BITMAP_LEN as u64);
-        i += 1;
+    words: &[Vec<u8>],
+    lookups: &[usize],
+    workload: &WorkloadOptions,
+    options: &LmdbOptions,
+    max_bitmap_len: Option<u64>,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<PhaseTimings> {
+    match options.lmdb_layout {
+        LmdbLayout::Bitmaps => test_lmdb(
+            rtxn,
+            database,
+            words,
+            lookups,
+            workload,
+            options.lmdb_value_compression,
+            max_bitmap_len,
+            progress,
+        ),
+        LmdbLayout::CompositeKeys => lmdb_layout::test_composite_keys(
+            rtxn,
+            database,
+            words,
+            lookups,
+            workload,
+            max_bitmap_len,
+            progress,
+        ),
+    }
+}
+
+/// Evaluates the bitmaps layout, the values are decompressed
+/// when they are read, whether they are validated or not.
+#[allow(clippy::too_many_arguments)]
+fn test_lmdb(
+    rtxn: &RoTxn,
+    database: Database,
+    words: &[Vec<u8>],
+    lookups: &[usize],
+    workload: &WorkloadOptions,
+    compression: CompressionType,
+    max_bitmap_len: Option<u64>,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<PhaseTimings> {
+    let mut expected = Vec::new();
+    let mut value = Vec::new();
+    let mut cpu = PhaseCpuTimes::default();
+    let mut iter_elapsed = None;
+    if workload.phases.contains(&Phase::Iter) {
+        progress.start_phase("iterating", words.len() as u64);
+        let cpu_before = platform::thread_cpu_time();
+        let before_iter = Instant::now();
+        let mut i = 0;
+        for result in database.iter(rtxn)? {
+            let (k, v) = result?;
+            let v = value_compression::decompressed(compression, v, &mut value)?;
+            if workload.validate.should_validate(i as u64) {
+                assert_eq!(k, &words[i][..]);
+                check_value(workload, max_bitmap_len, i, v, &mut expected);
+            }
+            i += 1;
+            if i as u64 % PROGRESS_STEP == 0 {
+                progress.set_position(i as u64);
+            }
+        }
+        iter_elapsed = Some(before_iter.elapsed());
+        cpu.iter = platform::thread_cpu_time_since(cpu_before);
+    }
+
+    let mut keys_iter_elapsed = None;
+    if workload.phases.contains(&Phase::KeysIter) {
+        progress.start_phase("iterating over keys", words.len() as u64);
+        let cpu_before = platform::thread_cpu_time();
+        let before_iter = Instant::now();
+        let mut i = 0;
+        // LMDB returns the values without copying them, they are never read.
+        for result in database.iter(rtxn)? {
+            let (k, _) = result?;
+            if workload.validate.should_validate(i as u64) {
+                assert_eq!(k, &words[i][..]);
+            }
+            i += 1;
+            if i as u64 % PROGRESS_STEP == 0 {
+                progress.set_position(i as u64);
+            }
+        }
+        keys_iter_elapsed = Some(before_iter.elapsed());
+        cpu.keys_iter = platform::thread_cpu_time_since(cpu_before);
     }
-    let iter_elapsed = before_iter.elapsed();
 
-    let before_jump = Instant::now();
-    for _ in 0..entry_count {
-        let word = words.choose(&mut rng).unwrap();
-        let (k, v) = database.get_greater_than_or_equal_to(rtxn, &word)?.unwrap();
-        assert_eq!(k, word.as_bytes());
-        let bitmap = RoaringBitmap::deserialize_from(v).unwrap();
-        assert!(bitmap.len() <= MAX_BITMAP_LEN as u64);
-        i += 1;
+    let mut jump_elapsed = None;
+    let mut slo = None;
+    if workload.phases.contains(&Phase::Jump) {
+        progress.start_phase("jumping", lookups.len() as u64);
+        let timed_seeks = !workload.slo.is_empty();
+        let mut latencies = Vec::with_capacity(if timed_seeks { lookups.len() } else { 0 });
+        let cpu_before = platform::thread_cpu_time();
+        let before_jump = Instant::now();
+        for (n, &index) in (0..).zip(lookups) {
+            if n % PROGRESS_STEP == 0 {
+                progress.set_position(n);
+            }
+            let word = &words[index];
+            let before_seek = timed_seeks.then(Instant::now);
+            let (k, v) = database.get_greater_than_or_equal_to(rtxn, &word)?.unwrap();
+            let v = value_compression::decompressed(compression, v, &mut value)?;
+            if let Some(before_seek) = before_seek {
+                latencies.push(before_seek.elapsed());
+            }
+            if workload.validate.should_validate(n) {
+                assert_eq!(k, &word[..]);
+                check_value(workload, max_bitmap_len, index, v, &mut expected);
+            }
+        }
+        jump_elapsed = Some(before_jump.elapsed());
+        cpu.jump = platform::thread_cpu_time_since(cpu_before);
+        slo = slo::evaluate(&workload.slo, &mut latencies);
+    }
+
+    progress.finish();
+
+    Ok(PhaseTimings {
+        iter_time: iter_elapsed,
+        keys_iter_time: keys_iter_elapsed,
+        jump_time: jump_elapsed,
+        exact_get_time: None,
+        slo,
+        warm_cold: None,
+        seeks: None,
+        cpu,
+    })
+}
+
+/// Checks the value of the entry of the word at `index`, it is compared with
+/// the generated one when deep validating.
+fn check_value(
+    workload: &WorkloadOptions,
+    max_bitmap_len: Option<u64>,
+    index: usize,
+    value: &[u8],
+    expected: &mut Vec<u8>,
+) {
+    manifest::check_bitmap(value, max_bitmap_len);
+    if let (true, Some(values)) = (workload.deep_validate, workload.values) {
+        values.generate(index as u64, expected);
+        assert!(
+            value == &expected[..],
+            "the value of the entry {} is not the generated one",
+            index
+        );
     }
+}
 
-    Ok((iter_elapsed, before_jump.elapsed()))
+/// Parses the parameters of a name like `Snappy.2.4096.16`, the name of a generated
+/// file, its path or the name of a configuration in the reports.
+fn parse_params_name(s: &str) -> Result<Parameters, String> {
+    let name = Path::new(s).file_name().and_then(|name| name.to_str()).unwrap_or(s);
+    name.parse()
 }
 
-fn name_from_params(params: &Parameters) -> String {
-    format!(
-        "{:?}.{}.{}.{}.grd",
-        params.compression, params.index_levels, params.block_size, params.index_key_interval
-    )
+/// A generated grenad file, the write time is unknown when the file
+/// was already generated by a previous run and is reused.
+#[derive(Debug, Clone)]
+struct GeneratedFile {
+    path: PathBuf,
+    write_time: Option<Duration>,
 }
 
-fn random_generate_from_params<P: AsRef<Path>, R: Rng>(
-    mut rng: R,
+/// Writes the words with the values generated for their index, the file is reused
+/// if it already exists, the folder must be dedicated to the value generator.
+fn generate_with_values<P: AsRef<Path>, W: Words + ?Sized>(
+    values: ValueGenerator,
     folder: P,
-    words: &[String],
+    words: &W,
     params: &Parameters,
-) -> anyhow::Result<File> {
-    let filename = name_from_params(params);
-    let filepath = folder.as_ref().join(filename);
-    match OpenOptions::new().create_new(true).write(true).read(true).open(&filepath) {
-        Ok(file) => {
-            let mut writer = WriterBuilder::new()
-                .compression_type(params.compression)
-                .index_levels(params.index_levels)
-                .block_size(params.block_size)
-                .index_key_interval(params.index_key_interval)
-                .build(BufWriter::new(file));
+    generation: GenerationOptions,
+) -> anyhow::Result<GeneratedFile> {
+    let manifest = Manifest::of_values(&values);
+    generate_file(folder, params, manifest, generation, |writer| {
+        let mut buffer = Vec::new();
+        for i in 0..words.len() {
+            values.generate(i as u64, &mut buffer);
+            writer.insert(words.word(i), &buffer)?;
+        }
+        Ok(())
+    })
+}
 
-            let mut buffer = Vec::new();
+/// The extension of the files being generated, `clean` removes the leftovers.
+const TEMPORARY_EXTENSION: &str = "grd.tmp";
 
-            for word in words {
-                random_generate_roaring(&mut rng, &mut buffer);
-                writer.insert(word, &buffer)?;
-            }
+/// The extension of the files locked during the generation of a file, they are left
+/// for the concurrent runs to lock the same file, `clean` removes them.
+const LOCK_EXTENSION: &str = "grd.lock";
 
-            Ok(writer.into_inner()?.into_inner()?)
-        }
-        Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(File::open(filepath)?),
-        Err(e) => Err(e.into()),
+/// Writes the file of the parameters with the given entries, the file is reused if
+/// it already exists. The generation is locked, a run sharing the folder waits for
+/// the file or fails, depending on `wait_for_lock`, it never reads a partial file.
+fn generate_file<P: AsRef<Path>>(
+    folder: P,
+    params: &Parameters,
+    mut manifest: Manifest,
+    generation: GenerationOptions,
+    insert_entries: impl FnOnce(&mut Writer<ChecksumWriter<BufWriter<File>>>) -> anyhow::Result<()>,
+) -> anyhow::Result<GeneratedFile> {
+    let filepath = folder.as_ref().join(params.file_name());
+    let lock_path = filepath.with_extension(LOCK_EXTENSION);
+    let _lock = platform::lock_file(&lock_path, generation.wait_for_lock).with_context(|| {
+        format!("while locking {}, use --wait-for-lock to wait for it", lock_path.display())
+    })?;
+    match fs::metadata(&filepath) {
+        Ok(_) => return Ok(GeneratedFile { path: filepath, write_time: None }),
+        Err(e) if e.kind() == ErrorKind::NotFound => (),
+        Err(e) => return Err(e.into()),
+    }
+
+    // The file is written under a temporary name and renamed once complete, an
+    // interrupted generation only leaves a temporary file that is never reused.
+    let temppath = filepath.with_extension(TEMPORARY_EXTENSION);
+    let result = (|| -> anyhow::Result<Duration> {
+        let before_write = Instant::now();
+        let file = File::create(&temppath)
+            .with_context(|| format!("while creating {}", temppath.display()))?;
+        let mut writer = WriterBuilder::new()
+            .compression_type(params.compression)
+            .index_levels(params.index_levels)
+            .block_size(params.block_size)
+            .index_key_interval(params.index_key_interval)
+            .build(ChecksumWriter::new(BufWriter::new(file), generation.checksum));
+        insert_entries(&mut writer)?;
+        let (writer, checksum) = writer.into_inner()?.into_parts();
+        writer.into_inner()?;
+        let elapsed = before_write.elapsed();
+        fs::rename(&temppath, &filepath)
+            .with_context(|| format!("while renaming {}", temppath.display()))?;
+        // The manifest describes the renamed file, a file without
+        // its manifest is removed to be generated again.
+        manifest.checksum = checksum;
+        remove_if_failed(&filepath, manifest.write(&filepath))?;
+        Ok(elapsed)
+    })();
+    remove_if_failed(&temppath, result)
+        .map(|elapsed| GeneratedFile { path: filepath, write_time: Some(elapsed) })
+}
+
+/// Removes a partially written file, it must not be reused by the next runs.
+fn remove_if_failed<T>(filepath: &Path, result: anyhow::Result<T>) -> anyhow::Result<T> {
+    if result.is_err() {
+        let _ = fs::remove_file(filepath);
     }
+    result
 }
 
-fn random_generate_lmdb<P: AsRef<Path>, R: Rng>(
-    mut rng: R,
+fn random_generate_lmdb<P: AsRef<Path>>(
+    values: ValueGenerator,
     folder: P,
-    words: &[String],
+    words: &[Vec<u8>],
+    options: &LmdbOptions,
 ) -> anyhow::Result<Env> {
     let filepath = folder.as_ref().join("random-lmdb").with_extension("mdb");
     fs::create_dir_all(&filepath)?;
@@ -689,13 +3919,14 @@ fn random_generate_lmdb<P: AsRef<Path>, R: Rng>(
     let mut wtxn = env.write_txn()?;
 
     let mut buffer = Vec::new();
+    let mut compressed = Vec::new();
 
     println!("Inserting values in LMDB...");
     let pb = ProgressBar::new(words.len() as u64)
         .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
-    for word in words.into_iter().progress_with(pb) {
-        random_generate_roaring(&mut rng, &mut buffer);
-        database.append(&mut wtxn, word, &buffer)?;
+    for (i, word) in words.iter().enumerate().progress_with(pb) {
+        values.generate(i as u64, &mut buffer);
+        lmdb_layout::append(options, database, &mut wtxn, word, &buffer, &mut compressed)?;
     }
 
     wtxn.commit()?;
@@ -703,47 +3934,29 @@ fn random_generate_lmdb<P: AsRef<Path>, R: Rng>(
     Ok(env)
 }
 
-fn random_generate_roaring<R: Rng>(rng: &mut R, buffer: &mut Vec<u8>) {
-    buffer.clear();
-    let start: u32 = rng.gen();
-    let end: u32 = start.saturating_add(rng.gen());
-    let roaring =
-        RoaringBitmap::from_sorted_iter((start..=end).filter(|_| rng.gen()).take(MAX_BITMAP_LEN))
-            .unwrap();
-    roaring.serialize_into(buffer).unwrap();
-}
-
 fn generate_from_params<P: AsRef<Path>, R: io::Read + io::Seek>(
     folder: P,
     cursor: &mut ReaderCursor<R>,
     params: &Parameters,
-) -> anyhow::Result<File> {
-    let filename = name_from_params(params);
-    let filepath = folder.as_ref().join(filename);
-    match OpenOptions::new().create_new(true).write(true).read(true).open(&filepath) {
-        Ok(file) => {
-            let mut writer = WriterBuilder::new()
-                .compression_type(params.compression)
-                .index_levels(params.index_levels)
-                .block_size(params.block_size)
-                .index_key_interval(params.index_key_interval)
-                .build(BufWriter::new(file));
-
-            cursor.reset();
-            while let Some((k, v)) = cursor.move_on_next()? {
+    generation: GenerationOptions,
+) -> anyhow::Result<GeneratedFile> {
+    generate_file(folder, params, Manifest::default(), generation, |writer| {
+        // The entries of a source out of order are skipped, see `--source-order warn`.
+        let mut sorted = SortedKeys::default();
+        cursor.reset();
+        while let Some((k, v)) = cursor.move_on_next()? {
+            if sorted.keep(k) {
                 writer.insert(k, v)?;
             }
-
-            Ok(writer.into_inner()?.into_inner()?)
         }
-        Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(File::open(filepath)?),
-        Err(e) => Err(e.into()),
-    }
+        Ok(())
+    })
 }
 
 fn generate_lmdb<P: AsRef<Path>, R: io::Read + io::Seek>(
     folder: P,
     cursor: &mut ReaderCursor<R>,
+    options: &LmdbOptions,
 ) -> anyhow::Result<Env> {
     let filepath = folder.as_ref().join("lmdb").with_extension("mdb");
     fs::create_dir_all(&filepath)?;
@@ -751,6 +3964,7 @@ fn generate_lmdb<P: AsRef<Path>, R: io::Read + io::Seek>(
     let env = EnvOpenOptions::new().map_size(FIVE_GIB).open(filepath)?;
     let database = env.create_database(None)?;
     let mut wtxn = env.write_txn()?;
+    let mut compressed = Vec::new();
 
     println!("Inserting values in LMDB...");
     let pb = ProgressBar::new(cursor.len())
@@ -758,7 +3972,7 @@ fn generate_lmdb<P: AsRef<Path>, R: io::Read + io::Seek>(
 
     cursor.reset();
     while let Some((k, v)) = cursor.move_on_next()? {
-        database.append(&mut wtxn, k, v)?;
+        lmdb_layout::append(options, database, &mut wtxn, k, v, &mut compressed)?;
         pb.inc(1);
     }
 
@@ -0,0 +1,97 @@
+//! The manifest of a generated dataset, a `<file>.json` sidecar recording how its
+//! values were generated, the validation reads the bounds of the values from it.
+//!
+//! The files re-encoded from an imported dataset, and the imported datasets, have no
+//! bound, their bitmaps are only deserialized, a bound of the generator would be
+//! meaningless for them. The checksum of every generated file is recorded too.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use roaring::RoaringBitmap;
+use serde_json::Value;
+
+use crate::checksum::Checksum;
+use crate::datagen::ValueGenerator;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Manifest {
+    /// The maximum cardinality of the bitmaps, `None` if it is not bounded.
+    pub max_bitmap_len: Option<u64>,
+    /// The checksum of the file, `None` if it was generated without `--checksum`.
+    pub checksum: Option<Checksum>,
+}
+
+impl Manifest {
+    pub fn of_values(values: &ValueGenerator) -> Manifest {
+        match *values {
+            ValueGenerator::Random { max_bitmap_len, .. } => {
+                Manifest { max_bitmap_len: Some(max_bitmap_len), checksum: None }
+            }
+            // The sized bitmaps are bounded by their size, not by their cardinality.
+            ValueGenerator::Sized { .. } => Manifest::default(),
+        }
+    }
+
+    pub fn path_of(dataset: &Path) -> PathBuf {
+        let mut path = dataset.as_os_str().to_owned();
+        path.push(".json");
+        PathBuf::from(path)
+    }
+
+    pub fn write(&self, dataset: &Path) -> anyhow::Result<()> {
+        let path = Manifest::path_of(dataset);
+        let checksum = self.checksum.map_or_else(
+            || "null".to_string(),
+            |checksum| {
+                format!("{{\"kind\":\"{}\",\"value\":\"{:016x}\"}}", checksum.kind, checksum.value)
+            },
+        );
+        let content = format!(
+            "{{\"max_bitmap_len\":{},\"checksum\":{}}}\n",
+            self.max_bitmap_len.map_or_else(|| "null".to_string(), |len| len.to_string()),
+            checksum,
+        );
+        fs::write(&path, content).with_context(|| format!("while writing {}", path.display()))
+    }
+
+    /// Reads the manifest of the dataset, `None` if it has none.
+    pub fn read(dataset: &Path) -> anyhow::Result<Option<Manifest>> {
+        let path = Manifest::path_of(dataset);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("while reading {}", path.display())),
+        };
+        let value: Value = serde_json::from_str(&content)
+            .with_context(|| format!("while parsing {}", path.display()))?;
+        let checksum = match &value["checksum"] {
+            Value::Null => None,
+            checksum => {
+                let kind = checksum["kind"].as_str().and_then(|kind| kind.parse().ok());
+                let value =
+                    checksum["value"].as_str().and_then(|v| u64::from_str_radix(v, 16).ok());
+                match (kind, value) {
+                    (Some(kind), Some(value)) => Some(Checksum { kind, value }),
+                    _ => anyhow::bail!("{} has an invalid checksum", path.display()),
+                }
+            }
+        };
+        Ok(Some(Manifest { max_bitmap_len: value["max_bitmap_len"].as_u64(), checksum }))
+    }
+
+    /// The maximum cardinality of the bitmaps of the dataset, `None` without manifest.
+    pub fn max_bitmap_len_of(dataset: &Path) -> anyhow::Result<Option<u64>> {
+        Ok(Manifest::read(dataset)?.and_then(|manifest| manifest.max_bitmap_len))
+    }
+}
+
+/// Deserializes the bitmap and checks its cardinality against the bound, if any.
+pub fn check_bitmap(value: &[u8], max_bitmap_len: Option<u64>) {
+    let bitmap = RoaringBitmap::deserialize_from(value).unwrap();
+    if let Some(max_bitmap_len) = max_bitmap_len {
+        assert!(bitmap.len() <= max_bitmap_len);
+    }
+}
@@ -0,0 +1,156 @@
+//! The registry of the merge functions of the sorter benchmark, the ones milli
+//! uses while indexing, each one with a generator of the values it expects.
+
+use std::borrow::Cow;
+use std::io;
+use std::str::FromStr;
+
+use rand::Rng;
+use roaring::RoaringBitmap;
+
+use crate::datagen::{self, Stream};
+
+pub const POSSIBLE_MERGE_FUNCTIONS: &[&str] =
+    &["union", "intersection", "keep-last", "concat-u32s", "cbo-union"];
+
+/// The maximum number of integers that milli stores as raw
+/// integers in a CboRoaringBitmap instead of a RoaringBitmap.
+const CBO_THRESHOLD: usize = 7;
+
+pub type MergeFn = for<'a> fn(&[u8], &[Cow<'a, [u8]>]) -> io::Result<Cow<'a, [u8]>>;
+
+#[derive(Debug, Copy, Clone)]
+pub enum MergeFunction {
+    /// The union of serialized RoaringBitmaps.
+    Union,
+    /// The intersection of serialized RoaringBitmaps.
+    Intersection,
+    /// Keeps the last inserted value.
+    KeepLast,
+    /// Concatenates arrays of native-endian u32s.
+    ConcatU32s,
+    /// The union of CboRoaringBitmaps, raw u32s under the threshold, RoaringBitmaps above.
+    CboUnion,
+}
+
+impl MergeFunction {
+    pub fn function(self) -> MergeFn {
+        match self {
+            MergeFunction::Union => roaring_union,
+            MergeFunction::Intersection => roaring_intersection,
+            MergeFunction::KeepLast => keep_last,
+            MergeFunction::ConcatU32s => concat_u32s,
+            MergeFunction::CboUnion => cbo_roaring_union,
+        }
+    }
+
+    /// Generates the value inserted at `index`, with the shape the merge function expects.
+    pub fn generate_value(self, seed: u64, index: u64, buffer: &mut Vec<u8>) {
+        let mut rng = datagen::entry_rng(seed, Stream::MergeValues, index);
+        buffer.clear();
+        match self {
+            MergeFunction::Union | MergeFunction::Intersection => {
+                // A narrow range of document ids for the bitmaps to overlap.
+                let len = rng.gen_range(1..=1000);
+                let bitmap: RoaringBitmap = (0..len).map(|_| rng.gen_range(0..100_000)).collect();
+                bitmap.serialize_into(buffer).unwrap();
+            }
+            MergeFunction::KeepLast => {
+                let len = rng.gen_range(8..=256);
+                buffer.extend((0..len).map(|_| rng.gen::<u8>()));
+            }
+            MergeFunction::ConcatU32s => {
+                let len = rng.gen_range(1..=16);
+                for _ in 0..len {
+                    buffer.extend_from_slice(&rng.gen::<u32>().to_ne_bytes());
+                }
+            }
+            MergeFunction::CboUnion => {
+                // Mostly small bitmaps for both representations to be exercised.
+                let len = if rng.gen_bool(0.8) { rng.gen_range(1..=CBO_THRESHOLD) } else { 100 };
+                let bitmap: RoaringBitmap = (0..len).map(|_| rng.gen_range(0..100_000)).collect();
+                serialize_cbo(&bitmap, buffer);
+            }
+        }
+    }
+}
+
+impl FromStr for MergeFunction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<MergeFunction, String> {
+        match s {
+            "union" => Ok(MergeFunction::Union),
+            "intersection" => Ok(MergeFunction::Intersection),
+            "keep-last" => Ok(MergeFunction::KeepLast),
+            "concat-u32s" => Ok(MergeFunction::ConcatU32s),
+            "cbo-union" => Ok(MergeFunction::CboUnion),
+            otherwise => Err(format!("invalid merge function {:?}", otherwise)),
+        }
+    }
+}
+
+fn roaring_union<'a>(_key: &[u8], values: &[Cow<'a, [u8]>]) -> io::Result<Cow<'a, [u8]>> {
+    let mut union = RoaringBitmap::new();
+    for value in values {
+        union |= RoaringBitmap::deserialize_from(&value[..])?;
+    }
+    let mut buffer = Vec::with_capacity(union.serialized_size());
+    union.serialize_into(&mut buffer)?;
+    Ok(Cow::Owned(buffer))
+}
+
+fn roaring_intersection<'a>(_key: &[u8], values: &[Cow<'a, [u8]>]) -> io::Result<Cow<'a, [u8]>> {
+    let (first, others) = match values.split_first() {
+        Some(split) => split,
+        None => return Ok(Cow::Borrowed(&[])),
+    };
+    let mut intersection = RoaringBitmap::deserialize_from(&first[..])?;
+    for value in others {
+        intersection &= RoaringBitmap::deserialize_from(&value[..])?;
+    }
+    let mut buffer = Vec::with_capacity(intersection.serialized_size());
+    intersection.serialize_into(&mut buffer)?;
+    Ok(Cow::Owned(buffer))
+}
+
+fn keep_last<'a>(_key: &[u8], values: &[Cow<'a, [u8]>]) -> io::Result<Cow<'a, [u8]>> {
+    Ok(values.last().cloned().unwrap_or_default())
+}
+
+fn concat_u32s<'a>(_key: &[u8], values: &[Cow<'a, [u8]>]) -> io::Result<Cow<'a, [u8]>> {
+    if values.len() == 1 {
+        return Ok(values[0].clone());
+    }
+    Ok(Cow::Owned(values.iter().flat_map(|value| value.iter().copied()).collect()))
+}
+
+fn cbo_roaring_union<'a>(_key: &[u8], values: &[Cow<'a, [u8]>]) -> io::Result<Cow<'a, [u8]>> {
+    let mut union = RoaringBitmap::new();
+    for value in values {
+        union |= deserialize_cbo(value)?;
+    }
+    let mut buffer = Vec::new();
+    serialize_cbo(&union, &mut buffer);
+    Ok(Cow::Owned(buffer))
+}
+
+fn serialize_cbo(bitmap: &RoaringBitmap, buffer: &mut Vec<u8>) {
+    if bitmap.len() as usize <= CBO_THRESHOLD {
+        bitmap.iter().for_each(|integer| buffer.extend_from_slice(&integer.to_ne_bytes()));
+    } else {
+        bitmap.serialize_into(buffer).unwrap();
+    }
+}
+
+fn deserialize_cbo(bytes: &[u8]) -> io::Result<RoaringBitmap> {
+    if bytes.len() <= CBO_THRESHOLD * std::mem::size_of::<u32>() {
+        let chunks = bytes.chunks_exact(std::mem::size_of::<u32>());
+        if !chunks.remainder().is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid CboRoaringBitmap"));
+        }
+        Ok(chunks.map(|chunk| u32::from_ne_bytes(chunk.try_into().unwrap())).collect())
+    } else {
+        RoaringBitmap::deserialize_from(bytes)
+    }
+}
@@ -0,0 +1,53 @@
+//! The metrics of the results by the names of the exported columns, the tidy export
+//! and the streamed results list them from `Results::metrics`, a new metric only
+//! needs to be measured and added there to be exported everywhere.
+
+use std::time::Duration;
+
+use crate::json;
+
+/// A measured value, the durations are serialized in nanoseconds.
+#[derive(Debug, Copy, Clone)]
+pub enum Value {
+    Time(Duration),
+    Number(f64),
+    Count(u64),
+}
+
+impl Value {
+    /// Serializes the value as a JSON number, `null` when it isn't finite,
+    /// the CSV exports use the same representation.
+    pub fn to_json(self) -> String {
+        match self {
+            Value::Time(duration) => json::nanos(Some(duration)),
+            Value::Number(number) => json::number(Some(number)),
+            Value::Count(count) => count.to_string(),
+        }
+    }
+}
+
+/// A metric of the results, `None` if it wasn't measured.
+#[derive(Debug, Copy, Clone)]
+pub struct Metric {
+    pub name: &'static str,
+    pub value: Option<Value>,
+}
+
+impl Metric {
+    pub fn time(name: &'static str, duration: Option<Duration>) -> Metric {
+        Metric { name, value: duration.map(Value::Time) }
+    }
+
+    pub fn number(name: &'static str, number: Option<f64>) -> Metric {
+        Metric { name, value: number.map(Value::Number) }
+    }
+
+    pub fn count(name: &'static str, count: Option<u64>) -> Metric {
+        Metric { name, value: count.map(Value::Count) }
+    }
+
+    /// The serialized value, `null` when it wasn't measured.
+    pub fn to_json(self) -> String {
+        self.value.map_or_else(|| "null".to_string(), Value::to_json)
+    }
+}
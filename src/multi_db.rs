@@ -0,0 +1,146 @@
+//! The multi-database workload, a search of milli reads several databases per query, the
+//! word docids, the prefix docids and the proximities. The files compete for the same
+//! page cache, the parameters of one of them can slow the lookups of the others down.
+//!
+//! Every database is first evaluated alone, then the queries are interleaved, every query
+//! does one lookup in each of the databases in turn. The slowdown of a database is the
+//! ratio between its lookups interleaved and alone, above one the other files evicted its
+//! pages or made its blocks slower to read.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use grenad::{Reader, ReaderCursor};
+
+use crate::datagen::KeyProfile;
+use crate::manifest::{self, Manifest};
+use crate::params::Parameters;
+use crate::read_method::{OpenOptions, ReadMethod, ReadSeek};
+
+/// A database of the workload, e.g. `words:Snappy.2.4096.16`, the shape of its keys
+/// and the parameters of its file.
+#[derive(Debug, Copy, Clone)]
+pub struct DatabaseSpec {
+    pub key_profile: KeyProfile,
+    pub params: Parameters,
+}
+
+impl FromStr for DatabaseSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DatabaseSpec, String> {
+        match s.split_once(':') {
+            Some((key_profile, params)) => {
+                Ok(DatabaseSpec { key_profile: key_profile.parse()?, params: params.parse()? })
+            }
+            None => Err(format!("`{}` is not a database like `words:Snappy.2.4096.16`", s)),
+        }
+    }
+}
+
+/// A generated database, its words and the words looked up by the queries.
+pub struct Database {
+    pub spec: DatabaseSpec,
+    pub path: PathBuf,
+    pub words: Vec<Vec<u8>>,
+    pub lookups: Vec<usize>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct DatabaseTimings {
+    /// The time of the lookups of the database evaluated alone.
+    pub alone: Duration,
+    /// The time of the same lookups interleaved with the ones of the other databases.
+    pub interleaved: Duration,
+}
+
+impl DatabaseTimings {
+    pub fn slowdown(&self) -> f64 {
+        self.interleaved.as_secs_f64() / self.alone.as_secs_f64()
+    }
+}
+
+/// A cursor over a database and the bounds of its bitmaps.
+type Opened = (ReaderCursor<Box<dyn ReadSeek>>, Option<u64>);
+
+fn open(
+    database: &Database,
+    read_method: ReadMethod,
+    options: &OpenOptions,
+) -> anyhow::Result<Opened> {
+    let max_bitmap_len = Manifest::max_bitmap_len_of(&database.path)?;
+    let reader = read_method.open(&database.path, options)?.reader;
+    Ok((Reader::new(reader)?.into_cursor()?, max_bitmap_len))
+}
+
+/// Looks the word up and validates its bitmap, returns the time of the lookup.
+fn lookup((cursor, max_bitmap_len): &mut Opened, word: &[u8]) -> anyhow::Result<Duration> {
+    let before_lookup = Instant::now();
+    let (k, v) = cursor.move_on_key_greater_than_or_equal_to(word)?.unwrap();
+    let elapsed = before_lookup.elapsed();
+    assert_eq!(k, word);
+    manifest::check_bitmap(v, *max_bitmap_len);
+    Ok(elapsed)
+}
+
+/// Evaluates the databases alone then interleaved, the files are opened again between the
+/// two workloads, their caches are dropped again when the options ask for it.
+pub fn run(
+    databases: &[Database],
+    read_method: ReadMethod,
+    options: &OpenOptions,
+) -> anyhow::Result<Vec<DatabaseTimings>> {
+    let mut alone = Vec::with_capacity(databases.len());
+    for database in databases {
+        let mut opened = open(database, read_method, options)?;
+        let mut elapsed = Duration::default();
+        for &index in &database.lookups {
+            elapsed += lookup(&mut opened, &database.words[index])?;
+        }
+        alone.push(elapsed);
+    }
+
+    let mut opened = databases
+        .iter()
+        .map(|database| open(database, read_method, options))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let mut interleaved = vec![Duration::default(); databases.len()];
+    let queries = databases.iter().map(|database| database.lookups.len()).max().unwrap_or(0);
+    for query in 0..queries {
+        for (i, database) in databases.iter().enumerate() {
+            if let Some(&index) = database.lookups.get(query) {
+                interleaved[i] += lookup(&mut opened[i], &database.words[index])?;
+            }
+        }
+    }
+
+    Ok(alone
+        .into_iter()
+        .zip(interleaved)
+        .map(|(alone, interleaved)| DatabaseTimings { alone, interleaved })
+        .collect())
+}
+
+pub fn print_multi_database_results(databases: &[Database], timings: &[DatabaseTimings]) {
+    println!(
+        "{:<24} {:<24} {:>12} {:>12} {:>9}",
+        "keys", "parameters", "alone", "interleaved", "slowdown"
+    );
+    for (database, timings) in databases.iter().zip(timings) {
+        println!(
+            "{:<24} {:<24} {:>12.02?} {:>12.02?} {:>8.02}x",
+            format!("{:?}", database.spec.key_profile),
+            database.spec.params.to_string(),
+            timings.alone,
+            timings.interleaved,
+            timings.slowdown(),
+        );
+    }
+    let alone: Duration = timings.iter().map(|t| t.alone).sum();
+    let interleaved: Duration = timings.iter().map(|t| t.interleaved).sum();
+    println!(
+        "the interleaved queries took {:.02?}, {:.02?} for the databases alone",
+        interleaved, alone
+    );
+}
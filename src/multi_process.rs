@@ -0,0 +1,218 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context};
+use grenad::Reader;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::manifest::{self, Manifest};
+use crate::platform::{self, MemoryCgroup};
+
+#[derive(Debug, Copy, Clone)]
+pub struct WorkerResults {
+    pub elapsed: Duration,
+    /// The resident memory of the mapping in KiB, `None` if it can't be read.
+    pub mapping_rss_kib: Option<u64>,
+    /// The part of the resident memory shared with other processes, in KiB.
+    pub mapping_shared_kib: Option<u64>,
+}
+
+/// Spawns `processes` copies of this binary that all memory-map the same file
+/// and start the jump workload at the same time, once every one of them is ready.
+///
+/// The workers read the memory of their mapping once every one of them jumped and
+/// exit once every one of them read it, the pages are shared by all the mappings then.
+pub fn run_workers(
+    folder: &Path,
+    file: &Path,
+    seed: u64,
+    lookups: u64,
+    processes: usize,
+) -> anyhow::Result<Vec<WorkerResults>> {
+    let mut children = Vec::with_capacity(processes);
+    for i in 0..processes {
+        children.push(spawn_worker(folder, file, seed + i as u64, lookups, false)?);
+    }
+
+    let mut stdouts = Vec::with_capacity(processes);
+    for child in &mut children {
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        expect_line(&mut stdout, "ready")?;
+        stdouts.push(stdout);
+    }
+
+    // Every worker extracted its words and mapped the file, we can start them all.
+    for child in &mut children {
+        writeln!(child.stdin.as_mut().unwrap(), "go")?;
+    }
+
+    for stdout in &mut stdouts {
+        expect_line(stdout, "jumped")?;
+    }
+    for child in &mut children {
+        writeln!(child.stdin.as_mut().unwrap(), "measure")?;
+    }
+
+    let mut results = Vec::with_capacity(processes);
+    for stdout in &mut stdouts {
+        let mut line = String::new();
+        stdout.read_line(&mut line)?;
+        results.push(parse_done_line(&line)?);
+    }
+
+    for child in &mut children {
+        writeln!(child.stdin.as_mut().unwrap(), "quit")?;
+    }
+    for child in children {
+        wait_for_success(child)?;
+    }
+
+    Ok(results)
+}
+
+/// Spawns a single jump worker limited by the cgroup, it is moved into the cgroup once it
+/// extracted its words, the file is evicted from the page cache first and every page the
+/// lookups read again is charged to the cgroup, the memory of the words is not.
+pub fn run_limited_worker(
+    folder: &Path,
+    file: &Path,
+    seed: u64,
+    lookups: u64,
+    cgroup: &MemoryCgroup,
+) -> anyhow::Result<WorkerResults> {
+    let mut child = spawn_worker(folder, file, seed, lookups, true)?;
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    expect_line(&mut stdout, "ready")?;
+    cgroup.add_process(child.id()).context("while moving the jump worker into its cgroup")?;
+    writeln!(child.stdin.as_mut().unwrap(), "go")?;
+    expect_line(&mut stdout, "jumped")?;
+    writeln!(child.stdin.as_mut().unwrap(), "measure")?;
+
+    let mut line = String::new();
+    stdout.read_line(&mut line)?;
+    let results = parse_done_line(&line)?;
+    writeln!(child.stdin.as_mut().unwrap(), "quit")?;
+    wait_for_success(child)?;
+    Ok(results)
+}
+
+fn spawn_worker(
+    folder: &Path,
+    file: &Path,
+    seed: u64,
+    lookups: u64,
+    cold: bool,
+) -> anyhow::Result<Child> {
+    let mut command = Command::new(env::current_exe()?);
+    command
+        .arg("--folder")
+        .arg(folder)
+        .arg("jump-worker")
+        .arg("--file")
+        .arg(file)
+        .arg("--seed")
+        .arg(seed.to_string())
+        .arg("--lookups")
+        .arg(lookups.to_string());
+    if cold {
+        command.arg("--cold");
+    }
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("while spawning a jump worker")
+}
+
+/// The entry point of the processes spawned by `run_workers` and `run_limited_worker`,
+/// the file is evicted from the page cache once the words are extracted when `cold`,
+/// the mapping is measured and released when the parent asks for it.
+pub fn jump_worker(path: &Path, seed: u64, lookups: u64, cold: bool) -> anyhow::Result<()> {
+    let max_bitmap_len = Manifest::max_bitmap_len_of(path)?;
+    let file = File::open(path).with_context(|| format!("while opening {}", path.display()))?;
+    let mut map = unsafe { memmap2::Mmap::map(&file)? };
+    let mut cursor = Reader::new(Cursor::new(&map))?.into_cursor()?;
+
+    let mut words = Vec::with_capacity(cursor.len() as usize);
+    while let Some((k, _)) = cursor.move_on_next()? {
+        words.push(k.to_vec());
+    }
+    drop(cursor);
+    if words.is_empty() {
+        bail!("{} has no entries to jump to", path.display());
+    }
+
+    if cold {
+        // The mapped pages can't be evicted, the file is mapped again once evicted.
+        drop(map);
+        platform::drop_file_cache(path)
+            .with_context(|| format!("while dropping the cache of {}", path.display()))?;
+        map = unsafe { memmap2::Mmap::map(&file)? };
+    }
+
+    let mut stdout = io::stdout();
+    writeln!(stdout, "ready")?;
+    stdout.flush()?;
+    expect_line(&mut io::stdin().lock(), "go")?;
+
+    let mut cursor = Reader::new(Cursor::new(&map))?.into_cursor()?;
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let before_jump = Instant::now();
+    for _ in 0..lookups {
+        let word = words.choose(&mut rng).unwrap();
+        let (k, v) = cursor.move_on_key_greater_than_or_equal_to(word)?.unwrap();
+        assert_eq!(k, &word[..]);
+        manifest::check_bitmap(v, max_bitmap_len);
+    }
+    let elapsed = before_jump.elapsed();
+    writeln!(stdout, "jumped")?;
+    stdout.flush()?;
+
+    // The peers still map the file, the pages they read are counted as shared.
+    expect_line(&mut io::stdin().lock(), "measure")?;
+    let (rss, shared) = match platform::mapping_memory(&map) {
+        Some((rss, shared)) => (rss.to_string(), shared.to_string()),
+        None => ("-".to_string(), "-".to_string()),
+    };
+    writeln!(stdout, "done {} {} {}", elapsed.as_nanos(), rss, shared)?;
+    stdout.flush()?;
+    expect_line(&mut io::stdin().lock(), "quit")?;
+
+    Ok(())
+}
+
+fn expect_line<R: BufRead>(reader: &mut R, expected: &str) -> anyhow::Result<()> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if line.trim_end() != expected {
+        bail!("expected {:?} from the jump worker but got {:?}", expected, line);
+    }
+    Ok(())
+}
+
+fn parse_done_line(line: &str) -> anyhow::Result<WorkerResults> {
+    let mut iter = line.split_whitespace();
+    match (iter.next(), iter.next(), iter.next(), iter.next()) {
+        (Some("done"), Some(elapsed), Some(rss), Some(shared)) => Ok(WorkerResults {
+            elapsed: Duration::from_nanos(elapsed.parse()?),
+            mapping_rss_kib: rss.parse().ok(),
+            mapping_shared_kib: shared.parse().ok(),
+        }),
+        _ => bail!("invalid jump worker output {:?}", line),
+    }
+}
+
+fn wait_for_success(mut child: Child) -> anyhow::Result<()> {
+    let status = child.wait()?;
+    if !status.success() {
+        bail!("a jump worker exited with {}", status);
+    }
+    Ok(())
+}
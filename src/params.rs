@@ -0,0 +1,147 @@
+//! The parameters of a grenad file and their name, e.g. `Snappy.2.4096.16`, the
+//! compression, the index levels, the block size and the index key interval.
+//!
+//! The generated files, the reports and the `--params` option all use this name, it
+//! parses back into the parameters, the case of the compression is ignored.
+
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+
+use grenad::CompressionType;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Parameters {
+    pub compression: CompressionType,
+    pub index_levels: u8,
+    pub block_size: usize,
+    pub index_key_interval: NonZeroUsize,
+}
+
+impl Parameters {
+    /// The name of the file generated with these parameters.
+    pub fn file_name(&self) -> String {
+        format!("{}.grd", self)
+    }
+
+    /// The parameters of a file named by `file_name`, `None` for the other names.
+    pub fn from_file_name(name: &str) -> Option<Parameters> {
+        name.strip_suffix(".grd")?.parse().ok()
+    }
+}
+
+impl fmt::Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?}.{}.{}.{}",
+            self.compression, self.index_levels, self.block_size, self.index_key_interval
+        )
+    }
+}
+
+impl FromStr for Parameters {
+    type Err = String;
+
+    /// Parses a name like `Snappy.2.4096.16`, with or without the `.grd` extension.
+    fn from_str(s: &str) -> Result<Parameters, String> {
+        let name = s.strip_suffix(".grd").unwrap_or(s);
+        let parts: Vec<_> = name.split('.').collect();
+        let (compression, index_levels, block_size, index_key_interval) = match parts.as_slice() {
+            [compression, index_levels, block_size, index_key_interval] => {
+                (compression, index_levels, block_size, index_key_interval)
+            }
+            _ => return Err(format!("`{}` is not named like `Snappy.2.4096.16`", s)),
+        };
+        let invalid = |what: &str, value: &str| format!("invalid {} {:?} in `{}`", what, value, s);
+        Ok(Parameters {
+            compression: compression
+                .to_lowercase()
+                .parse()
+                .map_err(|_| invalid("compression", compression))?,
+            index_levels: index_levels
+                .parse()
+                .map_err(|_| invalid("index levels", index_levels))?,
+            block_size: block_size.parse().map_err(|_| invalid("block size", block_size))?,
+            index_key_interval: index_key_interval
+                .parse()
+                .map_err(|_| invalid("index key interval", index_key_interval))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPRESSIONS: [CompressionType; 6] = [
+        CompressionType::None,
+        CompressionType::Zlib,
+        CompressionType::Snappy,
+        CompressionType::Zstd,
+        CompressionType::Lz4,
+        CompressionType::Lz4hc,
+    ];
+
+    fn parameters(compression: CompressionType) -> Parameters {
+        Parameters {
+            compression,
+            index_levels: 2,
+            block_size: 4096,
+            index_key_interval: NonZeroUsize::new(16).unwrap(),
+        }
+    }
+
+    fn assert_same(left: Parameters, right: Parameters) {
+        assert_eq!(format!("{:?}", left), format!("{:?}", right));
+    }
+
+    #[test]
+    fn display_parses_back() {
+        for compression in COMPRESSIONS {
+            let params = parameters(compression);
+            assert_same(params.to_string().parse().unwrap(), params);
+        }
+    }
+
+    #[test]
+    fn file_name_parses_back() {
+        for compression in COMPRESSIONS {
+            let params = parameters(compression);
+            let name = params.file_name();
+            assert!(name.ends_with(".grd"), "{}", name);
+            assert_same(Parameters::from_file_name(&name).unwrap(), params);
+        }
+    }
+
+    #[test]
+    fn compression_case_is_ignored() {
+        for name in ["snappy.2.4096.16", "SNAPPY.2.4096.16", "sNaPpY.2.4096.16"] {
+            assert_same(name.parse().unwrap(), parameters(CompressionType::Snappy));
+        }
+    }
+
+    #[test]
+    fn grd_suffix_is_optional() {
+        let with: Parameters = "Snappy.2.4096.16.grd".parse().unwrap();
+        let without: Parameters = "Snappy.2.4096.16".parse().unwrap();
+        assert_same(with, without);
+        assert!(Parameters::from_file_name("Snappy.2.4096.16").is_none());
+        assert!(Parameters::from_file_name("Snappy.2.4096.16.grd.zst").is_none());
+    }
+
+    #[test]
+    fn malformed_names_are_rejected() {
+        let names = [
+            "Snappy.2.4096",
+            "Snappy.2.4096.16.8",
+            "Snappy.2.4096.0",
+            "Brotli.2.4096.16",
+            "Snappy.two.4096.16",
+            "Snappy.2.4KiB.16",
+        ];
+        for name in names {
+            assert!(name.parse::<Parameters>().is_err(), "{} was accepted", name);
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! A backend writing the dataset as a Parquet file sorted by key, of a key and a value
+//! binary columns, to answer the recurring "why not a columnar format" with data.
+//!
+//! The lookups prune the row groups with the minimum and maximum keys recorded in the
+//! statistics of the file, the row group that can contain the key is then entirely
+//! decoded and searched. The backend is compiled in with the `arrow` feature.
+
+use std::path::Path;
+use std::time::Duration;
+
+use grenad::CompressionType;
+
+use crate::datagen::ValueGenerator;
+use crate::progress::PhaseProgress;
+use crate::{PhaseTimings, WorkloadOptions};
+
+/// The measurements of the Parquet backend.
+pub struct ParquetResults {
+    pub write_time: Duration,
+    pub file_size: u64,
+    pub row_groups: usize,
+    pub timings: PhaseTimings,
+}
+
+/// Writes the dataset as a Parquet file and executes the workload on it.
+#[cfg(feature = "arrow")]
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate(
+    folder: &Path,
+    values: ValueGenerator,
+    words: &[Vec<u8>],
+    lookups: &[usize],
+    compression: CompressionType,
+    row_group_size: usize,
+    workload: &WorkloadOptions,
+    progress: &mut PhaseProgress,
+) -> anyhow::Result<ParquetResults> {
+    imp::evaluate(folder, values, words, lookups, compression, row_group_size, workload, progress)
+}
+
+#[cfg(not(feature = "arrow"))]
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate(
+    _folder: &Path,
+    _values: ValueGenerator,
+    _words: &[Vec<u8>],
+    _lookups: &[usize],
+    _compression: CompressionType,
+    _row_group_size: usize,
+    _workload: &WorkloadOptions,
+    _progress: &mut PhaseProgress,
+) -> anyhow::Result<ParquetResults> {
+    anyhow::bail!("the Parquet backend is not compiled in, enable the `arrow` feature")
+}
+
+#[cfg(feature = "arrow")]
+mod imp {
+    use std::fs::File;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    use anyhow::Context;
+    use arrow_array::array::{ArrayRef, BinaryArray};
+    use arrow_array::datatypes::{DataType, Field, Schema};
+    use arrow_array::record_batch::RecordBatch;
+    use grenad::CompressionType;
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::file::statistics::Statistics;
+
+    use super::ParquetResults;
+    use crate::datagen::ValueGenerator;
+    use crate::manifest::Manifest;
+    use crate::progress::{PhaseProgress, PROGRESS_STEP};
+    use crate::{check_value, platform, Phase, PhaseCpuTimes, PhaseTimings, WorkloadOptions};
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn evaluate(
+        folder: &Path,
+        values: ValueGenerator,
+        words: &[Vec<u8>],
+        lookups: &[usize],
+        compression: CompressionType,
+        row_group_size: usize,
+        workload: &WorkloadOptions,
+        progress: &mut PhaseProgress,
+    ) -> anyhow::Result<ParquetResults> {
+        let name = format!("{:?}-{}", compression, row_group_size).to_lowercase();
+        let path = folder.join(name).with_extension("parquet");
+
+        let before_write = Instant::now();
+        write(&path, values, words, compression, row_group_size)?;
+        let write_time = before_write.elapsed();
+
+        let file =
+            File::open(&path).with_context(|| format!("while opening {}", path.display()))?;
+        let file_size = file.metadata()?.len();
+        let reader = SerializedFileReader::new(file)?;
+        let max_bitmap_len = Manifest::of_values(&values).max_bitmap_len;
+        let timings = test_parquet(&reader, words, lookups, workload, max_bitmap_len, progress)?;
+
+        Ok(ParquetResults {
+            write_time,
+            file_size,
+            row_groups: reader.metadata().num_row_groups(),
+            timings,
+        })
+    }
+
+    fn write(
+        path: &Path,
+        values: ValueGenerator,
+        words: &[Vec<u8>],
+        compression: CompressionType,
+        row_group_size: usize,
+    ) -> anyhow::Result<()> {
+        let codec = match compression {
+            CompressionType::None => Compression::UNCOMPRESSED,
+            CompressionType::Snappy => Compression::SNAPPY,
+            CompressionType::Lz4 => Compression::LZ4,
+            CompressionType::Zstd => Compression::ZSTD,
+            CompressionType::Zlib => Compression::GZIP,
+            otherwise => anyhow::bail!(
+                "the {:?} compression is not supported by the Parquet backend",
+                otherwise
+            ),
+        };
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("key", DataType::Binary, false),
+            Field::new("value", DataType::Binary, false),
+        ]));
+        let properties = WriterProperties::builder()
+            .set_compression(codec)
+            .set_max_row_group_size(row_group_size)
+            .build();
+        let file =
+            File::create(path).with_context(|| format!("while creating {}", path.display()))?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))?;
+
+        // A batch is written per row group, the values are not all held in memory.
+        let mut buffer = Vec::new();
+        for (chunk, keys) in words.chunks(row_group_size).enumerate() {
+            let values: Vec<_> = (0..keys.len())
+                .map(|i| {
+                    values.generate((chunk * row_group_size + i) as u64, &mut buffer);
+                    buffer.clone()
+                })
+                .collect();
+            let keys: ArrayRef =
+                Arc::new(BinaryArray::from(keys.iter().map(Vec::as_slice).collect::<Vec<_>>()));
+            let values: ArrayRef =
+                Arc::new(BinaryArray::from(values.iter().map(Vec::as_slice).collect::<Vec<_>>()));
+            writer.write(&RecordBatch::try_new(schema.clone(), vec![keys, values])?)?;
+        }
+
+        writer.close()?;
+        Ok(())
+    }
+
+    /// The last key of every row group, from the statistics of the key column.
+    fn row_group_max_keys(reader: &SerializedFileReader<File>) -> anyhow::Result<Vec<Vec<u8>>> {
+        let metadata = reader.metadata();
+        (0..metadata.num_row_groups())
+            .map(|i| match metadata.row_group(i).column(0).statistics() {
+                Some(Statistics::ByteArray(statistics)) if statistics.has_min_max_set() => {
+                    Ok(statistics.max().data().to_vec())
+                }
+                _ => anyhow::bail!("the row group {} has no statistics of its keys", i),
+            })
+            .collect()
+    }
+
+    fn test_parquet(
+        reader: &SerializedFileReader<File>,
+        words: &[Vec<u8>],
+        lookups: &[usize],
+        workload: &WorkloadOptions,
+        max_bitmap_len: Option<u64>,
+        progress: &mut PhaseProgress,
+    ) -> anyhow::Result<PhaseTimings> {
+        let mut expected = Vec::new();
+        let row_count = reader.metadata().file_metadata().num_rows() as u64;
+
+        let mut cpu = PhaseCpuTimes::default();
+        let mut iter_elapsed = None;
+        if workload.phases.contains(&Phase::Iter) {
+            progress.start_phase("iterating", row_count);
+            let cpu_before = platform::thread_cpu_time();
+            let before_iter = Instant::now();
+            for (i, row) in reader.get_row_iter(None)?.enumerate() {
+                let (k, v) = (row.get_bytes(0)?.data(), row.get_bytes(1)?.data());
+                if workload.validate.should_validate(i as u64) {
+                    assert_eq!(k, &words[i][..]);
+                    check_value(workload, max_bitmap_len, i, v, &mut expected);
+                }
+                if (i as u64 + 1) % PROGRESS_STEP == 0 {
+                    progress.set_position(i as u64 + 1);
+                }
+            }
+            iter_elapsed = Some(before_iter.elapsed());
+            cpu.iter = platform::thread_cpu_time_since(cpu_before);
+        }
+
+        let mut keys_iter_elapsed = None;
+        if workload.phases.contains(&Phase::KeysIter) {
+            // Only the key column is decoded, the values are never read.
+            let projection = reader.metadata().file_metadata().schema_descr().columns()[0].clone();
+            let projection = parquet::schema::types::Type::group_type_builder("schema")
+                .with_fields(&mut vec![projection.self_type_ptr()])
+                .build()?;
+            progress.start_phase("iterating over keys", row_count);
+            let cpu_before = platform::thread_cpu_time();
+            let before_iter = Instant::now();
+            for (i, row) in reader.get_row_iter(Some(projection))?.enumerate() {
+                let k = row.get_bytes(0)?.data();
+                if workload.validate.should_validate(i as u64) {
+                    assert_eq!(k, &words[i][..]);
+                }
+                if (i as u64 + 1) % PROGRESS_STEP == 0 {
+                    progress.set_position(i as u64 + 1);
+                }
+            }
+            keys_iter_elapsed = Some(before_iter.elapsed());
+            cpu.keys_iter = platform::thread_cpu_time_since(cpu_before);
+        }
+
+        let mut jump_elapsed = None;
+        if workload.phases.contains(&Phase::Jump) {
+            let max_keys = row_group_max_keys(reader)?;
+            progress.start_phase("jumping", lookups.len() as u64);
+            let cpu_before = platform::thread_cpu_time();
+            let before_jump = Instant::now();
+            for (n, &index) in (0..).zip(lookups) {
+                if n % PROGRESS_STEP == 0 {
+                    progress.set_position(n);
+                }
+                let word = &words[index];
+                // The first row group whose last key is not lower than the word.
+                let group = max_keys.partition_point(|max| max.as_slice() < word.as_slice());
+                let rows: Vec<_> = reader.get_row_group(group)?.get_row_iter(None)?.collect();
+                let position = rows.partition_point(|row| {
+                    row.get_bytes(0).map_or(false, |k| k.data() < word.as_slice())
+                });
+                let row = &rows[position];
+                let (k, v) = (row.get_bytes(0)?.data(), row.get_bytes(1)?.data());
+                if workload.validate.should_validate(n) {
+                    assert_eq!(k, &word[..]);
+                    check_value(workload, max_bitmap_len, index, v, &mut expected);
+                }
+            }
+            jump_elapsed = Some(before_jump.elapsed());
+            cpu.jump = platform::thread_cpu_time_since(cpu_before);
+        }
+
+        progress.finish();
+
+        Ok(PhaseTimings {
+            iter_time: iter_elapsed,
+            keys_iter_time: keys_iter_elapsed,
+            jump_time: jump_elapsed,
+            exact_get_time: None,
+            slo: None,
+            warm_cold: None,
+            seeks: None,
+            cpu,
+        })
+    }
+}
@@ -0,0 +1,557 @@
+//! Everything that depends on the operating system lives here, every function
+//! degrades gracefully on the platforms where the feature isn't available.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use memmap2::Mmap;
+
+/// Whether the kernel can be advised to back mappings with huge pages.
+pub const HUGE_PAGES: bool = cfg!(target_os = "linux");
+
+/// Whether the page cache of a single file can be dropped.
+pub const DROP_CACHES: bool = cfg!(any(target_os = "linux", target_os = "macos", windows));
+
+/// Whether the memory of a process, its page cache included, can be limited.
+pub const MEMORY_CGROUPS: bool = cfg!(target_os = "linux");
+
+/// Whether the process can be confined to a single core.
+pub const CPU_AFFINITY: bool = cfg!(target_os = "linux");
+
+/// Whether the priority of the process can be lowered.
+pub const PRIORITY: bool = cfg!(unix);
+
+/// Checks that a read method is available on this platform, it is used
+/// as a clap validator to be able to report the error at parse time.
+pub fn supported_read_method(method: &str) -> Result<(), String> {
+    let supported = match method {
+        "memory-mapped-locked" => cfg!(unix),
+        method => !method.starts_with("memory-mapped") || cfg!(any(unix, windows)),
+    };
+    if !supported {
+        Err(format!("the {} read method is not supported on {}", method, std::env::consts::OS))
+    } else {
+        Ok(())
+    }
+}
+
+/// Advises the kernel to back the mapping with transparent huge pages, returns
+/// `true` if the advice was accepted and THP are not disabled system-wide, the
+/// kernel may still not back the mapping with huge pages, see `huge_pages_mapped`.
+#[cfg(target_os = "linux")]
+pub fn advise_huge_pages(map: &Mmap) -> bool {
+    let ptr = map.as_ptr() as *mut libc::c_void;
+    let ret = unsafe { libc::madvise(ptr, map.len(), libc::MADV_HUGEPAGE) };
+    let enabled = std::fs::read_to_string("/sys/kernel/mm/transparent_hugepage/enabled")
+        .map_or(false, |mode| !mode.contains("[never]"));
+    ret == 0 && enabled
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn advise_huge_pages(_map: &Mmap) -> bool {
+    false
+}
+
+/// Faults in every page of the mapping and locks them in memory, the reads of the
+/// mapping do not depend on the state of the page cache anymore. The pages are
+/// unlocked when the mapping is dropped.
+#[cfg(unix)]
+pub fn lock_mapping(map: &Mmap) -> io::Result<()> {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as usize;
+    for offset in (0..map.len()).step_by(page_size) {
+        unsafe { std::ptr::read_volatile(map.as_ptr().add(offset)) };
+    }
+
+    if unsafe { libc::mlock(map.as_ptr() as *const libc::c_void, map.len()) } == 0 {
+        return Ok(());
+    }
+    let error = io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::ENOMEM) | Some(libc::EPERM) => {
+            let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+            let limit = if unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit) } == 0 {
+                if limit.rlim_cur == libc::RLIM_INFINITY {
+                    "unlimited".to_string()
+                } else {
+                    format!("{} KiB", limit.rlim_cur / 1024)
+                }
+            } else {
+                "unknown".to_string()
+            };
+            // The error is not reported as an OS error, it must not be retried as a
+            // transient lack of memory, it fails the same way until the limit is raised.
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "{} while locking {} bytes, the RLIMIT_MEMLOCK is {} (see ulimit -l)",
+                    error,
+                    map.len(),
+                    limit
+                ),
+            ))
+        }
+        _ => Err(error),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn lock_mapping(_map: &Mmap) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Other, "locking a mapping in memory is not supported"))
+}
+
+/// Evicts the pages of the given file from the page cache.
+///
+/// On macOS there is no per-file equivalent and the whole cache is purged,
+/// which requires the `purge` command and root privileges.
+#[cfg(target_os = "linux")]
+pub fn drop_file_cache(path: &Path) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = std::fs::File::open(path)?;
+    let ret = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+    match ret {
+        0 => Ok(()),
+        errno => Err(io::Error::from_raw_os_error(errno)),
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn drop_file_cache(_path: &Path) -> io::Result<()> {
+    let status = std::process::Command::new("purge").status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::Other, format!("purge exited with {}", status)))
+    }
+}
+
+/// Opening a file without buffering makes the cache manager
+/// flush and purge the cached pages of this file.
+#[cfg(windows)]
+pub fn drop_file_cache(path: &Path) -> io::Result<()> {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const FILE_FLAG_NO_BUFFERING: u32 = 0x2000_0000;
+    std::fs::OpenOptions::new().read(true).custom_flags(FILE_FLAG_NO_BUFFERING).open(path)?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn drop_file_cache(_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Other, "dropping the page cache is not supported"))
+}
+
+/// The filesystem of a folder and the algorithm it compresses the files with.
+#[derive(Debug, Clone)]
+pub struct FilesystemCompression {
+    pub filesystem: String,
+    /// `None` when the filesystem stores the files uncompressed.
+    pub algorithm: Option<String>,
+}
+
+/// Detects the filesystem of the folder and whether it compresses the files, only
+/// btrfs, with its `compress` mount options, and ZFS, with its `compression`
+/// property, are known to compress, `None` if the mount can't be found.
+#[cfg(target_os = "linux")]
+pub fn filesystem_compression(folder: &Path) -> Option<FilesystemCompression> {
+    let folder = std::fs::canonicalize(folder).ok()?;
+    let mounts = std::fs::read_to_string("/proc/self/mounts").ok()?;
+
+    // The mount of the folder is the one with the longest mount point containing it.
+    let (device, _, filesystem, options) = mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some((fields.next()?, fields.next()?, fields.next()?, fields.next()?))
+        })
+        .filter(|(_, mount_point, _, _)| folder.starts_with(mount_point))
+        .max_by_key(|(_, mount_point, _, _)| mount_point.len())?;
+
+    let algorithm = match filesystem {
+        "btrfs" => options.split(',').find_map(|option| {
+            let algorithm = option
+                .strip_prefix("compress-force=")
+                .or_else(|| option.strip_prefix("compress="))?;
+            Some(algorithm.to_string())
+        }),
+        "zfs" => std::process::Command::new("zfs")
+            .args(["get", "-H", "-o", "value", "compression", device])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|algorithm| algorithm != "off"),
+        _ => None,
+    };
+
+    Some(FilesystemCompression { filesystem: filesystem.to_string(), algorithm })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn filesystem_compression(_folder: &Path) -> Option<FilesystemCompression> {
+    None
+}
+
+/// Returns the space allocated on the disk for the file, ZFS reports the space of
+/// the compressed blocks but btrfs reports the uncompressed size (see `compsize`).
+#[cfg(unix)]
+pub fn allocated_size(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::fs::metadata(path).ok().map(|metadata| metadata.blocks() * 512)
+}
+
+#[cfg(not(unix))]
+pub fn allocated_size(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Raises the soft limit of open file descriptors up to the hard limit.
+#[cfg(unix)]
+pub fn raise_fd_limit() -> io::Result<()> {
+    let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // macOS refuses limits above OPEN_MAX even when the hard limit is unlimited.
+    let max = if cfg!(target_os = "macos") {
+        limit.rlim_max.min(libc::OPEN_MAX as libc::rlim_t)
+    } else {
+        limit.rlim_max
+    };
+
+    if limit.rlim_cur < max {
+        limit.rlim_cur = max;
+        if unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limit) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn raise_fd_limit() -> io::Result<()> {
+    Ok(())
+}
+
+/// Confines the calling thread to the first core it is allowed to run on, the threads
+/// it spawns afterwards inherit the affinity, returns the core.
+#[cfg(target_os = "linux")]
+pub fn pin_to_one_core() -> io::Result<usize> {
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+    let mut allowed: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sched_getaffinity(0, size, &mut allowed) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let core = (0..libc::CPU_SETSIZE as usize)
+        .find(|&core| unsafe { libc::CPU_ISSET(core, &allowed) })
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "no core is allowed"))?;
+    let mut one: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    unsafe { libc::CPU_SET(core, &mut one) };
+    if unsafe { libc::sched_setaffinity(0, size, &one) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(core)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_to_one_core() -> io::Result<usize> {
+    Err(io::Error::new(io::ErrorKind::Other, "the affinity of the threads can't be set"))
+}
+
+/// Sets the niceness of the calling thread, the threads it spawns afterwards inherit it.
+#[cfg(unix)]
+pub fn lower_priority(nice: i32) -> io::Result<()> {
+    // Linux sets the niceness of the calling thread only, the other systems of the process.
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn lower_priority(_nice: i32) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Other, "the priority of the process can't be lowered"))
+}
+
+/// An advisory lock held on a file until it is dropped.
+pub struct FileLock {
+    _file: std::fs::File,
+}
+
+/// Takes an exclusive advisory lock on the file, created if needed, waits for the
+/// other processes to release it or fails with `WouldBlock` when it is already held.
+#[cfg(unix)]
+pub fn lock_file(path: &Path, wait: bool) -> io::Result<FileLock> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = std::fs::OpenOptions::new().create(true).write(true).open(path)?;
+    let operation = if wait { libc::LOCK_EX } else { libc::LOCK_EX | libc::LOCK_NB };
+    while unsafe { libc::flock(file.as_raw_fd(), operation) } != 0 {
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+    Ok(FileLock { _file: file })
+}
+
+/// The files are not locked, the concurrent runs must not share a folder.
+#[cfg(not(unix))]
+pub fn lock_file(path: &Path, _wait: bool) -> io::Result<FileLock> {
+    let file = std::fs::OpenOptions::new().create(true).write(true).open(path)?;
+    Ok(FileLock { _file: file })
+}
+
+/// A cgroup v2 limiting the memory of the processes moved into it, the pages of the files
+/// they read are charged to it too, it is removed when dropped.
+pub struct MemoryCgroup {
+    path: PathBuf,
+}
+
+impl MemoryCgroup {
+    /// Creates the cgroup in the parent one, the memory controller must be enabled in the
+    /// `cgroup.subtree_control` of the parent and the parent must be writable by the user.
+    #[cfg(target_os = "linux")]
+    pub fn create(parent: &Path, name: &str, limit: u64) -> io::Result<MemoryCgroup> {
+        let cgroup = MemoryCgroup { path: parent.join(name) };
+        std::fs::create_dir(&cgroup.path)?;
+        std::fs::write(cgroup.path.join("memory.max"), limit.to_string())?;
+        // The anonymous pages must not be swapped out to leave the budget to the cache,
+        // the swap controller is not always enabled.
+        let _ = std::fs::write(cgroup.path.join("memory.swap.max"), "0");
+        Ok(cgroup)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn create(_parent: &Path, _name: &str, _limit: u64) -> io::Result<MemoryCgroup> {
+        Err(io::Error::new(io::ErrorKind::Other, "the memory cgroups are not supported"))
+    }
+
+    /// Moves the process into the cgroup, only the memory it uses from now on is charged.
+    pub fn add_process(&self, pid: u32) -> io::Result<()> {
+        std::fs::write(self.path.join("cgroup.procs"), pid.to_string())
+    }
+}
+
+impl Drop for MemoryCgroup {
+    /// The cgroup can only be removed once its processes exited.
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir(&self.path);
+    }
+}
+
+/// Whether the error is caused by a condition that may disappear by itself,
+/// like a full disk that gets cleaned up or a temporary lack of file descriptors.
+pub fn is_transient_io_error(error: &io::Error) -> bool {
+    if matches!(error.kind(), io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock) {
+        return true;
+    }
+    match error.raw_os_error() {
+        #[cfg(unix)]
+        Some(code) => {
+            [libc::ENOSPC, libc::EMFILE, libc::ENFILE, libc::ENOMEM, libc::EAGAIN].contains(&code)
+        }
+        // ERROR_NOT_ENOUGH_MEMORY, ERROR_TOO_MANY_OPEN_FILES and ERROR_DISK_FULL.
+        #[cfg(windows)]
+        Some(code) => [8, 4, 112].contains(&code),
+        _ => false,
+    }
+}
+
+/// Returns the numeric fields of the smaps of the given mapping, in KiB.
+#[cfg(target_os = "linux")]
+fn mapping_smaps(map: &Mmap) -> Option<Vec<(String, u64)>> {
+    let smaps = std::fs::read_to_string("/proc/self/smaps").ok()?;
+    let start = format!("{:08x}-", map.as_ptr() as usize);
+
+    let mut lines = smaps.lines().skip_while(|line| !line.starts_with(&start));
+    lines.next()?;
+    let mut values = Vec::new();
+    for line in lines {
+        let mut fields = line.split_whitespace();
+        let (name, value) = match (fields.next(), fields.next()) {
+            (Some(name), Some(value)) if name.ends_with(':') => (name, value),
+            // We reached the header of the next mapping.
+            _ => break,
+        };
+        if let Ok(value) = value.parse() {
+            values.push((name.trim_end_matches(':').to_string(), value));
+        }
+    }
+    Some(values)
+}
+
+/// Returns the resident and shared memory, in KiB, of the given mapping.
+#[cfg(target_os = "linux")]
+pub fn mapping_memory(map: &Mmap) -> Option<(u64, u64)> {
+    let (mut rss, mut shared) = (None, 0);
+    for (name, value) in mapping_smaps(map)? {
+        match name.as_str() {
+            "Rss" => rss = Some(value),
+            "Shared_Clean" | "Shared_Dirty" => shared += value,
+            _ => (),
+        }
+    }
+    rss.map(|rss| (rss, shared))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn mapping_memory(_map: &Mmap) -> Option<(u64, u64)> {
+    None
+}
+
+/// Whether some of the touched pages of the mapping are backed by huge pages, the
+/// `AnonHugePages`, `FilePmdMapped` or `ShmemPmdMapped` of its smaps are not zero.
+#[cfg(target_os = "linux")]
+pub fn huge_pages_mapped(map: &Mmap) -> bool {
+    let huge = ["AnonHugePages", "FilePmdMapped", "ShmemPmdMapped"];
+    mapping_smaps(map).map_or(false, |fields| {
+        fields.iter().any(|(name, kib)| huge.contains(&name.as_str()) && *kib > 0)
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn huge_pages_mapped(_map: &Mmap) -> bool {
+    false
+}
+
+/// Returns the resident memory of the process, in KiB.
+#[cfg(target_os = "linux")]
+pub fn resident_memory_kib() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn resident_memory_kib() -> Option<u64> {
+    None
+}
+
+/// The time a CPU spent running a thread, in user space and in the kernel.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct CpuTime {
+    pub user: Duration,
+    pub system: Duration,
+}
+
+impl CpuTime {
+    pub fn total(&self) -> Duration {
+        self.user + self.system
+    }
+}
+
+/// Returns the CPU time of the calling thread, the evaluations running concurrently and
+/// the background load are not accounted, at the resolution of the kernel accounting.
+#[cfg(target_os = "linux")]
+pub fn thread_cpu_time() -> Option<CpuTime> {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_THREAD, &mut usage) } != 0 {
+        return None;
+    }
+    let duration = |time: libc::timeval| {
+        Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+    };
+    Some(CpuTime { user: duration(usage.ru_utime), system: duration(usage.ru_stime) })
+}
+
+/// Only Linux reports the resources used by a single thread.
+#[cfg(not(target_os = "linux"))]
+pub fn thread_cpu_time() -> Option<CpuTime> {
+    None
+}
+
+/// Returns the CPU time of the calling thread since the `before` measurement.
+pub fn thread_cpu_time_since(before: Option<CpuTime>) -> Option<CpuTime> {
+    let (before, now) = (before?, thread_cpu_time()?);
+    Some(CpuTime {
+        user: now.user.saturating_sub(before.user),
+        system: now.system.saturating_sub(before.system),
+    })
+}
+
+/// The state of the CPU at a given time, throttling shows up
+/// as a lower frequency and usually a higher temperature.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Telemetry {
+    /// The mean frequency of the CPUs, in MHz.
+    pub frequency_mhz: Option<f64>,
+    /// The temperature of the CPU package, in degrees Celsius.
+    pub temperature: Option<f64>,
+}
+
+impl Telemetry {
+    pub fn read() -> Telemetry {
+        Telemetry { frequency_mhz: cpu_frequency_mhz(), temperature: package_temperature() }
+    }
+}
+
+/// Reads the current frequency of every CPU from cpufreq,
+/// falls back on `/proc/cpuinfo` when it is not available.
+#[cfg(target_os = "linux")]
+fn cpu_frequency_mhz() -> Option<f64> {
+    let mut frequencies = Vec::new();
+    for entry in std::fs::read_dir("/sys/devices/system/cpu").ok()?.flatten() {
+        let path = entry.path().join("cpufreq/scaling_cur_freq");
+        if let Some(khz) = std::fs::read_to_string(path).ok().and_then(|s| s.trim().parse().ok()) {
+            frequencies.push(khz / 1000.0);
+        }
+    }
+
+    if frequencies.is_empty() {
+        let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+        frequencies = cpuinfo
+            .lines()
+            .filter(|line| line.starts_with("cpu MHz"))
+            .filter_map(|line| line.split(':').nth(1)?.trim().parse().ok())
+            .collect();
+    }
+
+    if frequencies.is_empty() {
+        None
+    } else {
+        Some(frequencies.iter().sum::<f64>() / frequencies.len() as f64)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpu_frequency_mhz() -> Option<f64> {
+    None
+}
+
+/// Reads the temperature of the package from the Intel thermal zone,
+/// or from the coretemp and k10temp (AMD) hardware monitors.
+#[cfg(target_os = "linux")]
+fn package_temperature() -> Option<f64> {
+    let read_millidegrees = |path: std::path::PathBuf| -> Option<f64> {
+        let millidegrees: f64 = std::fs::read_to_string(path).ok()?.trim().parse().ok()?;
+        Some(millidegrees / 1000.0)
+    };
+    let name_is = |path: std::path::PathBuf, names: &[&str]| {
+        std::fs::read_to_string(path).map_or(false, |name| names.contains(&name.trim()))
+    };
+
+    let zones = std::fs::read_dir("/sys/class/thermal").into_iter().flatten().flatten();
+    for zone in zones {
+        if name_is(zone.path().join("type"), &["x86_pkg_temp"]) {
+            return read_millidegrees(zone.path().join("temp"));
+        }
+    }
+
+    let monitors = std::fs::read_dir("/sys/class/hwmon").into_iter().flatten().flatten();
+    for monitor in monitors {
+        if name_is(monitor.path().join("name"), &["coretemp", "k10temp", "zenpower"]) {
+            return read_millidegrees(monitor.path().join("temp1_input"));
+        }
+    }
+
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn package_temperature() -> Option<f64> {
+    None
+}
@@ -0,0 +1,140 @@
+//! The redundancy of the key prefixes inside the blocks, to evaluate whether
+//! front-coding the keys of a block (only storing the suffix that differs from
+//! the previous key) would be worth implementing in grenad.
+//!
+//! The blocks are simulated, an entry is appended to a block until its size reaches
+//! the block size, as the grenad `Writer` does, the exact framing of the entries
+//! is approximated by a constant overhead.
+
+use std::cmp::Ordering;
+
+use grenad::CompressionType;
+
+use crate::{stats, Outcome, Parameters};
+
+/// The approximate size of the lengths stored in front of every entry of a block.
+const ENTRY_OVERHEAD: usize = 4;
+
+/// The prefix redundancy of the keys of a dataset cut into blocks of a given size.
+#[derive(Debug, Clone)]
+pub struct PrefixRedundancy {
+    pub block_size: usize,
+    pub blocks: u64,
+    pub key_bytes: u64,
+    /// The bytes every key shares with the previous one of its block,
+    /// the first key of a block can't be front-coded.
+    pub shared_bytes: u64,
+    /// The share of the key bytes of every block that is redundant, sorted.
+    block_redundancies: Vec<f64>,
+}
+
+impl PrefixRedundancy {
+    /// Simulates the blocks of the sorted entries, given their keys and value lengths.
+    pub fn compute<'a>(
+        entries: impl IntoIterator<Item = (&'a [u8], usize)>,
+        block_size: usize,
+    ) -> PrefixRedundancy {
+        let mut redundancy = PrefixRedundancy {
+            block_size,
+            blocks: 0,
+            key_bytes: 0,
+            shared_bytes: 0,
+            block_redundancies: Vec::new(),
+        };
+
+        let mut previous: Option<&[u8]> = None;
+        let (mut block_len, mut block_key_bytes, mut block_shared_bytes) = (0, 0, 0);
+        for (key, value_len) in entries {
+            if let Some(previous) = previous {
+                block_shared_bytes += common_prefix_len(previous, key);
+            }
+            block_key_bytes += key.len();
+            block_len += key.len() + value_len + ENTRY_OVERHEAD;
+            previous = Some(key);
+
+            if block_len >= block_size {
+                redundancy.push_block(block_key_bytes, block_shared_bytes);
+                previous = None;
+                block_len = 0;
+                block_key_bytes = 0;
+                block_shared_bytes = 0;
+            }
+        }
+        if block_len != 0 {
+            redundancy.push_block(block_key_bytes, block_shared_bytes);
+        }
+
+        redundancy
+            .block_redundancies
+            .sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        redundancy
+    }
+
+    fn push_block(&mut self, key_bytes: usize, shared_bytes: usize) {
+        self.blocks += 1;
+        self.key_bytes += key_bytes as u64;
+        self.shared_bytes += shared_bytes as u64;
+        if key_bytes != 0 {
+            self.block_redundancies.push(shared_bytes as f64 / key_bytes as f64 * 100.0);
+        }
+    }
+
+    /// Returns the redundancy of the blocks at the given percentile, as a percentage.
+    pub fn block_percentile(&self, percentile: f64) -> f64 {
+        stats::percentile(&self.block_redundancies, percentile)
+    }
+
+    pub fn average_block_redundancy(&self) -> f64 {
+        if self.block_redundancies.is_empty() {
+            return 0.0;
+        }
+        self.block_redundancies.iter().sum::<f64>() / self.block_redundancies.len() as f64
+    }
+}
+
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// Prints the prefix redundancy for every block size and how the size of the
+/// uncompressed files without index levels responds to the index key interval.
+pub fn print_prefix_report(results: &[(Parameters, Outcome)], redundancies: &[PrefixRedundancy]) {
+    println!("shared-prefix redundancy:");
+    for redundancy in redundancies {
+        println!(
+            "block size {}: {} blocks, {} of {} key bytes are shared prefixes ({:.02}%), \
+             per block: average {:.02}%, p10 {:.02}%, p50 {:.02}%, p90 {:.02}%",
+            redundancy.block_size,
+            redundancy.blocks,
+            redundancy.shared_bytes,
+            redundancy.key_bytes,
+            redundancy.shared_bytes as f64 / redundancy.key_bytes.max(1) as f64 * 100.0,
+            redundancy.average_block_redundancy(),
+            redundancy.block_percentile(10.0),
+            redundancy.block_percentile(50.0),
+            redundancy.block_percentile(90.0),
+        );
+
+        let mut sizes: Vec<_> = results
+            .iter()
+            .filter(|(params, _)| {
+                params.block_size == redundancy.block_size
+                    && params.index_levels == 0
+                    && matches!(params.compression, CompressionType::None)
+            })
+            .filter_map(|(params, outcome)| {
+                Some((params.index_key_interval, outcome.as_ref().ok()?.file_size))
+            })
+            .collect();
+        sizes.sort_unstable_by_key(|(interval, _)| *interval);
+        for (interval, file_size) in sizes {
+            println!(
+                "    index key interval {}: {} bytes, front-coding would save about {:.02}%",
+                interval,
+                file_size,
+                redundancy.shared_bytes as f64 / file_size.max(1) as f64 * 100.0,
+            );
+        }
+    }
+    println!();
+}
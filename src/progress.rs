@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// The number of entries processed between two progress updates,
+/// updating more often would interfere with the measured timings.
+pub const PROGRESS_STEP: u64 = 1024;
+
+/// The minimum duration between two progress log lines.
+const LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Reports the progress of the phases (iteration, jumps) of a single evaluation.
+///
+/// A single configuration is displayed with its own progress bar, while during
+/// a parallel sweep we periodically print log lines above the sweep progress bar.
+pub enum PhaseProgress {
+    Bar(ProgressBar),
+    Log { output: ProgressBar, name: String, phase: &'static str, len: u64, last: Instant },
+}
+
+impl PhaseProgress {
+    pub fn bar() -> PhaseProgress {
+        let style = ProgressStyle::default_bar().template("{msg} {wide_bar} {pos}/{len} {eta}");
+        PhaseProgress::Bar(ProgressBar::new(0).with_style(style))
+    }
+
+    pub fn log(output: ProgressBar, name: String) -> PhaseProgress {
+        PhaseProgress::Log { output, name, phase: "", len: 0, last: Instant::now() }
+    }
+
+    pub fn start_phase(&mut self, new_phase: &'static str, new_len: u64) {
+        match self {
+            PhaseProgress::Bar(pb) => {
+                pb.reset();
+                pb.set_length(new_len);
+                pb.set_message(new_phase);
+            }
+            PhaseProgress::Log { phase, len, last, .. } => {
+                *phase = new_phase;
+                *len = new_len;
+                *last = Instant::now();
+            }
+        }
+    }
+
+    pub fn set_position(&mut self, position: u64) {
+        match self {
+            PhaseProgress::Bar(pb) => pb.set_position(position),
+            PhaseProgress::Log { output, name, phase, len, last } => {
+                if last.elapsed() >= LOG_INTERVAL {
+                    output.println(format!("{}: {} {}/{}", name, phase, position, len));
+                    *last = Instant::now();
+                }
+            }
+        }
+    }
+
+    pub fn finish(&self) {
+        if let PhaseProgress::Bar(pb) = self {
+            pb.finish_and_clear();
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! The read methods of the evaluated files, how the bytes of a file reach grenad, e.g.
+//! through a `BufReader`, a memory mapping or the frames of a seekable zstd copy.
+//!
+//! A new method is a variant opened here, every subcommand evaluating a file accepts
+//! it. The readers are boxed, a virtual call per read or seek is negligible next to
+//! the decompression and the search of the blocks.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::Context;
+use memmap2::Mmap;
+
+use crate::platform;
+use crate::seekable::{self, SeekableReader};
+
+pub const POSSIBLE_READ_METHODS: &[&str] = &[
+    "direct",
+    "read-to-vec",
+    "bufreader",
+    "memory-mapped",
+    "memory-mapped-bufreader",
+    "memory-mapped-locked",
+    "zstd-seekable",
+];
+/// The read methods of the subcommands that can evaluate them all on the same file.
+pub const POSSIBLE_READ_METHODS_OR_ALL: &[&str] = &[
+    "all",
+    "direct",
+    "read-to-vec",
+    "bufreader",
+    "memory-mapped",
+    "memory-mapped-bufreader",
+    "memory-mapped-locked",
+    "zstd-seekable",
+];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReadMethod {
+    /// The file is read through its descriptor, every read is a system call.
+    Direct,
+    /// The whole file is read in memory before the evaluation.
+    ReadToVec,
+    BufReader,
+    MemoryMapped,
+    MemoryMappedBufReader,
+    /// The mapping is locked in memory, it is never paged out.
+    MemoryMappedLocked,
+    /// The file is compressed in independent zstd frames, the frames are decompressed on
+    /// demand, to compare with the compression of grenad.
+    ZstdSeekable,
+}
+
+impl ReadMethod {
+    pub const ALL: [ReadMethod; 7] = [
+        ReadMethod::Direct,
+        ReadMethod::ReadToVec,
+        ReadMethod::BufReader,
+        ReadMethod::MemoryMapped,
+        ReadMethod::MemoryMappedBufReader,
+        ReadMethod::MemoryMappedLocked,
+        ReadMethod::ZstdSeekable,
+    ];
+
+    /// The methods supported by the platform, for `--read-method all`.
+    pub fn supported() -> Vec<ReadMethod> {
+        let supported =
+            |method: &ReadMethod| platform::supported_read_method(method.name()).is_ok();
+        ReadMethod::ALL.iter().copied().filter(supported).collect()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ReadMethod::Direct => "direct",
+            ReadMethod::ReadToVec => "read-to-vec",
+            ReadMethod::BufReader => "bufreader",
+            ReadMethod::MemoryMapped => "memory-mapped",
+            ReadMethod::MemoryMappedBufReader => "memory-mapped-bufreader",
+            ReadMethod::MemoryMappedLocked => "memory-mapped-locked",
+            ReadMethod::ZstdSeekable => "zstd-seekable",
+        }
+    }
+
+    /// The file read by the method, the seekable copy of the file for `zstd-seekable`,
+    /// it is written if missing, its size is the one compared with the compression of grenad.
+    pub fn read_file(
+        self,
+        path: &Path,
+        seekable_frame_size: NonZeroUsize,
+    ) -> anyhow::Result<PathBuf> {
+        match self {
+            ReadMethod::ZstdSeekable => seekable::compressed_file(path, seekable_frame_size.get()),
+            _ => Ok(path.to_path_buf()),
+        }
+    }
+
+    /// Opens the file to be read with the method, the cache of the file is dropped first
+    /// when requested.
+    pub fn open(self, path: &Path, options: &OpenOptions) -> anyhow::Result<Opened> {
+        let path = self.read_file(path, options.seekable_frame_size)?;
+        if options.drop_caches {
+            platform::drop_file_cache(&path)
+                .with_context(|| format!("while dropping the cache of {}", path.display()))?;
+        }
+        let mut file =
+            File::open(&path).with_context(|| format!("while opening {}", path.display()))?;
+
+        let map = |file: &File| -> anyhow::Result<(SharedMap, Option<HugePages>)> {
+            let map = Arc::new(unsafe { Mmap::map(file)? });
+            let huge_pages = options.huge_pages.then(|| HugePages {
+                accepted: platform::advise_huge_pages(&map),
+                map: map.clone(),
+            });
+            Ok((SharedMap(map), huge_pages))
+        };
+        let (reader, huge_pages): (Box<dyn ReadSeek>, _) = match self {
+            ReadMethod::Direct => (Box::new(file), None),
+            ReadMethod::ReadToVec => {
+                let mut bytes = Vec::new();
+                file.read_to_end(&mut bytes)?;
+                (Box::new(Cursor::new(bytes)), None)
+            }
+            ReadMethod::BufReader => (Box::new(BufReader::new(file)), None),
+            ReadMethod::MemoryMapped => {
+                let (map, huge_pages) = map(&file)?;
+                (Box::new(Cursor::new(map)), huge_pages)
+            }
+            ReadMethod::MemoryMappedBufReader => {
+                let (map, huge_pages) = map(&file)?;
+                (Box::new(BufReader::new(Cursor::new(map))), huge_pages)
+            }
+            ReadMethod::MemoryMappedLocked => {
+                let (map, huge_pages) = map(&file)?;
+                platform::lock_mapping(&map.0).with_context(|| {
+                    format!("while locking the mapping of {} in memory", path.display())
+                })?;
+                (Box::new(Cursor::new(map)), huge_pages)
+            }
+            ReadMethod::ZstdSeekable => {
+                let reader = SeekableReader::new(BufReader::new(file)).with_context(|| {
+                    format!("while reading the seek table of {}", path.display())
+                })?;
+                (Box::new(reader), None)
+            }
+        };
+        Ok(Opened { reader, huge_pages })
+    }
+}
+
+impl FromStr for ReadMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ReadMethod, String> {
+        ReadMethod::ALL.iter().copied().find(|method| method.name() == s).ok_or_else(|| {
+            format!(
+                "invalid read method {:?}, expected one of {}",
+                s,
+                POSSIBLE_READ_METHODS.join(", ")
+            )
+        })
+    }
+}
+
+impl fmt::Display for ReadMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A reader that can be boxed, whatever the method.
+pub trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+#[derive(Debug, Copy, Clone)]
+pub struct OpenOptions {
+    /// Advise the kernel to back the mappings with transparent huge pages.
+    pub huge_pages: bool,
+    pub drop_caches: bool,
+    pub seekable_frame_size: NonZeroUsize,
+}
+
+impl Default for OpenOptions {
+    /// The options of the command line by default, the frames are of 64KiB.
+    fn default() -> OpenOptions {
+        let seekable_frame_size = NonZeroUsize::new(64 * 1024).unwrap();
+        OpenOptions { huge_pages: false, drop_caches: false, seekable_frame_size }
+    }
+}
+
+/// An opened file, ready to be read by grenad.
+pub struct Opened {
+    pub reader: Box<dyn ReadSeek>,
+    /// The mapping advised to be backed by huge pages, `None` if not requested or not mapped.
+    pub huge_pages: Option<HugePages>,
+}
+
+/// A mapping advised to be backed by huge pages, it outlives the reader for the pages
+/// to be inspected once the workload touched them.
+pub struct HugePages {
+    map: Arc<Mmap>,
+    /// Whether the kernel accepted the advice.
+    accepted: bool,
+}
+
+impl HugePages {
+    /// Whether the kernel backs some of the touched pages with huge pages, an accepted
+    /// advice is often not applied to the mappings of a file, it must be called once the
+    /// mapping is read.
+    pub fn honored(&self) -> bool {
+        self.accepted && platform::huge_pages_mapped(&self.map)
+    }
+}
+
+/// A mapping shared by the reader and its `HugePages`.
+struct SharedMap(Arc<Mmap>);
+
+impl AsRef<[u8]> for SharedMap {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
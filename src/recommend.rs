@@ -0,0 +1,146 @@
+//! The recommendation of parameters from the results of the previous sweeps.
+//!
+//! Every sweep can record its best configuration along with the shape of its
+//! dataset in a model file, one tab-separated observation per line. The model
+//! is then queried without running any benchmark, the observation of the closest
+//! dataset, in number of entries and mean value size, is recommended.
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::path::Path;
+
+use anyhow::Context;
+use grenad::CompressionType;
+
+use crate::{Outcome, Parameters};
+
+/// The number of keys above which the top level of the index should be split.
+const MAX_TOP_INDEX_KEYS: u64 = 64 * 1024;
+
+/// The number of index levels recommended for the number of entries, every level
+/// divides the keys of the level above by the index key interval until the top level
+/// has at most `MAX_TOP_INDEX_KEYS`, e.g. 2 levels for 10M entries and an interval of 16.
+pub fn recommended_index_levels(entry_count: u64, index_key_interval: NonZeroUsize) -> u8 {
+    let interval = index_key_interval.get() as u64;
+    let (mut keys, mut levels) = (entry_count, 0);
+    while keys > MAX_TOP_INDEX_KEYS && interval > 1 && levels < u8::MAX {
+        keys /= interval;
+        levels += 1;
+    }
+    levels
+}
+
+/// The best configuration of a sweep over a dataset of the given shape.
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub entries: u64,
+    pub mean_value_size: u64,
+    pub params: Parameters,
+}
+
+impl Observation {
+    /// Returns the first successful configuration of the already sorted results.
+    pub fn from_sweep(
+        results: &[(Parameters, Outcome)],
+        entries: u64,
+        mean_value_size: u64,
+    ) -> Option<Observation> {
+        let (params, _) = results.iter().find(|(_, outcome)| outcome.is_ok())?;
+        Some(Observation { entries, mean_value_size, params: *params })
+    }
+
+    /// The distance between the shapes of two datasets, in orders of magnitude.
+    fn distance(&self, entries: u64, mean_value_size: u64) -> f64 {
+        let log = |x: u64| (x.max(1) as f64).log10();
+        let entries = log(self.entries) - log(entries);
+        let value_size = log(self.mean_value_size) - log(mean_value_size);
+        (entries * entries + value_size * value_size).sqrt()
+    }
+
+    fn to_line(&self) -> String {
+        let params = &self.params;
+        let compression = format!("{:?}", params.compression).to_lowercase();
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            self.entries,
+            self.mean_value_size,
+            compression,
+            params.index_levels,
+            params.block_size,
+            params.index_key_interval,
+        )
+    }
+
+    fn from_line(line: &str) -> anyhow::Result<Observation> {
+        let fields: Vec<_> = line.split('\t').collect();
+        if fields.len() != 6 {
+            anyhow::bail!("expected 6 tab-separated fields, found {}", fields.len());
+        }
+        let compression: CompressionType = fields[2]
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid compression {:?}", fields[2]))?;
+        Ok(Observation {
+            entries: fields[0].parse()?,
+            mean_value_size: fields[1].parse()?,
+            params: Parameters {
+                compression,
+                index_levels: fields[3].parse()?,
+                block_size: fields[4].parse()?,
+                index_key_interval: fields[5].parse()?,
+            },
+        })
+    }
+}
+
+impl fmt::Display for Observation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let params = &self.params;
+        write!(
+            f,
+            "for {} entries of mean size {} bytes use {} levels, interval {}, \
+             blocks of {} bytes and the {:?} compression",
+            self.entries,
+            self.mean_value_size,
+            params.index_levels,
+            params.index_key_interval,
+            params.block_size,
+            params.compression,
+        )
+    }
+}
+
+/// Appends the observation to the model file, it is created if it doesn't exist.
+pub fn record(path: &Path, observation: &Observation) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("while opening {}", path.display()))?;
+    file.write_all(observation.to_line().as_bytes())?;
+    Ok(())
+}
+
+pub fn read_model(path: &Path) -> anyhow::Result<Vec<Observation>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("while reading {}", path.display()))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            Observation::from_line(line)
+                .with_context(|| format!("at line {} of {}", i + 1, path.display()))
+        })
+        .collect()
+}
+
+/// Returns the observation of the dataset that is the closest to the given shape.
+pub fn suggest(model: &[Observation], entries: u64, mean_value_size: u64) -> Option<&Observation> {
+    model.iter().min_by(|a, b| {
+        let a = a.distance(entries, mean_value_size);
+        let b = b.distance(entries, mean_value_size);
+        a.partial_cmp(&b).unwrap()
+    })
+}
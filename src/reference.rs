@@ -0,0 +1,93 @@
+//! The reference datasets, `--dataset milli-small` generates the same dataset on every
+//! machine, the results posted in the issues are comparable across contributors without
+//! sharing gigabytes of files.
+//!
+//! The datasets are generated, they are not copies of the milli or Wikipedia databases,
+//! their keys and values only mimic the shape of them: the number of entries, the keys
+//! of single words or of word pairs and the sizes of the bitmaps. Their seeds are fixed,
+//! a profile must never change once published, a new shape is a new profile.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::datagen::{Charset, KeyProfile, ValueGenerator, WordShape};
+
+pub const POSSIBLE_DATASETS: &[&str] = &["milli-small", "milli-medium", "wiki-en-titles"];
+
+/// The seed of every reference dataset.
+const SEED: u64 = 42;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReferenceDataset {
+    /// The word docids of a small milli index, a hundred thousand words
+    /// of a few documents each, the bitmaps are tiny.
+    MilliSmall,
+    /// The word pair proximity docids of a medium milli index, two million
+    /// long keys sharing long prefixes and larger bitmaps.
+    MilliMedium,
+    /// The titles of the English Wikipedia, six million long and almost
+    /// unique keys, most of them are in a single document.
+    WikiEnTitles,
+}
+
+/// How a dataset is generated, the arguments of `extended-random-tests`.
+#[derive(Debug, Copy, Clone)]
+pub struct Generation {
+    pub seed: u64,
+    pub entry_count: u64,
+    pub key_profile: KeyProfile,
+    pub shape: WordShape,
+    pub values: ValueGenerator,
+}
+
+impl ReferenceDataset {
+    pub fn generation(self) -> Generation {
+        let (entry_count, key_profile, shape, values) = match self {
+            ReferenceDataset::MilliSmall => (
+                100_000,
+                KeyProfile::Words,
+                WordShape { min_len: 3, max_len: 15, charset: Charset::AsciiLower },
+                ValueGenerator::Sized { seed: SEED, mean_size: 64 },
+            ),
+            ReferenceDataset::MilliMedium => (
+                2_000_000,
+                KeyProfile::WordPairProximity,
+                WordShape { min_len: 3, max_len: 15, charset: Charset::AsciiLower },
+                ValueGenerator::Sized { seed: SEED, mean_size: 1024 },
+            ),
+            ReferenceDataset::WikiEnTitles => (
+                6_000_000,
+                KeyProfile::Words,
+                WordShape { min_len: 4, max_len: 60, charset: Charset::Alnum },
+                ValueGenerator::Sized { seed: SEED, mean_size: 24 },
+            ),
+        };
+        Generation { seed: SEED, entry_count, key_profile, shape, values }
+    }
+}
+
+impl FromStr for ReferenceDataset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ReferenceDataset, String> {
+        match s {
+            "milli-small" => Ok(ReferenceDataset::MilliSmall),
+            "milli-medium" => Ok(ReferenceDataset::MilliMedium),
+            "wiki-en-titles" => Ok(ReferenceDataset::WikiEnTitles),
+            otherwise => Err(format!(
+                "invalid dataset {:?}, expected milli-small, milli-medium or wiki-en-titles",
+                otherwise
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ReferenceDataset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReferenceDataset::MilliSmall => f.write_str("milli-small"),
+            ReferenceDataset::MilliMedium => f.write_str("milli-medium"),
+            ReferenceDataset::WikiEnTitles => f.write_str("wiki-en-titles"),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! The refinement of a sweep, `--refine 1` evaluates the configurations around the best
+//! ones of the grid in the same run, the intermediate block sizes and index key intervals
+//! the grid skipped, e.g. 3072 and 6144 around a best block size of 4096.
+//!
+//! The intermediate values are the middles between the value of a best configuration and
+//! its neighbors among the evaluated values, a value at the edge of the grid is refined
+//! halfway to half or twice it. Every round refines the best configurations of all the
+//! rounds before it, the compression and the index levels are never refined.
+
+use std::num::NonZeroUsize;
+
+use crate::{significance, Outcome, Parameters};
+
+/// The number of best configurations refined by a round.
+const REFINED_CONFIGURATIONS: usize = 3;
+
+/// The configurations around the best ones of the sorted results, the evaluated and
+/// skipped configurations excluded.
+pub fn refinement(results: &[(Parameters, Outcome)], skipped: &[Parameters]) -> Vec<Parameters> {
+    let evaluated: Vec<_> =
+        results.iter().map(|(params, _)| *params).chain(skipped.iter().copied()).collect();
+    let block_sizes = sorted_values(evaluated.iter().map(|params| params.block_size));
+    let intervals = sorted_values(evaluated.iter().map(|params| params.index_key_interval.get()));
+
+    let mut refined = Vec::new();
+    let best = results.iter().filter(|(_, outcome)| outcome.is_ok()).take(REFINED_CONFIGURATIONS);
+    for (params, _) in best {
+        let block_sizes = around(params.block_size, &block_sizes);
+        let intervals = around(params.index_key_interval.get(), &intervals);
+        for &block_size in &block_sizes {
+            for &interval in &intervals {
+                let candidate = Parameters {
+                    block_size,
+                    index_key_interval: NonZeroUsize::new(interval).unwrap(),
+                    ..*params
+                };
+                let known =
+                    |other: &Parameters| significance::is_same_configuration(other, &candidate);
+                if !evaluated.iter().any(known) && !refined.iter().any(known) {
+                    refined.push(candidate);
+                }
+            }
+        }
+    }
+    refined
+}
+
+fn sorted_values(values: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut values: Vec<_> = values.collect();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+/// The value and the middles between it and its neighbors among the sorted values.
+fn around(value: usize, values: &[usize]) -> Vec<usize> {
+    let position = values.binary_search(&value).unwrap_or_else(|position| position);
+    let lower = match position.checked_sub(1) {
+        Some(lower) => values[lower],
+        None => value / 2,
+    };
+    let upper = values.get(position + 1).copied().unwrap_or(value * 2);
+
+    let mut around = vec![value];
+    for middle in [(lower + value) / 2, (value + upper) / 2] {
+        if middle != 0 && middle != lower && middle != value && middle != upper {
+            around.push(middle);
+        }
+    }
+    around
+}
@@ -0,0 +1,600 @@
+//! The self-contained HTML report of a sweep, the charts are rendered as
+//! SVG and embedded in the page so that it can be attached to an issue as is.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use plotters::prelude::*;
+
+use crate::background::BackgroundLoad;
+use crate::cpu_limit::CpuLimit;
+use crate::experiment::ExperimentId;
+use crate::heap::HeapResults;
+use crate::platform::CpuTime;
+use crate::read_method::ReadMethod;
+use crate::sentinel::{self, SentinelRun};
+use crate::significance::{self, Comparison};
+use crate::stats::DatasetStats;
+use crate::{cpu_share, Outcome, Parameters, SortOptions};
+
+const CHART_SIZE: (u32, u32) = (640, 360);
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: right; }
+th { cursor: pointer; background: #eee; }
+tr.failed { color: #b00; }
+tr.noisy { color: #a60; }
+tr.skipped { color: #888; }
+td.current { font-weight: bold; }
+pre { background: #f6f6f6; padding: 1em; }
+.charts { display: flex; flex-wrap: wrap; gap: 1em; }
+";
+
+const SORT_SCRIPT: &str = "
+document.querySelectorAll('th').forEach((th, column) => th.addEventListener('click', () => {
+  const tbody = th.closest('table').querySelector('tbody');
+  const ascending = th.dataset.order !== 'asc';
+  th.dataset.order = ascending ? 'asc' : 'desc';
+  const value = row => row.children[column].dataset.value ?? row.children[column].textContent;
+  const rows = Array.from(tbody.rows).sort((a, b) => {
+    const x = value(a), y = value(b);
+    const order = isNaN(x) || isNaN(y) ? x.localeCompare(y) : x - y;
+    return ascending ? order : -order;
+  });
+  rows.forEach(row => tbody.appendChild(row));
+}));
+";
+
+/// Describes how a sweep was executed.
+pub struct Environment<'a> {
+    pub subcommand: &'static str,
+    pub seed: u64,
+    pub experiment: ExperimentId,
+    pub read_method: ReadMethod,
+    pub sort: &'a SortOptions,
+    /// The outcome of the file the configurations were re-encoded from, as is.
+    pub input_file: Option<&'a Outcome>,
+    /// The parameters the input file was written with, its configuration is marked current.
+    pub current: Option<Parameters>,
+    /// The load competing with the evaluations, if any.
+    pub background_load: Option<BackgroundLoad>,
+    /// The limits of the CPU of the run, empty if it is not limited.
+    pub cpu_limit: &'a [CpuLimit],
+    /// The configurations unsupported by the grenad version, with the reason.
+    pub skipped: &'a [(Parameters, String)],
+    /// The timings of the in-memory baseline, if it was evaluated.
+    pub heap: Option<&'a HeapResults>,
+}
+
+/// Writes the report of the given sweep results at `path`.
+pub fn write_html_report(
+    path: &Path,
+    environment: &Environment,
+    results: &[(Parameters, Outcome)],
+    dataset: Option<&DatasetStats>,
+    comparisons: Option<&[Comparison]>,
+    sentinel_runs: Option<&[SentinelRun]>,
+) -> anyhow::Result<()> {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    writeln!(html, "<title>grenad parameters report ({})</title>", environment.subcommand)?;
+    writeln!(html, "<style>{}</style>\n</head>\n<body>", STYLE)?;
+    writeln!(html, "<h1>grenad parameters report ({})</h1>", environment.subcommand)?;
+
+    html.push_str("<h2>Environment</h2>\n<table>\n<tbody>\n");
+    for (name, value) in metadata(environment) {
+        writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", name, escape(&value))?;
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    if let Some(dataset) = dataset {
+        writeln!(html, "<h2>Dataset</h2>\n<pre>{}</pre>", escape(&dataset.to_string()))?;
+    }
+
+    html.push_str("<h2>Results</h2>\n");
+    write_results_table(&mut html, results, environment)?;
+
+    if let Some(heap) = environment.heap {
+        write_heap_table(&mut html, heap, results)?;
+    }
+
+    if let Some(comparisons) = comparisons {
+        write_comparisons_table(&mut html, comparisons)?;
+    }
+
+    if let Some(runs) = sentinel_runs {
+        write_sentinel_table(&mut html, runs)?;
+    }
+
+    html.push_str("<h2>Parameters effect</h2>\n<div class=\"charts\">\n");
+    html.push_str(&parameter_effect_chart("compression", results, |p| {
+        (p.compression as u64, format!("{:?}", p.compression))
+    })?);
+    html.push_str(&parameter_effect_chart("index levels", results, |p| {
+        (p.index_levels as u64, p.index_levels.to_string())
+    })?);
+    html.push_str(&parameter_effect_chart("block size", results, |p| {
+        (p.block_size as u64, p.block_size.to_string())
+    })?);
+    html.push_str(&parameter_effect_chart("index key interval", results, |p| {
+        (p.index_key_interval.get() as u64, p.index_key_interval.to_string())
+    })?);
+    html.push_str("</div>\n");
+
+    html.push_str("<h2>File size against time</h2>\n");
+    html.push_str(&pareto_chart(results)?);
+
+    writeln!(html, "<script>{}</script>\n</body>\n</html>", SORT_SCRIPT)?;
+
+    fs::write(path, html).with_context(|| format!("while writing {}", path.display()))
+}
+
+fn metadata(environment: &Environment) -> Vec<(&'static str, String)> {
+    let generated_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let sort_keys: Vec<_> = environment.sort.sort_by.iter().map(ToString::to_string).collect();
+    let cpu_limit = match environment.cpu_limit {
+        [] => "none".to_string(),
+        limits => limits.iter().map(ToString::to_string).collect::<Vec<_>>().join(","),
+    };
+    vec![
+        ("command line", std::env::args().collect::<Vec<_>>().join(" ")),
+        ("version", env!("CARGO_PKG_VERSION").to_string()),
+        ("generated at (unix time)", generated_at.as_secs().to_string()),
+        ("os", std::env::consts::OS.to_string()),
+        ("arch", std::env::consts::ARCH.to_string()),
+        ("threads", rayon::current_num_threads().to_string()),
+        ("seed", environment.seed.to_string()),
+        ("experiment", environment.experiment.to_string()),
+        ("read method", environment.read_method.to_string()),
+        (
+            "background load",
+            environment.background_load.map_or("none".to_string(), |load| load.to_string()),
+        ),
+        ("cpu limit", cpu_limit),
+        ("sort by", sort_keys.join(",")),
+        ("sort tolerance", format!("{}%", environment.sort.sort_tolerance)),
+    ]
+}
+
+/// Writes a row per configuration, preceded by the input file the configurations were
+/// generated from when it was evaluated too and followed by the skipped configurations.
+fn write_results_table(
+    html: &mut String,
+    results: &[(Parameters, Outcome)],
+    environment: &Environment,
+) -> anyhow::Result<()> {
+    html.push_str("<table>\n<thead>\n<tr>");
+    for column in &[
+        "name",
+        "compression",
+        "index levels",
+        "block size",
+        "index key interval",
+        "iter (ms)",
+        "keys iter (ms)",
+        "jump (ms)",
+        "iter (entries/s)",
+        "jump (lookups/s)",
+        "read bandwidth (MB/s)",
+        "iter cpu (%)",
+        "jump cpu (%)",
+        "write (ms)",
+        "file size (bytes)",
+        "energy (J)",
+        "variation (%)",
+        "slo margin",
+        "cpu frequency (MHz)",
+        "temperature (°C)",
+        "validation",
+        "status",
+    ] {
+        write!(html, "<th>{}</th>", column)?;
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+
+    if let Some(outcome) = environment.input_file {
+        write!(html, "<tr{}>", row_class(outcome))?;
+        html.push_str("<td>baseline (input file)</td>");
+        match &environment.current {
+            Some(params) => write_parameter_values(html, params)?,
+            None => {
+                for _ in 0..4 {
+                    html.push_str("<td data-value=\"Infinity\">unknown</td>");
+                }
+            }
+        }
+        write_outcome_cells(html, outcome)?;
+        html.push_str("</tr>\n");
+    }
+
+    for (params, outcome) in results {
+        write!(html, "<tr{}>", row_class(outcome))?;
+        match &environment.current {
+            Some(current) if significance::is_same_configuration(params, current) => {
+                let name = format!("{} (current)", params.file_name());
+                write!(html, "<td class=\"current\">{}</td>", escape(&name))?;
+                write_parameter_values(html, params)?;
+            }
+            _ => write_parameter_cells(html, params)?,
+        }
+        write_outcome_cells(html, outcome)?;
+        html.push_str("</tr>\n");
+    }
+
+    for (params, reason) in environment.skipped {
+        html.push_str("<tr class=\"skipped\">");
+        write_parameter_cells(html, params)?;
+        write_missing_cells(html, &format!("skipped: {}", reason))?;
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</tbody>\n</table>\n");
+    Ok(())
+}
+
+fn write_parameter_cells(html: &mut String, params: &Parameters) -> anyhow::Result<()> {
+    write!(html, "<td>{}</td>", escape(&params.file_name()))?;
+    write_parameter_values(html, params)
+}
+
+/// Writes the cells of the parameters without the name of their file.
+fn write_parameter_values(html: &mut String, params: &Parameters) -> anyhow::Result<()> {
+    write!(html, "<td>{:?}</td>", params.compression)?;
+    write!(html, "<td>{}</td>", params.index_levels)?;
+    write!(html, "<td>{}</td>", params.block_size)?;
+    write!(html, "<td>{}</td>", params.index_key_interval)?;
+    Ok(())
+}
+
+fn row_class(outcome: &Outcome) -> &'static str {
+    match outcome {
+        Err(_) => " class=\"failed\"",
+        Ok(results) if results.noisy => " class=\"noisy\"",
+        Ok(_) => "",
+    }
+}
+
+fn write_outcome_cells(html: &mut String, outcome: &Outcome) -> anyhow::Result<()> {
+    match outcome {
+        Ok(results) => {
+            write_duration_cell(html, results.iter_time, "skipped")?;
+            write_duration_cell(html, results.keys_iter_time, "skipped")?;
+            write_duration_cell(html, results.jump_time, "skipped")?;
+            write_throughput_cell(html, results.iter_throughput(), 0)?;
+            write_throughput_cell(html, results.jump_throughput(), 0)?;
+            write_throughput_cell(html, results.read_bandwidth(), 2)?;
+            write_cpu_cell(html, results.cpu.iter, results.iter_time)?;
+            write_cpu_cell(html, results.cpu.jump, results.jump_time)?;
+            write_duration_cell(html, results.write_time, "reused")?;
+            write!(html, "<td>{}</td>", results.file_size)?;
+            match results.energy {
+                Some(joules) => write!(html, "<td>{:.02}</td>", joules)?,
+                None => html.push_str("<td data-value=\"Infinity\">unmeasured</td>"),
+            }
+            match results.variation() {
+                Some(variation) if results.noisy => {
+                    write!(html, "<td data-value=\"{0}\">{0:.02} (noisy)</td>", variation)?
+                }
+                Some(variation) => write!(html, "<td>{:.02}</td>", variation)?,
+                None => html.push_str("<td data-value=\"Infinity\">single run</td>"),
+            }
+            match results.slo {
+                Some(slo) => {
+                    let status = if slo.passed { "pass" } else { "fail" };
+                    write!(html, "<td data-value=\"{0}\">{0:.02} ({1})</td>", slo.margin, status)?
+                }
+                None => html.push_str("<td data-value=\"Infinity\">no objective</td>"),
+            }
+            let (before, after) = (results.telemetry_before, results.telemetry_after);
+            write_telemetry_cell(html, before.frequency_mhz, after.frequency_mhz, 0)?;
+            write_telemetry_cell(html, before.temperature, after.temperature, 1)?;
+            write!(html, "<td>{}</td>", escape(&results.validation.to_string()))?;
+            html.push_str("<td>ok</td>");
+        }
+        Err(error) => write_missing_cells(html, &format!("failed: {}", error))?,
+    }
+    Ok(())
+}
+
+/// Writes the cells of a configuration without results, sorted last, and its status.
+fn write_missing_cells(html: &mut String, status: &str) -> anyhow::Result<()> {
+    for _ in 0..16 {
+        html.push_str("<td data-value=\"Infinity\"></td>");
+    }
+    write!(html, "<td>{}</td>", escape(status))?;
+    Ok(())
+}
+
+fn write_comparisons_table(html: &mut String, comparisons: &[Comparison]) -> anyhow::Result<()> {
+    let baseline = significance::baseline_parameters().file_name();
+    writeln!(html, "<h2>Significance against {}</h2>", escape(&baseline))?;
+    writeln!(
+        html,
+        "<p>Mann-Whitney U test of the repetitions on the first sort key, \
+         the differences are significant under p = {}.</p>",
+        significance::SIGNIFICANCE_LEVEL,
+    )?;
+    html.push_str("<table>\n<thead>\n<tr>");
+    for column in &["name", "median", "baseline median", "difference (%)", "p-value", "significant"]
+    {
+        write!(html, "<th>{}</th>", column)?;
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+    for comparison in comparisons {
+        write!(html, "<tr><td>{}</td>", escape(&comparison.params.file_name()))?;
+        write!(html, "<td>{:.03}</td>", comparison.median)?;
+        write!(html, "<td>{:.03}</td>", comparison.baseline_median)?;
+        write!(html, "<td>{:+.02}</td>", comparison.difference())?;
+        match comparison.p_value {
+            Some(p) => write!(html, "<td>{:.03}</td>", p)?,
+            None => html.push_str("<td data-value=\"Infinity\">not testable</td>"),
+        }
+        let significant = if comparison.is_significant() { "yes" } else { "no" };
+        writeln!(html, "<td>{}</td></tr>", significant)?;
+    }
+    html.push_str("</tbody>\n</table>\n");
+    Ok(())
+}
+
+fn write_heap_table(
+    html: &mut String,
+    heap: &HeapResults,
+    results: &[(Parameters, Outcome)],
+) -> anyhow::Result<()> {
+    writeln!(html, "<h2>In-memory baseline ({})</h2>", heap.kind)?;
+    html.push_str(
+        "<p>The same workloads executed on the entries held in memory, \
+         the timings of the configurations are expressed as multiples of these.</p>\n",
+    );
+    html.push_str(
+        "<table>\n<thead>\n<tr><th>iter (ms)</th><th>keys iter (ms)</th><th>jump (ms)</th></tr>\n",
+    );
+    html.push_str("</thead>\n<tbody>\n<tr>");
+    write_duration_cell(html, heap.iter_time, "skipped")?;
+    write_duration_cell(html, heap.keys_iter_time, "skipped")?;
+    write_duration_cell(html, heap.jump_time, "skipped")?;
+    html.push_str("</tr>\n</tbody>\n</table>\n");
+
+    html.push_str("<table>\n<thead>\n<tr><th>name</th><th>iter (x)</th><th>jump (x)</th></tr>\n");
+    html.push_str("</thead>\n<tbody>\n");
+    for (params, outcome) in results {
+        if let Ok(results) = outcome {
+            let (iter, jump) = heap.multiples(results);
+            write!(html, "<tr><td>{}</td>", escape(&params.file_name()))?;
+            for multiple in [iter, jump] {
+                match multiple {
+                    Some(multiple) => write!(html, "<td>{:.02}</td>", multiple)?,
+                    None => html.push_str("<td data-value=\"Infinity\">skipped</td>"),
+                }
+            }
+            html.push_str("</tr>\n");
+        }
+    }
+    html.push_str("</tbody>\n</table>\n");
+    Ok(())
+}
+
+fn write_sentinel_table(html: &mut String, runs: &[SentinelRun]) -> anyhow::Result<()> {
+    html.push_str("<h2>Sentinel runs</h2>\n");
+    let drift =
+        sentinel::drift(runs).map_or_else(|| "unknown".to_string(), |d| format!("{:+.02}%", d));
+    let spread =
+        sentinel::spread(runs).map_or_else(|| "unknown".to_string(), |s| format!("{:.02}%", s));
+    writeln!(
+        html,
+        "<p>The baseline configuration was evaluated {} times during the sweep, \
+         its timings drifted by {} and spread over {}.</p>",
+        runs.len(),
+        drift,
+        spread,
+    )?;
+    html.push_str(
+        "<table>\n<thead>\n<tr><th>at (s)</th><th>iter (ms)</th><th>jump (ms)</th></tr>\n",
+    );
+    html.push_str("</thead>\n<tbody>\n");
+    for run in runs {
+        write!(html, "<tr><td>{:.03}</td>", run.at.as_secs_f64())?;
+        write_duration_cell(html, run.iter_time, "skipped")?;
+        write_duration_cell(html, run.jump_time, "skipped")?;
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+    Ok(())
+}
+
+/// Writes the values before and after an evaluation, sorted by the value after.
+fn write_telemetry_cell(
+    html: &mut String,
+    before: Option<f64>,
+    after: Option<f64>,
+    precision: usize,
+) -> anyhow::Result<()> {
+    match (before, after) {
+        (Some(before), Some(after)) => write!(
+            html,
+            "<td data-value=\"{}\">{:.*} → {:.*}</td>",
+            after, precision, before, precision, after
+        )?,
+        _ => html.push_str("<td data-value=\"Infinity\">unknown</td>"),
+    }
+    Ok(())
+}
+
+fn write_duration_cell(
+    html: &mut String,
+    duration: Option<Duration>,
+    missing: &str,
+) -> anyhow::Result<()> {
+    match duration {
+        Some(duration) => write!(html, "<td>{:.03}</td>", millis(duration))?,
+        None => write!(html, "<td data-value=\"Infinity\">{}</td>", missing)?,
+    }
+    Ok(())
+}
+
+/// Writes the CPU time of a phase as a percentage of its wall time, the user and
+/// the system times are in the title of the cell, only Linux measures them.
+fn write_cpu_cell(
+    html: &mut String,
+    cpu: Option<CpuTime>,
+    elapsed: Option<Duration>,
+) -> anyhow::Result<()> {
+    match (cpu_share(cpu, elapsed), cpu) {
+        (Some(share), Some(cpu)) => write!(
+            html,
+            "<td title=\"{:.03} ms user, {:.03} ms system\">{:.0}</td>",
+            millis(cpu.user),
+            millis(cpu.system),
+            share,
+        )?,
+        _ if elapsed.is_none() => html.push_str("<td data-value=\"Infinity\">skipped</td>"),
+        _ => html.push_str("<td data-value=\"Infinity\">unmeasured (Linux only)</td>"),
+    }
+    Ok(())
+}
+
+/// Writes a throughput, the higher the better, the missing ones are sorted last
+/// when sorting from the highest.
+fn write_throughput_cell(
+    html: &mut String,
+    throughput: Option<f64>,
+    precision: usize,
+) -> anyhow::Result<()> {
+    match throughput {
+        Some(throughput) => write!(html, "<td>{:.*}</td>", precision, throughput)?,
+        None => html.push_str("<td data-value=\"-Infinity\">skipped</td>"),
+    }
+    Ok(())
+}
+
+/// Draws the mean total time of the successful configurations
+/// for every value taken by one of the parameters.
+fn parameter_effect_chart(
+    name: &str,
+    results: &[(Parameters, Outcome)],
+    key: impl Fn(&Parameters) -> (u64, String),
+) -> anyhow::Result<String> {
+    let mut groups = BTreeMap::new();
+    for (params, outcome) in results {
+        if let Some(time) = outcome.as_ref().ok().and_then(|r| total_time(r.iter_time, r.jump_time))
+        {
+            let (order, label) = key(params);
+            let (_, sum, count) = groups.entry(order).or_insert((label, 0.0, 0));
+            *sum += millis(time);
+            *count += 1;
+        }
+    }
+
+    let means: Vec<_> =
+        groups.into_values().map(|(label, sum, count)| (label, sum / count as f64)).collect();
+    let max = means.iter().map(|(_, mean)| *mean).fold(0.0, f64::max);
+
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, CHART_SIZE).into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption(format!("mean time by {}", name), ("sans-serif", 20))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d((0..means.len() as u32).into_segmented(), 0.0..max * 1.1 + 1.0)?;
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .x_desc(name)
+            .y_desc("time (ms)")
+            .x_label_formatter(&|value| match value {
+                SegmentValue::CenterOf(i) => {
+                    means.get(*i as usize).map_or_else(String::new, |(label, _)| label.clone())
+                }
+                _ => String::new(),
+            })
+            .draw()?;
+        chart.draw_series(
+            Histogram::vertical(&chart)
+                .style(BLUE.mix(0.6).filled())
+                .margin(10)
+                .data(means.iter().enumerate().map(|(i, (_, mean))| (i as u32, *mean))),
+        )?;
+        root.present()?;
+    }
+
+    Ok(svg)
+}
+
+/// Draws the file size against the total time of every successful configuration
+/// and highlights the Pareto front, the configurations that no other beats on both.
+fn pareto_chart(results: &[(Parameters, Outcome)]) -> anyhow::Result<String> {
+    let mut points: Vec<_> = results
+        .iter()
+        .filter_map(|(_, outcome)| {
+            let r = outcome.as_ref().ok()?;
+            let time = total_time(r.iter_time, r.jump_time)?;
+            Some((r.file_size as f64 / (1024.0 * 1024.0), millis(time)))
+        })
+        .collect();
+    points.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let mut front = Vec::new();
+    for &(size, time) in &points {
+        if front.last().map_or(true, |&(_, best)| time < best) {
+            front.push((size, time));
+        }
+    }
+
+    let max_size = points.iter().map(|(size, _)| *size).fold(0.0, f64::max);
+    let max_time = points.iter().map(|(_, time)| *time).fold(0.0, f64::max);
+
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, CHART_SIZE).into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption("file size against total time", ("sans-serif", 20))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(0.0..max_size * 1.1 + 0.1, 0.0..max_time * 1.1 + 1.0)?;
+        chart.configure_mesh().x_desc("file size (MiB)").y_desc("time (ms)").draw()?;
+        chart.draw_series(
+            points.iter().map(|&point| Circle::new(point, 3, BLACK.mix(0.4).filled())),
+        )?;
+        chart.draw_series(LineSeries::new(front.iter().copied(), &RED))?;
+        chart.draw_series(front.iter().map(|&point| Circle::new(point, 4, RED.filled())))?;
+        root.present()?;
+    }
+
+    Ok(svg)
+}
+
+fn total_time(iter_time: Option<Duration>, jump_time: Option<Duration>) -> Option<Duration> {
+    match (iter_time, jump_time) {
+        (None, None) => None,
+        (iter, jump) => Some(iter.unwrap_or_default() + jump.unwrap_or_default()),
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
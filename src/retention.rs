@@ -0,0 +1,115 @@
+//! The retention of the generated files once a sweep is evaluated, `--keep-files best:3`
+//! keeps the files of the three best configurations for the follow-up `one-test` runs
+//! and removes the others, a sweep of a large dataset leaves gigabytes behind.
+//!
+//! The manifests, the seekable copies and the locks of the removed files are removed too.
+
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Context;
+
+use crate::manifest::Manifest;
+use crate::{Outcome, Parameters, LOCK_EXTENSION};
+
+/// The generated files kept after the evaluation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum KeepFiles {
+    None,
+    /// The files of the best configurations, according to the sort keys.
+    Best(usize),
+    All,
+}
+
+impl FromStr for KeepFiles {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<KeepFiles, String> {
+        match s {
+            "none" => Ok(KeepFiles::None),
+            "all" => Ok(KeepFiles::All),
+            otherwise => match otherwise.strip_prefix("best:").map(str::parse) {
+                Some(Ok(count)) => Ok(KeepFiles::Best(count)),
+                _ => Err(format!(
+                    "invalid retention {:?}, expected none, best:<n> or all",
+                    otherwise
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for KeepFiles {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeepFiles::None => f.write_str("none"),
+            KeepFiles::Best(count) => write!(f, "best:{}", count),
+            KeepFiles::All => f.write_str("all"),
+        }
+    }
+}
+
+/// Removes the files of the configurations that are not kept from the folder, the
+/// results must already be sorted. Returns the number of files and bytes removed.
+pub fn remove_files(
+    folder: &Path,
+    results: &[(Parameters, Outcome)],
+    keep: KeepFiles,
+) -> anyhow::Result<(usize, u64)> {
+    let kept = match keep {
+        KeepFiles::All => return Ok((0, 0)),
+        KeepFiles::None => 0,
+        KeepFiles::Best(count) => count,
+    };
+
+    let mut successful = 0;
+    let mut names = Vec::new();
+    for (params, outcome) in results {
+        if outcome.is_ok() {
+            successful += 1;
+            if successful <= kept {
+                continue;
+            }
+        }
+        names.push(params.file_name());
+    }
+
+    let mut paths = Vec::new();
+    for name in &names {
+        let path = folder.join(name);
+        paths.push(Manifest::path_of(&path));
+        paths.push(path.with_extension(LOCK_EXTENSION));
+        paths.push(path);
+    }
+    // The seekable copies are named after the file and their frame size.
+    let entries =
+        fs::read_dir(folder).with_context(|| format!("while reading {}", folder.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let copy_of = |name: &String| {
+            file_name
+                .strip_prefix(name.as_str())
+                .map_or(false, |rest| rest.starts_with('.') && rest.ends_with(".zst"))
+        };
+        if names.iter().any(copy_of) {
+            paths.push(path);
+        }
+    }
+
+    let (mut files, mut bytes) = (0, 0);
+    for path in paths {
+        let len = match fs::metadata(&path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e).with_context(|| format!("while reading {}", path.display())),
+        };
+        fs::remove_file(&path).with_context(|| format!("while removing {}", path.display()))?;
+        files += 1;
+        bytes += len;
+    }
+    Ok((files, bytes))
+}
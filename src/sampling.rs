@@ -0,0 +1,150 @@
+//! The sampling of the grid of a sweep, `--grid-sampling latin-hypercube:<n>` or
+//! `fractional` cover a large grid statistically without evaluating every configuration.
+//!
+//! Every dimension of the grid is a list of levels, the ranks of its sorted values. The
+//! Latin hypercube draws `n` configurations, every dimension is split into `n` strata and
+//! every stratum is drawn once, in a random order by dimension, the configurations missing
+//! from the grid are replaced by their nearest one. The fractional design keeps the
+//! configurations whose sum of levels is a multiple of the smallest number of levels, a
+//! fraction of the grid where every value of a dimension is evaluated with every value of
+//! another one, as long as a third dimension has that many levels. The sampling seed is
+//! fixed, every run samples the same configurations of the same grid.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::Parameters;
+
+/// The seed of the Latin hypercubes.
+const SEED: u64 = 42;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GridSampling {
+    /// Every configuration of the grid.
+    Full,
+    /// The given number of configurations drawn from a Latin hypercube.
+    LatinHypercube(NonZeroUsize),
+    /// The configurations whose sum of levels is a multiple of the smallest number of levels.
+    Fractional,
+}
+
+impl GridSampling {
+    pub fn sample(self, parameters: Vec<Parameters>) -> Vec<Parameters> {
+        match self {
+            GridSampling::Full => parameters,
+            GridSampling::LatinHypercube(count) if count.get() >= parameters.len() => parameters,
+            GridSampling::LatinHypercube(count) => latin_hypercube(parameters, count.get()),
+            GridSampling::Fractional => fractional(parameters),
+        }
+    }
+}
+
+/// The levels of the configurations in every dimension of the grid, and the number of
+/// levels of the dimensions.
+pub fn grid_levels(parameters: &[Parameters]) -> (Vec<[usize; 4]>, [usize; 4]) {
+    let keys = |params: &Parameters| {
+        [
+            params.compression as usize,
+            params.index_levels as usize,
+            params.block_size,
+            params.index_key_interval.get(),
+        ]
+    };
+    let mut values: [Vec<usize>; 4] = Default::default();
+    for params in parameters {
+        for (values, key) in values.iter_mut().zip(keys(params)) {
+            values.push(key);
+        }
+    }
+    for values in &mut values {
+        values.sort_unstable();
+        values.dedup();
+    }
+
+    let levels = parameters
+        .iter()
+        .map(|params| {
+            let mut levels = [0; 4];
+            for ((level, values), key) in levels.iter_mut().zip(&values).zip(keys(params)) {
+                *level = values.binary_search(&key).unwrap();
+            }
+            levels
+        })
+        .collect();
+    (levels, values.map(|values| values.len()))
+}
+
+fn latin_hypercube(parameters: Vec<Parameters>, count: usize) -> Vec<Parameters> {
+    let (levels, counts) = grid_levels(&parameters);
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let strata = counts.map(|_| {
+        let mut strata: Vec<_> = (0..count).collect();
+        strata.shuffle(&mut rng);
+        strata
+    });
+
+    let mut sampled: Vec<usize> = Vec::with_capacity(count);
+    for i in 0..count {
+        let mut target = [0.0; 4];
+        for ((target, strata), level_count) in target.iter_mut().zip(&strata).zip(counts) {
+            // The middle of the stratum, scaled to the levels of the dimension.
+            *target = (strata[i] as f64 + 0.5) / count as f64 * level_count as f64 - 0.5;
+        }
+        let distance = |index: usize| -> f64 {
+            let levels = levels[index].iter().zip(&target);
+            levels.map(|(&level, target)| (level as f64 - target).powi(2)).sum()
+        };
+        let nearest = (0..parameters.len())
+            .filter(|index| !sampled.contains(index))
+            .min_by(|&a, &b| distance(a).partial_cmp(&distance(b)).unwrap_or(Ordering::Equal));
+        sampled.extend(nearest);
+    }
+    sampled.sort_unstable();
+    sampled.into_iter().map(|index| parameters[index]).collect()
+}
+
+fn fractional(parameters: Vec<Parameters>) -> Vec<Parameters> {
+    let (levels, counts) = grid_levels(&parameters);
+    let modulus = counts.into_iter().filter(|&count| count > 1).min().unwrap_or(1);
+    parameters
+        .into_iter()
+        .zip(levels)
+        .filter(|(_, levels)| levels.iter().sum::<usize>() % modulus == 0)
+        .map(|(params, _)| params)
+        .collect()
+}
+
+impl FromStr for GridSampling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<GridSampling, String> {
+        match s.split_once(':') {
+            None if s == "full" => Ok(GridSampling::Full),
+            None if s == "fractional" => Ok(GridSampling::Fractional),
+            Some(("latin-hypercube", count)) => match count.parse() {
+                Ok(count) => Ok(GridSampling::LatinHypercube(count)),
+                Err(_) => Err(format!("`{}` is not a positive number of configurations", count)),
+            },
+            _ => Err(format!(
+                "invalid grid sampling {:?}, expected full, latin-hypercube:<n> or fractional",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for GridSampling {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridSampling::Full => f.write_str("full"),
+            GridSampling::LatinHypercube(count) => write!(f, "latin-hypercube:{}", count),
+            GridSampling::Fractional => f.write_str("fractional"),
+        }
+    }
+}
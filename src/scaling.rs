@@ -0,0 +1,109 @@
+//! The scaling studies, a fixed configuration evaluated along an axis of the
+//! dataset, to extrapolate the tuning results to the production sizes.
+//!
+//! Every metric is fitted by a power law, an exponent of 1 means that it grows
+//! linearly with the axis, the classic `O(n log n)` is slightly above 1.
+
+use std::time::Duration;
+
+use crate::{stats, Outcome, Results};
+
+/// A configuration evaluated at a given position of the studied axis.
+pub struct ScalingPoint {
+    pub x: u64,
+    pub outcome: Outcome,
+}
+
+/// A series must grow, a factor lower than 2 would never reach its end.
+pub fn validate_growth_factor(factor: &str) -> Result<(), String> {
+    match factor.parse::<u64>() {
+        Ok(factor) if factor >= 2 => Ok(()),
+        Ok(_) => Err("the growth factor must be at least 2".to_string()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// Returns `min, min * factor, min * factor², ...` up to `max` included.
+pub fn geometric_series(min: u64, max: u64, factor: u64) -> Vec<u64> {
+    let mut series = Vec::new();
+    let mut x = min;
+    while x <= max {
+        series.push(x);
+        x = match x.checked_mul(factor) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    series
+}
+
+pub fn print_scaling_results(axis: &str, points: &[ScalingPoint]) {
+    println!(
+        "{:>12} {:>12} {:>12} {:>12} {:>12} {:>16}",
+        axis, "iter", "keys iter", "jump", "write", "file size"
+    );
+    for point in points {
+        match &point.outcome {
+            Ok(results) => println!(
+                "{:>12} {:>12} {:>12} {:>12} {:>12} {:>16}",
+                point.x,
+                format_time(results.iter_time),
+                format_time(results.keys_iter_time),
+                format_time(results.jump_time),
+                format_time(results.write_time),
+                results.file_size,
+            ),
+            Err(error) => println!("{:>12} failed: {}", point.x, error),
+        }
+    }
+
+    let metrics: [(&str, fn(&Results) -> Option<f64>); 5] = [
+        ("iter time", |r| r.iter_time.map(|t| t.as_secs_f64())),
+        ("keys iter time", |r| r.keys_iter_time.map(|t| t.as_secs_f64())),
+        ("jump time", |r| r.jump_time.map(|t| t.as_secs_f64())),
+        ("write time", |r| r.write_time.map(|t| t.as_secs_f64())),
+        ("file size", |r| Some(r.file_size as f64)),
+    ];
+    for (name, metric) in metrics {
+        let samples: Vec<_> = points
+            .iter()
+            .filter_map(|point| {
+                let value = metric(point.outcome.as_ref().ok()?)?;
+                Some((point.x as f64, value))
+            })
+            .collect();
+        if let Some(exponent) = stats::power_law_exponent(&samples) {
+            println!("{} grows as {}^{:.03}", name, axis, exponent);
+        }
+    }
+    println!();
+}
+
+fn format_time(time: Option<Duration>) -> String {
+    match time {
+        Some(time) => format!("{:.02?}", time),
+        None => "skipped".to_string(),
+    }
+}
+
+/// Prints, for every position of the axis, the series that iterates and jumps the fastest,
+/// the series must have been evaluated at the same positions.
+pub fn print_best_series(axis: &str, series: &[(String, Vec<ScalingPoint>)]) {
+    let positions = series.first().map_or(0, |(_, points)| points.len());
+    for i in 0..positions {
+        let best = series
+            .iter()
+            .filter_map(|(name, points)| {
+                let point = points.get(i)?;
+                let results = point.outcome.as_ref().ok()?;
+                let total =
+                    results.iter_time.unwrap_or_default() + results.jump_time.unwrap_or_default();
+                Some((point.x, name, total))
+            })
+            .min_by_key(|(_, _, total)| *total);
+        if let Some((x, name, total)) = best {
+            println!("{} {}: {} is the fastest ({:.02?})", axis, x, name, total);
+        }
+    }
+    println!();
+}
@@ -0,0 +1,132 @@
+//! The JSON schemas of the structured files written by the subcommands,
+//! to build external tooling against documented formats.
+
+/// The formats that have a schema, for the `possible_values` of the command line.
+pub const POSSIBLE_FORMATS: &[&str] = &["critcmp-export", "stream-results"];
+
+/// The schema of the `--critcmp-export` files, a subset of the format of `critcmp --export`.
+const CRITCMP_EXPORT: &str = r##"{
+  "$schema": "http://json-schema.org/draft-07/schema#",
+  "title": "test-grenad critcmp export",
+  "type": "object",
+  "required": ["name", "benchmarks"],
+  "properties": {
+    "name": { "type": "string", "description": "The baseline, the stem of the exported file." },
+    "benchmarks": {
+      "type": "object",
+      "description": "The benchmarks by id, `<phase>/<configuration>`.",
+      "additionalProperties": { "$ref": "#/definitions/benchmark" }
+    }
+  },
+  "definitions": {
+    "benchmark": {
+      "type": "object",
+      "required": ["baseline", "fullname", "criterion_benchmark_v1", "criterion_estimates_v1"],
+      "properties": {
+        "baseline": { "type": "string" },
+        "fullname": { "type": "string", "description": "`<baseline>/<phase>/<configuration>`." },
+        "criterion_benchmark_v1": {
+          "type": "object",
+          "required": ["group_id", "function_id", "full_id", "directory_name"],
+          "properties": {
+            "group_id": { "type": "string", "enum": ["iter", "keys-iter", "jump"] },
+            "function_id": { "type": "string", "description": "The configuration, e.g. `None.0.8192.16`." },
+            "value_str": { "type": "null" },
+            "throughput": { "type": "null" },
+            "full_id": { "type": "string" },
+            "directory_name": { "type": "string" }
+          }
+        },
+        "criterion_estimates_v1": {
+          "type": "object",
+          "required": ["mean", "median", "median_abs_dev", "std_dev"],
+          "properties": {
+            "mean": { "$ref": "#/definitions/estimate" },
+            "median": { "$ref": "#/definitions/estimate" },
+            "median_abs_dev": { "$ref": "#/definitions/estimate" },
+            "slope": { "type": "null" },
+            "std_dev": { "$ref": "#/definitions/estimate" }
+          }
+        }
+      }
+    },
+    "estimate": {
+      "type": "object",
+      "description": "An estimate computed from the repetitions, in nanoseconds.",
+      "required": ["confidence_interval", "point_estimate", "standard_error"],
+      "properties": {
+        "confidence_interval": {
+          "type": "object",
+          "required": ["confidence_level", "lower_bound", "upper_bound"],
+          "properties": {
+            "confidence_level": { "type": "number", "const": 0.95 },
+            "lower_bound": { "type": "number" },
+            "upper_bound": { "type": "number" }
+          }
+        },
+        "point_estimate": { "type": "number" },
+        "standard_error": { "type": "number" }
+      }
+    }
+  }
+}
+"##;
+
+/// The schema of every line printed with `--stream-results`.
+const STREAM_RESULTS: &str = r##"{
+  "$schema": "http://json-schema.org/draft-07/schema#",
+  "title": "test-grenad streamed result",
+  "type": "object",
+  "required": ["experiment_id", "name", "compression", "index_levels", "block_size", "index_key_interval", "status"],
+  "properties": {
+    "experiment_id": { "type": "string", "description": "The hash of the dataset, the grid and the workload, the same for the same experiment." },
+    "name": { "type": "string", "description": "The configuration, e.g. `None.0.8192.16.grd`." },
+    "compression": { "type": "string", "enum": ["none", "snappy", "zlib", "lz4", "lz4hc", "zstd"] },
+    "index_levels": { "type": "integer", "minimum": 0, "maximum": 255 },
+    "block_size": { "type": "integer", "minimum": 0 },
+    "index_key_interval": { "type": "integer", "minimum": 1 },
+    "status": { "type": "string", "enum": ["ok", "failed", "skipped"] },
+    "error": { "type": "string", "description": "Why the configuration failed." },
+    "reason": { "type": "string", "description": "Why the configuration was skipped." },
+    "write_time_ns": { "type": ["integer", "null"], "description": "`null` if the file was reused." },
+    "lmdb_ingestion_time_ns": { "type": ["integer", "null"], "description": "`null` without `--lmdb-ingestion`." },
+    "pipeline_cost": { "type": ["number", "null"], "description": "`null` without `--pipeline-cost`." },
+    "iter_time_ns": { "type": ["integer", "null"], "description": "`null` if the phase was skipped." },
+    "keys_iter_time_ns": { "type": ["integer", "null"] },
+    "jump_time_ns": { "type": ["integer", "null"] },
+    "exact_get_time_ns": { "type": ["integer", "null"], "description": "`null` unless the exact-get phase is executed." },
+    "iter_user_time_ns": { "type": ["integer", "null"], "description": "The CPU time of the phase in user space, `null` if the phase was skipped or the platform doesn't report it, only Linux does." },
+    "iter_system_time_ns": { "type": ["integer", "null"], "description": "The CPU time of the phase in the kernel." },
+    "keys_iter_user_time_ns": { "type": ["integer", "null"] },
+    "keys_iter_system_time_ns": { "type": ["integer", "null"] },
+    "jump_user_time_ns": { "type": ["integer", "null"] },
+    "jump_system_time_ns": { "type": ["integer", "null"] },
+    "exact_get_user_time_ns": { "type": ["integer", "null"] },
+    "exact_get_system_time_ns": { "type": ["integer", "null"] },
+    "iter_entries_per_sec": { "type": ["number", "null"] },
+    "jump_lookups_per_sec": { "type": ["number", "null"] },
+    "read_mb_per_sec": { "type": ["number", "null"], "description": "The file size over the iteration time." },
+    "file_size": { "type": "integer", "description": "In bytes." },
+    "energy_joules": { "type": ["number", "null"] },
+    "variation": { "type": ["number", "null"], "description": "The coefficient of variation, in percent." },
+    "noisy": { "type": "boolean" },
+    "repetitions": { "type": "integer", "minimum": 1 },
+    "slo_margin": { "type": ["number", "null"], "description": "`null` without `--slo`." },
+    "slo_passed": { "type": ["boolean", "null"] },
+    "cold_seek_ns": { "type": ["integer", "null"], "description": "`null` without `--warm-cold`." },
+    "warm_seek_ns": { "type": ["integer", "null"] },
+    "avg_seeks_per_lookup": { "type": ["number", "null"], "description": "`null` when the jumps were skipped or replayed from a trace." },
+    "max_seeks_per_lookup": { "type": ["integer", "null"] },
+    "validation": { "type": "string", "description": "`off`, `full` or `sampled:<pct>%`." }
+  }
+}
+"##;
+
+/// Returns the schema of one of the `POSSIBLE_FORMATS`.
+pub fn schema(format: &str) -> Option<&'static str> {
+    match format {
+        "critcmp-export" => Some(CRITCMP_EXPORT),
+        "stream-results" => Some(STREAM_RESULTS),
+        _ => None,
+    }
+}
@@ -0,0 +1,223 @@
+//! The zstd seekable format, the whole grenad file is compressed in independent frames
+//! followed by the index of their offsets, any range of the file can be decompressed
+//! without the previous frames, the way the snapshots are transferred and read.
+//!
+//! The compressed file is written next to the grenad file, once, and reused. The reader
+//! decompresses the frames on demand and keeps the last one, the blocks of grenad are
+//! read through it, the cost is compared with the compression of the blocks by grenad.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+const SKIPPABLE_FRAME_MAGIC: u32 = 0x184D_2A5E;
+const SEEKABLE_MAGIC: u32 = 0x8F92_EAB1;
+/// The number of frames, the descriptor and the magic number ending the seek table.
+const FOOTER_SIZE: u64 = 9;
+const CHECKSUM_FLAG: u8 = 1 << 7;
+/// The suffix of the copies being written, removed by the `clean` subcommand.
+pub const TEMPORARY_SUFFIX: &str = ".zst.tmp";
+
+/// Returns the path of the seekable copy of the file, it is written if missing.
+pub fn compressed_file(path: &Path, frame_size: usize) -> anyhow::Result<PathBuf> {
+    let mut compressed = path.as_os_str().to_owned();
+    compressed.push(format!(".{}.zst", frame_size));
+    let compressed = PathBuf::from(compressed);
+    if fs::metadata(&compressed).is_ok() {
+        return Ok(compressed);
+    }
+
+    // The copy is written under a temporary name, the evaluations
+    // running concurrently never read a partially written one.
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(format!(".{}{}", frame_size, TEMPORARY_SUFFIX));
+    let temporary = PathBuf::from(temporary);
+    write_seekable(path, &temporary, frame_size)
+        .with_context(|| format!("while compressing {}", path.display()))?;
+    fs::rename(&temporary, &compressed)
+        .with_context(|| format!("while renaming {}", temporary.display()))?;
+    Ok(compressed)
+}
+
+fn write_seekable(input: &Path, output: &Path, frame_size: usize) -> anyhow::Result<()> {
+    let mut input = BufReader::new(File::open(input)?);
+    let mut output = BufWriter::new(File::create(output)?);
+    let mut compressor = zstd::bulk::Compressor::new(zstd::DEFAULT_COMPRESSION_LEVEL)?;
+
+    let mut frames = Vec::new();
+    let mut chunk = vec![0; frame_size];
+    loop {
+        let len = read_chunk(&mut input, &mut chunk)?;
+        if len == 0 {
+            break;
+        }
+        let frame = compressor.compress(&chunk[..len])?;
+        output.write_all(&frame)?;
+        frames.push((frame.len() as u32, len as u32));
+    }
+
+    let table_size = frames.len() as u32 * 8 + FOOTER_SIZE as u32;
+    output.write_all(&SKIPPABLE_FRAME_MAGIC.to_le_bytes())?;
+    output.write_all(&table_size.to_le_bytes())?;
+    for (compressed, decompressed) in &frames {
+        output.write_all(&compressed.to_le_bytes())?;
+        output.write_all(&decompressed.to_le_bytes())?;
+    }
+    output.write_all(&(frames.len() as u32).to_le_bytes())?;
+    output.write_all(&[0])?;
+    output.write_all(&SEEKABLE_MAGIC.to_le_bytes())?;
+    output.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    Ok(())
+}
+
+/// Fills the buffer unless the end of the reader is reached, returns the bytes read.
+fn read_chunk(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buffer.len() {
+        match reader.read(&mut buffer[len..]) {
+            Ok(0) => break,
+            Ok(read) => len += read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Frame {
+    compressed_offset: u64,
+    compressed_size: usize,
+    decompressed_offset: u64,
+    decompressed_size: usize,
+}
+
+/// Reads a seekable file as the decompressed one, the frames are decompressed on demand.
+pub struct SeekableReader<R> {
+    inner: R,
+    frames: Vec<Frame>,
+    len: u64,
+    position: u64,
+    decompressor: zstd::bulk::Decompressor<'static>,
+    /// The index of the decompressed frame, if any.
+    current: Option<usize>,
+    compressed: Vec<u8>,
+    decompressed: Vec<u8>,
+}
+
+impl<R: Read + Seek> SeekableReader<R> {
+    pub fn new(mut inner: R) -> io::Result<SeekableReader<R>> {
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        let file_len = inner.seek(SeekFrom::End(0))?;
+        if file_len < FOOTER_SIZE {
+            return Err(invalid("the file is too short to be in the seekable format"));
+        }
+
+        let mut footer = [0; FOOTER_SIZE as usize];
+        inner.seek(SeekFrom::End(-(FOOTER_SIZE as i64)))?;
+        inner.read_exact(&mut footer)?;
+        let frame_count = u32::from_le_bytes([footer[0], footer[1], footer[2], footer[3]]) as u64;
+        let descriptor = footer[4];
+        if u32::from_le_bytes([footer[5], footer[6], footer[7], footer[8]]) != SEEKABLE_MAGIC {
+            return Err(invalid("the file does not end with a seek table"));
+        }
+
+        let entry_size = if descriptor & CHECKSUM_FLAG != 0 { 12 } else { 8 };
+        let table_len = frame_count * entry_size;
+        if file_len < table_len + FOOTER_SIZE + 8 {
+            return Err(invalid("the seek table is larger than the file"));
+        }
+        let mut table = vec![0; table_len as usize];
+        inner.seek(SeekFrom::Start(file_len - FOOTER_SIZE - table_len))?;
+        inner.read_exact(&mut table)?;
+
+        let mut frames = Vec::with_capacity(frame_count as usize);
+        let (mut compressed_offset, mut decompressed_offset) = (0, 0);
+        for entry in table.chunks_exact(entry_size as usize) {
+            let compressed_size = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            let decompressed_size = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+            frames.push(Frame {
+                compressed_offset,
+                compressed_size: compressed_size as usize,
+                decompressed_offset,
+                decompressed_size: decompressed_size as usize,
+            });
+            compressed_offset += compressed_size as u64;
+            decompressed_offset += decompressed_size as u64;
+        }
+
+        Ok(SeekableReader {
+            inner,
+            frames,
+            len: decompressed_offset,
+            position: 0,
+            decompressor: zstd::bulk::Decompressor::new()?,
+            current: None,
+            compressed: Vec::new(),
+            decompressed: Vec::new(),
+        })
+    }
+
+    /// Decompresses the frame at the given index, unless it is the current one.
+    fn load_frame(&mut self, index: usize) -> io::Result<()> {
+        if self.current == Some(index) {
+            return Ok(());
+        }
+        let frame = self.frames[index];
+        self.compressed.resize(frame.compressed_size, 0);
+        self.inner.seek(SeekFrom::Start(frame.compressed_offset))?;
+        self.inner.read_exact(&mut self.compressed)?;
+        self.decompressed.clear();
+        self.decompressed.reserve(frame.decompressed_size);
+        self.decompressor.decompress_to_buffer(&self.compressed[..], &mut self.decompressed)?;
+        self.current = Some(index);
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Read for SeekableReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        let position = self.position;
+        let index = self.frames.partition_point(|frame| {
+            frame.decompressed_offset + frame.decompressed_size as u64 <= position
+        });
+        self.load_frame(index)?;
+
+        let start = (position - self.frames[index].decompressed_offset) as usize;
+        let available = &self.decompressed[start..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl<R> Seek for SeekableReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(offset) => (offset, 0),
+            SeekFrom::End(offset) => (self.len, offset),
+            SeekFrom::Current(offset) => (self.position, offset),
+        };
+        let position = if offset >= 0 {
+            base.checked_add(offset as u64)
+        } else {
+            base.checked_sub(offset.unsigned_abs())
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! The number of seeks issued by the cursor during every lookup of the jump phase.
+//!
+//! On a high-latency storage every seek is a round trip, the seeks per lookup is the
+//! metric that matters and it directly reflects the number of index levels. The seeks
+//! are those of grenad on its reader, whatever the read method issues underneath.
+
+use std::cell::Cell;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::rc::Rc;
+
+/// The number of seeks of a `SeekCounter`, shared with the workload.
+#[derive(Debug, Clone, Default)]
+pub struct SeekLog(Rc<Cell<u64>>);
+
+impl SeekLog {
+    pub fn count(&self) -> u64 {
+        self.0.get()
+    }
+}
+
+/// Wraps a reader and counts its seeks into the log.
+pub struct SeekCounter<R> {
+    inner: R,
+    log: SeekLog,
+}
+
+impl<R> SeekCounter<R> {
+    pub fn new(inner: R, log: SeekLog) -> SeekCounter<R> {
+        SeekCounter { inner, log }
+    }
+}
+
+impl<R: Read> Read for SeekCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Seek> Seek for SeekCounter<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.log.0.set(self.log.0.get() + 1);
+        self.inner.seek(pos)
+    }
+}
+
+/// The seeks per lookup of the jump phase.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SeekCounts {
+    pub average: f64,
+    pub max: u64,
+}
+
+impl SeekCounts {
+    /// The counts of the lookups from their total and maximum, `None` without any lookup.
+    pub fn new(total: u64, max: u64, lookups: u64) -> Option<SeekCounts> {
+        (lookups != 0).then(|| SeekCounts { average: total as f64 / lookups as f64, max })
+    }
+}
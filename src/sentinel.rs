@@ -0,0 +1,101 @@
+//! The sentinel runs, a fixed configuration evaluated again and again during a
+//! sweep. Its timings must not change, a drift indicates that the machine got
+//! slower (thermal throttling, background load...) and that the configurations
+//! evaluated late in the sweep are disadvantaged.
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::Results;
+
+/// A single evaluation of the sentinel configuration.
+#[derive(Debug, Copy, Clone)]
+pub struct SentinelRun {
+    /// The time elapsed since the beginning of the sweep.
+    pub at: Duration,
+    pub iter_time: Option<Duration>,
+    pub jump_time: Option<Duration>,
+}
+
+impl SentinelRun {
+    fn total_time(&self) -> Option<Duration> {
+        match (self.iter_time, self.jump_time) {
+            (None, None) => None,
+            (iter, jump) => Some(iter.unwrap_or_default() + jump.unwrap_or_default()),
+        }
+    }
+}
+
+pub struct Sentinel<F> {
+    interval: NonZeroUsize,
+    evaluate: F,
+    start: Instant,
+    completed: AtomicUsize,
+    runs: Mutex<Vec<SentinelRun>>,
+}
+
+impl<F: Fn() -> anyhow::Result<Results> + Sync> Sentinel<F> {
+    /// Creates a sentinel that is evaluated every `interval` configurations.
+    pub fn new(interval: NonZeroUsize, evaluate: F) -> Sentinel<F> {
+        Sentinel {
+            interval,
+            evaluate,
+            start: Instant::now(),
+            completed: AtomicUsize::new(0),
+            runs: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Evaluates the sentinel and records its timings, a failure is only reported.
+    pub fn run(&self) {
+        let at = self.start.elapsed();
+        match (self.evaluate)() {
+            Ok(results) => self.runs.lock().unwrap().push(SentinelRun {
+                at,
+                iter_time: results.iter_time,
+                jump_time: results.jump_time,
+            }),
+            Err(error) => eprintln!("the sentinel evaluation failed: {:#}", error),
+        }
+    }
+
+    /// Must be called every time a configuration has been evaluated,
+    /// the sentinel is evaluated when the interval is reached.
+    pub fn tick(&self) {
+        let completed = self.completed.fetch_add(1, Ordering::Relaxed) + 1;
+        if completed % self.interval.get() == 0 {
+            self.run();
+        }
+    }
+
+    /// Returns the sentinel runs in chronological order.
+    pub fn into_runs(self) -> Vec<SentinelRun> {
+        let mut runs = self.runs.into_inner().unwrap();
+        runs.sort_unstable_by_key(|run| run.at);
+        runs
+    }
+}
+
+/// The relative difference between the last and the first sentinel runs,
+/// as a percentage, a positive drift means that the machine got slower.
+pub fn drift(runs: &[SentinelRun]) -> Option<f64> {
+    let first = runs.first()?.total_time()?.as_secs_f64();
+    let last = runs.last()?.total_time()?.as_secs_f64();
+    if runs.len() < 2 || first == 0.0 {
+        return None;
+    }
+    Some((last - first) / first * 100.0)
+}
+
+/// The relative difference between the slowest and the fastest sentinel runs.
+pub fn spread(runs: &[SentinelRun]) -> Option<f64> {
+    let times: Vec<_> = runs.iter().filter_map(|run| run.total_time()).collect();
+    let fastest = times.iter().min()?.as_secs_f64();
+    let slowest = times.iter().max()?.as_secs_f64();
+    if times.len() < 2 || fastest == 0.0 {
+        return None;
+    }
+    Some((slowest - fastest) / fastest * 100.0)
+}
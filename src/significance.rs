@@ -0,0 +1,150 @@
+//! Tests whether the best configurations of a sweep are significantly
+//! different from the default parameters, not only lucky on a noisy run.
+//!
+//! The repetitions of both configurations are compared with the Mann-Whitney U
+//! test, it doesn't assume the timings to be normally distributed.
+
+use std::cmp::Ordering;
+use std::num::NonZeroUsize;
+
+use grenad::CompressionType;
+
+use crate::sort::SortExpression;
+use crate::{stats, Outcome, Parameters};
+
+/// The p-value under which a difference is considered significant.
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// The number of best configurations compared to the baseline.
+const COMPARED_CONFIGURATIONS: usize = 5;
+
+/// The configuration the others are compared to, it mirrors
+/// the default parameters of the grenad `WriterBuilder`.
+pub fn baseline_parameters() -> Parameters {
+    Parameters {
+        compression: CompressionType::None,
+        index_levels: 0,
+        block_size: 8 * 1024,
+        index_key_interval: NonZeroUsize::new(16).unwrap(),
+    }
+}
+
+/// The comparison of a configuration to the baseline on the first sort key.
+pub struct Comparison {
+    pub params: Parameters,
+    pub median: f64,
+    pub baseline_median: f64,
+    /// `None` when there are not enough repetitions or they are all identical.
+    pub p_value: Option<f64>,
+}
+
+impl Comparison {
+    pub fn is_significant(&self) -> bool {
+        self.p_value.map_or(false, |p| p < SIGNIFICANCE_LEVEL)
+    }
+
+    /// The relative difference to the baseline, negative when faster.
+    pub fn difference(&self) -> f64 {
+        (self.median - self.baseline_median) / self.baseline_median * 100.0
+    }
+}
+
+/// Compares the best configurations of the already sorted results to the baseline,
+/// returns `None` if the baseline wasn't evaluated successfully during the sweep.
+pub fn compare_to_baseline(
+    results: &[(Parameters, Outcome)],
+    key: &SortExpression,
+) -> Option<Vec<Comparison>> {
+    let baseline = baseline_parameters();
+    let scores = |outcome: &Outcome| -> Vec<f64> {
+        outcome.as_ref().map_or_else(
+            |_| Vec::new(),
+            |results| results.repetitions().filter_map(|r| key.score(&r)).collect(),
+        )
+    };
+
+    let baseline_scores = results
+        .iter()
+        .find(|(params, _)| is_same_configuration(params, &baseline))
+        .map(|(_, outcome)| scores(outcome))
+        .filter(|scores| !scores.is_empty())?;
+    let baseline_median = stats::median(baseline_scores.clone())?;
+
+    let comparisons = results
+        .iter()
+        .filter(|(params, _)| !is_same_configuration(params, &baseline))
+        .filter_map(|(params, outcome)| {
+            let scores = scores(outcome);
+            Some(Comparison {
+                params: *params,
+                median: stats::median(scores.clone())?,
+                baseline_median,
+                p_value: mann_whitney_u(&scores, &baseline_scores),
+            })
+        })
+        .take(COMPARED_CONFIGURATIONS)
+        .collect();
+
+    Some(comparisons)
+}
+
+pub fn is_same_configuration(a: &Parameters, b: &Parameters) -> bool {
+    a.compression as u8 == b.compression as u8
+        && a.index_levels == b.index_levels
+        && a.block_size == b.block_size
+        && a.index_key_interval == b.index_key_interval
+}
+
+/// Returns the two-sided p-value of the Mann-Whitney U test, computed with the normal
+/// approximation, corrected for ties and continuity. It is rough under 8 samples per side.
+fn mann_whitney_u(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+
+    let mut samples: Vec<_> =
+        a.iter().map(|&x| (x, true)).chain(b.iter().map(|&x| (x, false))).collect();
+    samples.sort_unstable_by(|x, y| x.0.partial_cmp(&y.0).unwrap_or(Ordering::Equal));
+
+    // The tied samples share the average of their ranks.
+    let mut rank_sum = 0.0;
+    let mut ties = 0.0;
+    let mut start = 0;
+    while start < samples.len() {
+        let len = samples[start..].iter().take_while(|(x, _)| *x == samples[start].0).count();
+        let len = len.max(1);
+        let rank = start as f64 + (len as f64 + 1.0) / 2.0;
+        let from_a = samples[start..start + len].iter().filter(|(_, from_a)| *from_a).count();
+        rank_sum += rank * from_a as f64;
+        ties += (len * len * len - len) as f64;
+        start += len;
+    }
+
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let n = n1 + n2;
+    let u = rank_sum - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return None;
+    }
+
+    let z = ((u - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    Some((2.0 * (1.0 - normal_cdf(z))).clamp(0.0, 1.0))
+}
+
+fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / std::f64::consts::SQRT_2))
+}
+
+/// The Abramowitz and Stegun approximation of the error function (7.1.26).
+fn erf(x: f64) -> f64 {
+    let sign = x.signum();
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let polynomial = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    sign * (1.0 - polynomial * (-x * x).exp())
+}
@@ -0,0 +1,83 @@
+//! The latency objectives of the random jumps, e.g. `--slo "p99_seek < 500us"`,
+//! to tune the parameters for a tail latency target rather than the total time.
+//!
+//! When objectives are given every seek of the jump phase is timed individually,
+//! the jump time then includes the overhead of reading the clock twice per seek.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::args::parse_duration;
+use crate::stats;
+
+/// A latency objective, a percentile of the seek latencies below a target.
+#[derive(Debug, Clone)]
+pub struct Slo {
+    source: String,
+    percentile: f64,
+    target: Duration,
+    inclusive: bool,
+}
+
+/// How the seek latencies of a repetition compare to the objectives.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SloOutcome {
+    /// The largest ratio of a measured percentile to its target,
+    /// below one the objectives are met with room to spare.
+    pub margin: f64,
+    pub passed: bool,
+}
+
+/// Compares the latencies of the seeks to the objectives, `None` if there are none.
+pub fn evaluate(slos: &[Slo], latencies: &mut [Duration]) -> Option<SloOutcome> {
+    if slos.is_empty() || latencies.is_empty() {
+        return None;
+    }
+    latencies.sort_unstable();
+    let mut outcome = SloOutcome { margin: 0.0, passed: true };
+    for slo in slos {
+        let measured = stats::percentile(latencies, slo.percentile);
+        let margin = measured.as_secs_f64() / slo.target.as_secs_f64();
+        outcome.margin = outcome.margin.max(margin);
+        outcome.passed &=
+            if slo.inclusive { measured <= slo.target } else { measured < slo.target };
+    }
+    Some(outcome)
+}
+
+impl fmt::Display for Slo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for Slo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Slo, String> {
+        let (left, right, inclusive) = if let Some((left, right)) = s.split_once("<=") {
+            (left, right, true)
+        } else if let Some((left, right)) = s.split_once('<') {
+            (left, right, false)
+        } else {
+            return Err(format!("`{}` must be like `p99_seek < 500us`", s));
+        };
+
+        let percentile =
+            left.trim().strip_prefix('p').and_then(|left| left.strip_suffix("_seek")).ok_or_else(
+                || format!("`{}` must be a percentile of the seeks like `p99_seek`", left.trim()),
+            )?;
+        let percentile: f64 = match percentile.parse() {
+            Ok(percentile) if (0.0..=100.0).contains(&percentile) => percentile,
+            _ => return Err(format!("`{}` is not a percentile between 0 and 100", percentile)),
+        };
+
+        Ok(Slo {
+            source: s.trim().to_string(),
+            percentile,
+            target: parse_duration(right.trim())?,
+            inclusive,
+        })
+    }
+}
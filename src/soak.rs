@@ -0,0 +1,172 @@
+//! The soak test, the random jumps executed continuously on a single configuration
+//! for hours, the issues that a short benchmark misses show up as a drift of the
+//! throughput or the latencies, or as a growth of the resident memory.
+//!
+//! Every interval is compared with the first one, the warm-up of the page cache
+//! is part of the first interval, it should be short compared to the run.
+
+use std::io::{Read, Seek};
+use std::time::{Duration, Instant};
+
+use grenad::Reader;
+
+use crate::{manifest, platform, stats};
+
+/// The number of seeks between two readings of the clock of the intervals.
+const CLOCK_STEP: usize = 1024;
+
+pub struct SoakOptions {
+    pub duration: Duration,
+    pub log_interval: Duration,
+    /// The change of throughput, in percent, above which the run drifted.
+    pub max_drift: f64,
+    /// The growth of the resident memory, in percent, above which the run leaked.
+    pub max_rss_growth: f64,
+}
+
+/// The measurements of an interval of the run.
+#[derive(Debug, Copy, Clone)]
+struct Interval {
+    /// The time elapsed since the beginning of the run at the end of the interval.
+    at: Duration,
+    throughput: f64,
+    p50: Duration,
+    p99: Duration,
+    p999: Duration,
+    rss_kib: Option<u64>,
+}
+
+/// How the last interval of the run compares to the first one.
+pub struct SoakReport {
+    pub intervals: usize,
+    pub lookups: u64,
+    /// The change of throughput, in percent, `None` with a single interval.
+    pub drift: Option<f64>,
+    /// The growth of the resident memory, in percent, `None` if it is unknown.
+    pub rss_growth: Option<f64>,
+    pub drifted: bool,
+    pub leaked: bool,
+}
+
+/// Jumps to the words in the order of the lookups, again and again, until the duration
+/// elapsed, the keys are always checked, the values when a bound is known.
+pub fn soak<R: Read + Seek>(
+    reader: R,
+    words: &[Vec<u8>],
+    lookups: &[usize],
+    max_bitmap_len: Option<u64>,
+    options: &SoakOptions,
+) -> anyhow::Result<SoakReport> {
+    if lookups.is_empty() {
+        anyhow::bail!("the soak test needs at least one lookup, the dataset is empty");
+    }
+    let mut cursor = Reader::new(reader)?.into_cursor()?;
+
+    let mut intervals = Vec::new();
+    let mut latencies = Vec::new();
+    let mut total_lookups = 0;
+    let start = Instant::now();
+    let mut interval_start = start;
+    for &index in lookups.iter().cycle() {
+        let word = &words[index];
+        let before_seek = Instant::now();
+        let (k, v) = cursor.move_on_key_greater_than_or_equal_to(word)?.unwrap();
+        latencies.push(before_seek.elapsed());
+        assert_eq!(k, &word[..]);
+        if max_bitmap_len.is_some() {
+            manifest::check_bitmap(v, max_bitmap_len);
+        }
+
+        if latencies.len() % CLOCK_STEP != 0 {
+            continue;
+        }
+        let now = Instant::now();
+        let finished = now.duration_since(start) >= options.duration;
+        if finished || now.duration_since(interval_start) >= options.log_interval {
+            latencies.sort_unstable();
+            let interval = Interval {
+                at: now.duration_since(start),
+                throughput: stats::throughput(
+                    latencies.len() as u64,
+                    now.duration_since(interval_start),
+                ),
+                p50: stats::percentile(&latencies, 50.0),
+                p99: stats::percentile(&latencies, 99.0),
+                p999: stats::percentile(&latencies, 99.9),
+                rss_kib: platform::resident_memory_kib(),
+            };
+            print_interval(&interval, intervals.first());
+            total_lookups += latencies.len() as u64;
+            intervals.push(interval);
+            latencies.clear();
+            interval_start = now;
+        }
+        if finished {
+            break;
+        }
+    }
+
+    let (first, last) = (intervals[0], intervals[intervals.len() - 1]);
+    let drift = (intervals.len() > 1).then(|| change(first.throughput, last.throughput));
+    let rss_growth = match (first.rss_kib, last.rss_kib) {
+        (Some(first), Some(last)) => Some(change(first as f64, last as f64)),
+        _ => None,
+    };
+    Ok(SoakReport {
+        intervals: intervals.len(),
+        lookups: total_lookups,
+        drift,
+        rss_growth,
+        drifted: drift.map_or(false, |drift| drift.abs() > options.max_drift),
+        leaked: rss_growth.map_or(false, |growth| growth > options.max_rss_growth),
+    })
+}
+
+fn print_interval(interval: &Interval, first: Option<&Interval>) {
+    let throughput_change = first.map_or_else(String::new, |first| {
+        format!(" ({:+.02}%)", change(first.throughput, interval.throughput))
+    });
+    let rss = match (interval.rss_kib, first.and_then(|first| first.rss_kib)) {
+        (Some(rss), Some(first)) => {
+            format!("{} KiB ({:+.02}%)", rss, change(first as f64, rss as f64))
+        }
+        (Some(rss), None) => format!("{} KiB", rss),
+        (None, _) => "unknown".to_string(),
+    };
+    println!(
+        "{:>10.0?}: {:.0} lookups/s{}, p50 {:.02?}, p99 {:.02?}, p99.9 {:.02?}, rss {}",
+        interval.at,
+        interval.throughput,
+        throughput_change,
+        interval.p50,
+        interval.p99,
+        interval.p999,
+        rss,
+    );
+}
+
+/// The change from the first value to the second one, in percent.
+fn change(first: f64, second: f64) -> f64 {
+    if first == 0.0 {
+        0.0
+    } else {
+        (second - first) / first * 100.0
+    }
+}
+
+pub fn print_soak_report(report: &SoakReport) {
+    println!("{} lookups in {} intervals", report.lookups, report.intervals);
+    match report.drift {
+        Some(drift) if report.drifted => println!("the throughput drifted by {:+.02}%", drift),
+        Some(drift) => println!("the throughput is stable ({:+.02}%)", drift),
+        None => println!("the run is too short to measure a drift"),
+    }
+    match report.rss_growth {
+        Some(growth) if report.leaked => {
+            println!("the resident memory grew by {:+.02}%, it may leak", growth)
+        }
+        Some(growth) => println!("the resident memory is stable ({:+.02}%)", growth),
+        None => println!("the resident memory is unknown on this platform"),
+    }
+    println!();
+}
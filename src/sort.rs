@@ -0,0 +1,206 @@
+//! The `--sort-by` expressions, weighted sums of the measured metrics
+//! that are used to rank the configurations, lower is better.
+//!
+//! An expression is a sum of terms like `jump+0.5*iter` where each term is a
+//! metric optionally multiplied by a weight. The durations are expressed in
+//! milliseconds, the file size in MiB and the energy in joules. The `slo-margin`
+//! is the largest ratio of a measured seek percentile to its `--slo` target. The
+//! `pipeline-cost` is the score of the `--pipeline-cost` expression.
+//!
+//! The expressions can be chained, the next ones only order the configurations
+//! that are tied on the previous ones, optionally within a tolerance.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::{Outcome, Parameters, Results};
+
+const METRIC_NAMES: &[&str] = &[
+    "iter",
+    "keys-iter",
+    "jump",
+    "exact-get",
+    "write",
+    "ingestion",
+    "pipeline-cost",
+    "file-size",
+    "energy",
+    "slo-margin",
+    "iter-only",
+    "jump-only",
+    "iter-and-jump",
+];
+
+#[derive(Debug, Copy, Clone)]
+enum Metric {
+    Iter,
+    KeysIter,
+    Jump,
+    ExactGet,
+    Write,
+    Ingestion,
+    PipelineCost,
+    FileSize,
+    Energy,
+    SloMargin,
+}
+
+impl Metric {
+    fn from_name(name: &str) -> Option<&'static [Metric]> {
+        match name {
+            "iter" | "iter-only" => Some(&[Metric::Iter]),
+            "keys-iter" => Some(&[Metric::KeysIter]),
+            "jump" | "jump-only" => Some(&[Metric::Jump]),
+            "exact-get" => Some(&[Metric::ExactGet]),
+            "iter-and-jump" => Some(&[Metric::Iter, Metric::Jump]),
+            "write" => Some(&[Metric::Write]),
+            "ingestion" => Some(&[Metric::Ingestion]),
+            "pipeline-cost" => Some(&[Metric::PipelineCost]),
+            "file-size" | "file_size" => Some(&[Metric::FileSize]),
+            "energy" => Some(&[Metric::Energy]),
+            "slo-margin" => Some(&[Metric::SloMargin]),
+            _ => None,
+        }
+    }
+
+    fn value(self, results: &Results) -> Option<f64> {
+        match self {
+            Metric::Iter => results.iter_time.map(millis),
+            Metric::KeysIter => results.keys_iter_time.map(millis),
+            Metric::Jump => results.jump_time.map(millis),
+            Metric::ExactGet => results.exact_get_time.map(millis),
+            Metric::Write => results.write_time.map(millis),
+            Metric::Ingestion => results.ingestion_time.map(millis),
+            Metric::PipelineCost => results.pipeline_cost,
+            Metric::FileSize => Some(results.file_size as f64 / (1024.0 * 1024.0)),
+            Metric::Energy => results.energy,
+            Metric::SloMargin => results.slo.map(|slo| slo.margin),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SortExpression {
+    source: String,
+    terms: Vec<(f64, Metric)>,
+}
+
+impl SortExpression {
+    /// Scores the results, the metrics that were not measured (e.g. a skipped phase)
+    /// are ignored and `None` is returned if none of them were measured.
+    pub fn score(&self, results: &Results) -> Option<f64> {
+        let mut score = None;
+        for &(weight, metric) in &self.terms {
+            if let Some(value) = metric.value(results) {
+                *score.get_or_insert(0.0) += weight * value;
+            }
+        }
+        score
+    }
+}
+
+impl FromStr for SortExpression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut terms = Vec::new();
+        for term in s.split('+') {
+            let mut weight = 1.0;
+            let mut metrics = None;
+            for factor in term.split('*').map(str::trim) {
+                if let Ok(number) = factor.parse::<f64>() {
+                    if !number.is_finite() {
+                        return Err(format!("invalid weight `{}`", factor));
+                    }
+                    weight *= number;
+                } else if let Some(found) = Metric::from_name(factor) {
+                    if metrics.replace(found).is_some() {
+                        return Err(format!("the term `{}` multiplies two metrics", term.trim()));
+                    }
+                } else {
+                    return Err(format!(
+                        "unknown metric `{}`, expected one of {}",
+                        factor,
+                        METRIC_NAMES.join(", ")
+                    ));
+                }
+            }
+            match metrics {
+                Some(metrics) => terms.extend(metrics.iter().map(|&metric| (weight, metric))),
+                None => return Err(format!("the term `{}` has no metric", term.trim())),
+            }
+        }
+        Ok(SortExpression { source: s.to_string(), terms })
+    }
+}
+
+impl fmt::Display for SortExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Sorts the results by the first key, the configurations within `tolerance` percent
+/// of the best one of their bucket are tied and sorted by the next keys. The remaining
+/// ties are broken by the parameters, the failed and unscored configurations are last.
+pub fn sort_results(
+    results: &mut [(Parameters, Outcome)],
+    keys: &[SortExpression],
+    tolerance: f64,
+) {
+    results.sort_unstable_by_key(|(p, _)| {
+        (p.compression as u8, p.index_levels, p.block_size, p.index_key_interval)
+    });
+    sort_by_keys(results, keys, tolerance);
+}
+
+fn sort_by_keys(results: &mut [(Parameters, Outcome)], keys: &[SortExpression], tolerance: f64) {
+    let (key, next_keys) = match keys.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+
+    let score =
+        |(_, outcome): &(Parameters, Outcome)| outcome.as_ref().ok().and_then(|r| key.score(r));
+    // The sort must be stable to keep the parameters order between the ties.
+    results.sort_by(|a, b| match (score(a), score(b)) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    });
+
+    let mut start = 0;
+    while start < results.len() {
+        let best = score(&results[start]);
+        let len = results[start..]
+            .iter()
+            .take_while(|result| is_tied(best, score(result), tolerance))
+            .count()
+            .max(1);
+        sort_by_keys(&mut results[start..start + len], next_keys, tolerance);
+        start += len;
+    }
+}
+
+fn is_tied(best: Option<f64>, score: Option<f64>, tolerance: f64) -> bool {
+    match (best, score) {
+        (Some(best), Some(score)) => score <= best + best.abs() * tolerance / 100.0,
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Parses a percentage with an optional `%` suffix, e.g. `2%` or `2.5`.
+pub fn parse_percentage(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let number = s.strip_suffix('%').unwrap_or(s);
+    match number.parse::<f64>() {
+        Ok(percentage) if percentage >= 0.0 && percentage.is_finite() => Ok(percentage),
+        _ => Err(format!("invalid percentage `{}`", s)),
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,267 @@
+//! The sorter benchmark, the unordered and duplicated insertions of an indexing
+//! are sorted and merged by a grenad `Sorter` before being written into a file.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use grenad::{ChunkCreator, CompressionType, SorterBuilder, WriterBuilder};
+use indicatif::{ProgressBar, ProgressIterator, ProgressStyle};
+
+use crate::merge::MergeFunction;
+
+/// The parameters of the sorter, they define its memory budget.
+#[derive(Debug, Copy, Clone)]
+pub struct SorterParameters {
+    pub merge_function: MergeFunction,
+    pub dump_threshold: usize,
+    pub max_nb_chunks: usize,
+    pub chunk_compression: CompressionType,
+}
+
+#[derive(Debug)]
+pub struct SorterResults {
+    pub insertions: u64,
+    /// The time taken to insert the entries, it includes the dumps of the chunks.
+    pub insert_time: Duration,
+    /// The time taken to merge the chunks and write the final file.
+    pub write_time: Duration,
+    pub file_size: u64,
+    pub chunks: ChunkStats,
+}
+
+/// What the sorter wrote in its temporary chunks, the I/O amplification
+/// of a memory budget that wall times alone don't show.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ChunkStats {
+    /// The number of chunks created, by the dumps and the intermediate merges.
+    pub created: u64,
+    /// The number of chunks created by the intermediate merges.
+    pub merged: u64,
+    /// The number of bytes written into the chunks.
+    pub bytes_written: u64,
+    /// Whether chunks have been read since the last one was created, the chunks
+    /// are only read when merged and the merge output is created before the reads.
+    reads_since_creation: bool,
+}
+
+impl ChunkStats {
+    /// The number of chunks created by the dumps of the in-memory entries.
+    pub fn dumped(&self) -> u64 {
+        self.created - self.merged
+    }
+
+    pub fn average_chunk_size(&self) -> Option<u64> {
+        self.bytes_written.checked_div(self.created)
+    }
+
+    /// The number of times the chunks were merged, including the final merge.
+    pub fn merge_passes(&self) -> u64 {
+        self.merged + 1
+    }
+
+    fn on_create(&mut self) {
+        if self.reads_since_creation {
+            self.merged += 1;
+        }
+        self.created += 1;
+        self.reads_since_creation = false;
+    }
+
+    /// Must be called before the final merge, its reads must not be mistaken
+    /// for the ones of an intermediate merge.
+    fn on_insertions_end(&mut self) {
+        if self.reads_since_creation {
+            self.merged += 1;
+        }
+        self.reads_since_creation = false;
+    }
+}
+
+/// Creates the chunks in temporary files, counting what is written into them.
+///
+/// The files are unlinked as soon as they are created, they are
+/// removed when the sorter is dropped, even when it failed.
+#[derive(Default, Clone)]
+struct CountingChunkCreator {
+    temp_folder: Option<PathBuf>,
+    stats: Arc<Mutex<ChunkStats>>,
+}
+
+impl ChunkCreator for CountingChunkCreator {
+    type Chunk = CountingChunk;
+    type Error = io::Error;
+
+    fn create(&self) -> io::Result<CountingChunk> {
+        let file = match &self.temp_folder {
+            Some(folder) => tempfile::tempfile_in(folder)?,
+            None => tempfile::tempfile()?,
+        };
+        self.stats.lock().unwrap().on_create();
+        Ok(CountingChunk { file, stats: self.stats.clone() })
+    }
+}
+
+struct CountingChunk {
+    file: File,
+    stats: Arc<Mutex<ChunkStats>>,
+}
+
+impl Write for CountingChunk {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.stats.lock().unwrap().bytes_written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Read for CountingChunk {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stats.lock().unwrap().reads_since_creation = true;
+        self.file.read(buf)
+    }
+}
+
+impl Seek for CountingChunk {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
+
+/// Inserts the words in the given order, each time with a new value,
+/// and writes the sorted and merged entries into a file of the folder.
+/// The chunks are created in the temporary folder when there is one.
+pub fn test_sorter(
+    folder: &Path,
+    temp_folder: Option<&Path>,
+    seed: u64,
+    words: &[Vec<u8>],
+    insertions: &[usize],
+    params: &SorterParameters,
+) -> anyhow::Result<SorterResults> {
+    let merge_function = params.merge_function;
+    if let Some(temp_folder) = temp_folder {
+        fs::metadata(temp_folder)
+            .with_context(|| format!("while accessing {}", temp_folder.display()))?;
+    }
+    let chunk_creator = CountingChunkCreator {
+        temp_folder: temp_folder.map(Path::to_path_buf),
+        stats: Arc::default(),
+    };
+    let mut sorter = SorterBuilder::new(merge_function.function())
+        .dump_threshold(params.dump_threshold)
+        .max_nb_chunks(params.max_nb_chunks)
+        .chunk_compression_type(params.chunk_compression)
+        .chunk_creator(chunk_creator.clone())
+        .build();
+
+    let pb = ProgressBar::new(insertions.len() as u64)
+        .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
+    let mut buffer = Vec::new();
+    let before_insert = Instant::now();
+    for (i, &index) in insertions.iter().enumerate().progress_with(pb) {
+        merge_function.generate_value(seed, i as u64, &mut buffer);
+        sorter.insert(&words[index], &buffer)?;
+    }
+    let insert_time = before_insert.elapsed();
+    chunk_creator.stats.lock().unwrap().on_insertions_end();
+
+    let filepath = folder.join(format!("sorter.{:?}.grd", merge_function));
+    let file = File::create(&filepath)
+        .with_context(|| format!("while creating {}", filepath.display()))?;
+    let before_write = Instant::now();
+    let mut writer = WriterBuilder::new().build(BufWriter::new(file));
+    let written = sorter
+        .write_into_stream_writer(&mut writer)
+        .map_err(anyhow::Error::from)
+        .and_then(|()| Ok(writer.into_inner()?.into_inner()?));
+    let file = match written {
+        Ok(file) => file,
+        Err(error) => {
+            // A partially written file must not be mistaken for a complete one.
+            let _ = fs::remove_file(&filepath);
+            return Err(error);
+        }
+    };
+    let write_time = before_write.elapsed();
+    let file_size = file.metadata()?.len();
+
+    let chunks = *chunk_creator.stats.lock().unwrap();
+    Ok(SorterResults {
+        insertions: insertions.len() as u64,
+        insert_time,
+        write_time,
+        file_size,
+        chunks,
+    })
+}
+
+pub fn print_sorter_results(params: &SorterParameters, results: &SorterResults) {
+    println!("{:#?}", params);
+    println!(
+        "took {:.02?} to insert {} entries ({:.0} insertions/s)",
+        results.insert_time,
+        results.insertions,
+        crate::stats::throughput(results.insertions, results.insert_time),
+    );
+    println!("took {:.02?} to merge and write the entries", results.write_time);
+    println!("file size: {} bytes", results.file_size);
+    let chunks = &results.chunks;
+    println!(
+        "{} chunks dumped, {} merged, {} merge passes",
+        chunks.dumped(),
+        chunks.merged,
+        chunks.merge_passes()
+    );
+    match chunks.average_chunk_size() {
+        Some(size) => println!(
+            "{} bytes written into the chunks, {} bytes per chunk on average",
+            chunks.bytes_written, size
+        ),
+        None => println!("no chunk was written, the entries fit in memory"),
+    }
+    println!();
+}
+
+/// Prints one line per configuration, the bytes written into the chunks relative
+/// to the size of the final file is the I/O amplification of the memory budget.
+pub fn print_amplification_table(results: &[(SorterParameters, anyhow::Result<SorterResults>)]) {
+    println!(
+        "{:>14} {:>6} {:>8} {:>10} {:>7} {:>7} {:>14} {:>13}",
+        "dump threshold",
+        "chunks",
+        "compr.",
+        "total time",
+        "dumped",
+        "passes",
+        "avg chunk size",
+        "amplification"
+    );
+    for (params, outcome) in results {
+        let results = match outcome {
+            Ok(results) => results,
+            Err(_) => continue,
+        };
+        let chunks = &results.chunks;
+        let compression = format!("{:?}", params.chunk_compression);
+        let amplification = chunks.bytes_written as f64 / results.file_size.max(1) as f64;
+        println!(
+            "{:>14} {:>6} {:>8} {:>10.02?} {:>7} {:>7} {:>14} {:>12.02}x",
+            params.dump_threshold,
+            params.max_nb_chunks,
+            compression,
+            results.insert_time + results.write_time,
+            chunks.dumped(),
+            chunks.merge_passes(),
+            chunks.average_chunk_size().unwrap_or(0),
+            amplification,
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! The validation of the order of the keys of a source file, the keys of a grenad file
+//! must be strictly increasing. The cursors of a file out of order don't find the keys
+//! they seek and its entries are rejected by the writers of the generated files, a bad
+//! source surfaced as a confusing error in the middle of a sweep.
+//!
+//! The keys are read once in the order of the file before it is extracted, every key equal
+//! to the previous one is a duplicate and every lower key is out of order. The anomalies
+//! are reported with the index of their entry in the file and either fail the run or are
+//! only reported, the entries whose key isn't greater than every previous one are then
+//! skipped wherever the file is read, the lookups and the generated files see sorted and
+//! unique keys, the file itself is not evaluated as is.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::Cursor;
+use std::str::FromStr;
+
+use grenad::Reader;
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::progress::PROGRESS_STEP;
+
+pub const POSSIBLE_SOURCE_ORDERS: &[&str] = &["fail", "warn", "off"];
+
+/// The number of anomalies printed, the others are only counted.
+const REPORTED_ANOMALIES: usize = 10;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SourceOrder {
+    /// The anomalies are reported and fail the run.
+    Fail,
+    /// The anomalies are reported, their entries are skipped.
+    Warn,
+    /// The order of the keys is not validated.
+    Off,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum AnomalyKind {
+    Duplicate,
+    OutOfOrder,
+}
+
+/// A key that is not strictly greater than the previous one.
+struct Anomaly {
+    kind: AnomalyKind,
+    /// The index of the entry in the file, not a byte offset.
+    entry: u64,
+    previous: Vec<u8>,
+    key: Vec<u8>,
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            AnomalyKind::Duplicate => write!(
+                f,
+                "entry {}: duplicate key {:?}",
+                self.entry,
+                String::from_utf8_lossy(&self.key)
+            ),
+            AnomalyKind::OutOfOrder => write!(
+                f,
+                "entry {}: key {:?} is lower than the previous key {:?}",
+                self.entry,
+                String::from_utf8_lossy(&self.key),
+                String::from_utf8_lossy(&self.previous),
+            ),
+        }
+    }
+}
+
+/// The anomalies of the keys read so far, the first ones are kept to be reported.
+#[derive(Default)]
+struct Scan {
+    previous: Vec<u8>,
+    reported: Vec<Anomaly>,
+    duplicates: u64,
+    out_of_order: u64,
+    entries: u64,
+}
+
+impl Scan {
+    fn push(&mut self, key: &[u8]) {
+        let kind = match key.cmp(&self.previous[..]) {
+            _ if self.entries == 0 => None,
+            Ordering::Greater => None,
+            Ordering::Equal => Some(AnomalyKind::Duplicate),
+            Ordering::Less => Some(AnomalyKind::OutOfOrder),
+        };
+        match kind {
+            Some(AnomalyKind::Duplicate) => self.duplicates += 1,
+            Some(AnomalyKind::OutOfOrder) => self.out_of_order += 1,
+            None => (),
+        }
+        if let Some(kind) = kind.filter(|_| self.reported.len() < REPORTED_ANOMALIES) {
+            self.reported.push(Anomaly {
+                kind,
+                entry: self.entries,
+                previous: self.previous.clone(),
+                key: key.to_vec(),
+            });
+        }
+        self.previous.clear();
+        self.previous.extend_from_slice(key);
+        self.entries += 1;
+    }
+}
+
+/// Reads the keys of the file in order and reports the duplicate and out-of-order ones,
+/// fails when there are some and the order must be respected, returns whether the keys
+/// are strictly increasing, assumed when the order is not validated.
+pub fn check(bytes: &[u8], order: SourceOrder) -> anyhow::Result<bool> {
+    if order == SourceOrder::Off {
+        return Ok(true);
+    }
+
+    println!("validating the order of the keys...");
+    let mut cursor = Reader::new(Cursor::new(bytes))?.into_cursor()?;
+    let pb = ProgressBar::new(cursor.len())
+        .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
+    let mut scan = Scan::default();
+    while let Some((key, _)) = cursor.move_on_next()? {
+        scan.push(key);
+        if scan.entries % PROGRESS_STEP == 0 {
+            pb.set_position(scan.entries);
+        }
+    }
+    pb.finish_and_clear();
+
+    if scan.reported.is_empty() {
+        return Ok(true);
+    }
+    for anomaly in &scan.reported {
+        eprintln!("{}", anomaly);
+    }
+    let message = format!(
+        "the keys of the file are not strictly increasing, {} duplicate and {} out-of-order \
+         keys, the first at entry {}",
+        scan.duplicates, scan.out_of_order, scan.reported[0].entry,
+    );
+    match order {
+        SourceOrder::Fail => anyhow::bail!("{}, use --source-order warn to skip them", message),
+        SourceOrder::Warn | SourceOrder::Off => {
+            eprintln!("{}, their entries are skipped", message);
+            Ok(false)
+        }
+    }
+}
+
+/// Keeps the entries whose key is greater than every key kept before,
+/// the entries of a file read in its order are then sorted and unique.
+#[derive(Default)]
+pub struct SortedKeys {
+    last: Option<Vec<u8>>,
+}
+
+impl SortedKeys {
+    /// Whether the entry of this key is kept, the following ones must be greater.
+    pub fn keep(&mut self, key: &[u8]) -> bool {
+        match &mut self.last {
+            Some(last) if key <= &last[..] => false,
+            Some(last) => {
+                last.clear();
+                last.extend_from_slice(key);
+                true
+            }
+            None => {
+                self.last = Some(key.to_vec());
+                true
+            }
+        }
+    }
+}
+
+impl FromStr for SourceOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SourceOrder, String> {
+        match s {
+            "fail" => Ok(SourceOrder::Fail),
+            "warn" => Ok(SourceOrder::Warn),
+            "off" => Ok(SourceOrder::Off),
+            otherwise => {
+                Err(format!("invalid source order {:?}, expected fail, warn or off", otherwise))
+            }
+        }
+    }
+}
+
+impl fmt::Display for SourceOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SourceOrder::Fail => f.write_str("fail"),
+            SourceOrder::Warn => f.write_str("warn"),
+            SourceOrder::Off => f.write_str("off"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grenad::WriterBuilder;
+
+    use super::*;
+
+    // The writers of grenad reject the keys out of order, the
+    // anomalies are scanned from the keys of an unsorted file.
+    const UNSORTED: [&[u8]; 5] = [b"apple", b"banana", b"banana", b"abricot", b"cherry"];
+
+    #[test]
+    fn scan_counts_the_anomalies() {
+        let mut scan = Scan::default();
+        UNSORTED.iter().for_each(|key| scan.push(key));
+        assert_eq!((scan.duplicates, scan.out_of_order, scan.entries), (1, 1, 5));
+        let kinds: Vec<_> = scan.reported.iter().map(|a| (a.kind, a.entry)).collect();
+        assert_eq!(kinds, [(AnomalyKind::Duplicate, 2), (AnomalyKind::OutOfOrder, 3)]);
+        assert_eq!(scan.reported[1].previous, b"banana");
+    }
+
+    #[test]
+    fn sorted_keys_skip_the_anomalies() {
+        let mut sorted = SortedKeys::default();
+        let kept: Vec<_> = UNSORTED.iter().copied().filter(|key| sorted.keep(key)).collect();
+        assert_eq!(kept, [&b"apple"[..], b"banana", b"cherry"]);
+    }
+
+    #[test]
+    fn sorted_file_passes() {
+        let mut writer = WriterBuilder::new().build(Vec::new());
+        for key in [&b"apple"[..], b"banana", b"cherry"] {
+            writer.insert(key, b"").unwrap();
+        }
+        let bytes = writer.into_inner().unwrap();
+        assert!(check(&bytes, SourceOrder::Fail).unwrap());
+        assert!(check(&bytes, SourceOrder::Warn).unwrap());
+    }
+
+    #[test]
+    fn off_short_circuits() {
+        assert!(check(b"not a grenad file", SourceOrder::Off).unwrap());
+        assert!(check(b"not a grenad file", SourceOrder::Fail).is_err());
+    }
+}
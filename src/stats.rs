@@ -0,0 +1,167 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Returns the value at the given percentile (between `0.0` and `100.0`)
+/// of an already sorted slice, the default value if the slice is empty.
+pub fn percentile<T: Copy + Default>(sorted: &[T], percentile: f64) -> T {
+    if sorted.is_empty() {
+        return T::default();
+    }
+    let rank = (percentile / 100.0 * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// Returns the median of the values, `None` if there are none.
+pub fn median<T: Copy + Default + PartialOrd>(mut values: Vec<T>) -> Option<T> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    Some(percentile(&values, 50.0))
+}
+
+/// Returns the coefficient of variation of the values as a percentage,
+/// `None` if there are less than two values or their mean is zero.
+pub fn coefficient_of_variation(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    if mean == 0.0 {
+        return None;
+    }
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some(variance.sqrt() / mean * 100.0)
+}
+
+/// Fits `y = a * x^k` to the points by a least squares regression of their
+/// logarithms and returns `k`, `None` with less than two usable points.
+pub fn power_law_exponent(points: &[(f64, f64)]) -> Option<f64> {
+    let logs: Vec<_> = points
+        .iter()
+        .filter(|(x, y)| *x > 0.0 && *y > 0.0)
+        .map(|(x, y)| (x.ln(), y.ln()))
+        .collect();
+    if logs.len() < 2 {
+        return None;
+    }
+    let n = logs.len() as f64;
+    let mean_x = logs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = logs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = logs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        return None;
+    }
+    Some(covariance / variance)
+}
+
+/// Returns the number of operations executed per second.
+pub fn throughput(operations: u64, elapsed: Duration) -> f64 {
+    operations as f64 / elapsed.as_secs_f64()
+}
+
+/// The shape of a dataset, performance results are uninterpretable without it.
+///
+/// We count the occurrences of every key length and value size instead of
+/// storing them all, datasets can contain hundreds of millions of entries.
+#[derive(Debug, Default, Clone)]
+pub struct DatasetStats {
+    pub entries: u64,
+    pub key_bytes: u64,
+    pub value_bytes: u64,
+    key_lengths: BTreeMap<usize, u64>,
+    value_sizes: BTreeMap<usize, u64>,
+}
+
+impl DatasetStats {
+    pub fn insert(&mut self, key: &[u8], value: &[u8]) {
+        self.entries += 1;
+        self.key_bytes += key.len() as u64;
+        self.value_bytes += value.len() as u64;
+        *self.key_lengths.entry(key.len()).or_default() += 1;
+        *self.value_sizes.entry(value.len()).or_default() += 1;
+    }
+
+    /// The statistics of the entries counted by these distributions, of the key lengths
+    /// and of the value sizes.
+    pub fn from_distributions(
+        key_lengths: BTreeMap<usize, u64>,
+        value_sizes: BTreeMap<usize, u64>,
+    ) -> DatasetStats {
+        DatasetStats {
+            entries: key_lengths.values().sum(),
+            key_bytes: key_lengths.iter().map(|(&length, &count)| length as u64 * count).sum(),
+            value_bytes: value_sizes.iter().map(|(&size, &count)| size as u64 * count).sum(),
+            key_lengths,
+            value_sizes,
+        }
+    }
+
+    /// The number of keys of every length and of values of every size.
+    pub fn distributions(&self) -> (&BTreeMap<usize, u64>, &BTreeMap<usize, u64>) {
+        (&self.key_lengths, &self.value_sizes)
+    }
+
+    /// Adds the entries counted by the other statistics, of another part of the dataset.
+    pub fn merge(&mut self, other: DatasetStats) {
+        self.entries += other.entries;
+        self.key_bytes += other.key_bytes;
+        self.value_bytes += other.value_bytes;
+        for (length, count) in other.key_lengths {
+            *self.key_lengths.entry(length).or_default() += count;
+        }
+        for (size, count) in other.value_sizes {
+            *self.value_sizes.entry(size).or_default() += count;
+        }
+    }
+}
+
+fn counts_percentile(counts: &BTreeMap<usize, u64>, total: u64, percentile: f64) -> usize {
+    let rank = (percentile / 100.0 * total.saturating_sub(1) as f64).round() as u64;
+    let mut seen = 0;
+    for (&size, &count) in counts {
+        seen += count;
+        if seen > rank {
+            return size;
+        }
+    }
+    0
+}
+
+impl fmt::Display for DatasetStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "entries: {}", self.entries)?;
+        writeln!(
+            f,
+            "total size: {} bytes ({} bytes of keys, {} bytes of values)",
+            self.key_bytes + self.value_bytes,
+            self.key_bytes,
+            self.value_bytes,
+        )?;
+        write_distribution(f, "key length", &self.key_lengths, self.entries)?;
+        write_distribution(f, "value size", &self.value_sizes, self.entries)?;
+        Ok(())
+    }
+}
+
+fn write_distribution(
+    f: &mut fmt::Formatter,
+    name: &str,
+    counts: &BTreeMap<usize, u64>,
+    total: u64,
+) -> fmt::Result {
+    writeln!(
+        f,
+        "{}: min {}, p50 {}, p90 {}, p99 {}, max {}",
+        name,
+        counts_percentile(counts, total, 0.0),
+        counts_percentile(counts, total, 50.0),
+        counts_percentile(counts, total, 90.0),
+        counts_percentile(counts, total, 99.0),
+        counts_percentile(counts, total, 100.0),
+    )
+}
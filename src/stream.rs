@@ -0,0 +1,58 @@
+//! The streaming of the results of a sweep as NDJSON, one line is printed on the
+//! standard output as soon as a configuration is evaluated, in completion order.
+//!
+//! The progress bars are drawn on the standard error, a wrapper consuming the
+//! results in real time keeps the lines of the standard output starting with `{`.
+
+use std::io::{self, Write};
+
+use crate::experiment::ExperimentId;
+use crate::json::string;
+use crate::{Outcome, Parameters};
+
+/// Prints the line of an evaluated configuration.
+pub fn emit_result(experiment: ExperimentId, params: &Parameters, outcome: &Outcome) {
+    let status = match outcome {
+        Ok(results) => {
+            let mut status = String::from("\"status\":\"ok\"");
+            for metric in results.metrics() {
+                status.push_str(&format!(",{}:{}", string(metric.name), metric.to_json()));
+            }
+            status.push_str(&format!(
+                ",\"noisy\":{},\"repetitions\":{},\"slo_passed\":{},\"validation\":{}",
+                results.noisy,
+                results.samples.len(),
+                results.slo.map_or_else(|| "null".to_string(), |slo| slo.passed.to_string()),
+                string(&results.validation.to_string()),
+            ));
+            status
+        }
+        Err(error) => format!("\"status\":\"failed\",\"error\":{}", string(error)),
+    };
+    emit(experiment, params, &status);
+}
+
+/// Prints the line of a configuration unsupported by the grenad version.
+pub fn emit_skipped(experiment: ExperimentId, params: &Parameters, reason: &str) {
+    let status = format!("\"status\":\"skipped\",\"reason\":{}", string(reason));
+    emit(experiment, params, &status);
+}
+
+fn emit(experiment: ExperimentId, params: &Parameters, status: &str) {
+    let line = format!(
+        "{{\"experiment_id\":{},\"name\":{},\"compression\":{},\"index_levels\":{},\"block_size\":{},\
+         \"index_key_interval\":{},{}}}\n",
+        string(&experiment.to_string()),
+        string(&params.file_name()),
+        string(&format!("{:?}", params.compression).to_lowercase()),
+        params.index_levels,
+        params.block_size,
+        params.index_key_interval,
+        status,
+    );
+    // The line is written at once, under the lock, the lines of the
+    // threads evaluating the configurations are not interleaved.
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let _ = stdout.write_all(line.as_bytes()).and_then(|()| stdout.flush());
+}
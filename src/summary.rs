@@ -0,0 +1,86 @@
+//! A short Markdown summary of a sweep, opinionated and small enough
+//! to be pasted as is in a pull request comment.
+
+use std::cmp::Ordering;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::report::Environment;
+use crate::significance;
+use crate::{Outcome, Parameters};
+
+/// The number of configurations listed for every sort key.
+const TOP_CONFIGURATIONS: usize = 5;
+
+pub fn write_markdown_summary(
+    path: &Path,
+    environment: &Environment,
+    results: &[(Parameters, Outcome)],
+) -> anyhow::Result<()> {
+    let mut markdown = String::new();
+    let failed = results.iter().filter(|(_, outcome)| outcome.is_err()).count();
+    writeln!(markdown, "### test-grenad {}", environment.subcommand)?;
+    writeln!(markdown)?;
+    writeln!(
+        markdown,
+        "version {} · {}/{} · {} threads · seed {} · experiment `{}` · read method `{}` · \
+         {} configurations ({} failed, {} skipped)",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        rayon::current_num_threads(),
+        environment.seed,
+        environment.experiment,
+        environment.read_method,
+        results.len() + environment.skipped.len(),
+        failed,
+        environment.skipped.len(),
+    )?;
+
+    let baseline = significance::baseline_parameters();
+    let baseline = results
+        .iter()
+        .find(|(params, _)| significance::is_same_configuration(params, &baseline))
+        .and_then(|(_, outcome)| outcome.as_ref().ok());
+
+    for key in &environment.sort.sort_by {
+        let mut scored: Vec<_> = results
+            .iter()
+            .filter_map(|(params, outcome)| Some((params, key.score(outcome.as_ref().ok()?)?)))
+            .collect();
+        scored.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        let baseline_score = baseline.and_then(|results| key.score(results));
+
+        writeln!(markdown)?;
+        writeln!(markdown, "#### `{}`, lower is better", key)?;
+        writeln!(markdown)?;
+        writeln!(markdown, "| # | configuration | score | vs default |")?;
+        writeln!(markdown, "|--:|---|--:|--:|")?;
+        for (rank, (params, score)) in scored.iter().take(TOP_CONFIGURATIONS).enumerate() {
+            let delta = match baseline_score {
+                Some(baseline) if baseline != 0.0 => {
+                    format!("{:+.02}%", (score - baseline) / baseline * 100.0)
+                }
+                _ => "n/a".to_string(),
+            };
+            writeln!(
+                markdown,
+                "| {} | `{}` | {:.03} | {} |",
+                rank + 1,
+                params.file_name(),
+                score,
+                delta,
+            )?;
+        }
+        if baseline_score.is_none() {
+            writeln!(markdown)?;
+            writeln!(markdown, "The default parameters were not evaluated successfully.")?;
+        }
+    }
+
+    fs::write(path, markdown).with_context(|| format!("while writing {}", path.display()))?;
+    Ok(())
+}
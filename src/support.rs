@@ -0,0 +1,68 @@
+//! The detection of the parameters the compiled in grenad version doesn't support.
+//!
+//! Every configuration of a sweep is probed before any file is generated, a small
+//! file is written in memory and read back. The configurations that fail the probe
+//! are skipped with the reason, instead of failing one by one in the middle of the sweep.
+
+use std::io::Cursor;
+
+use grenad::{Reader, WriterBuilder};
+
+use crate::{capture_failure, Parameters};
+
+/// The number of entries of the probe, enough to fill several blocks and index levels.
+const PROBE_ENTRIES: u32 = 4096;
+
+/// Writes and reads back a small file with the parameters, returns why it failed.
+pub fn probe(params: &Parameters) -> Result<(), String> {
+    capture_failure(|| {
+        let mut writer = WriterBuilder::new()
+            .compression_type(params.compression)
+            .index_levels(params.index_levels)
+            .block_size(params.block_size)
+            .index_key_interval(params.index_key_interval)
+            .build(Vec::new());
+        for i in 0..PROBE_ENTRIES {
+            writer.insert(format!("{:010}", i), i.to_be_bytes())?;
+        }
+        let bytes = writer.into_inner()?;
+
+        let mut cursor = Reader::new(Cursor::new(bytes))?.into_cursor()?;
+        let mut count = 0;
+        while let Some((key, _)) = cursor.move_on_next()? {
+            anyhow::ensure!(key == format!("{:010}", count).as_bytes(), "the keys are not sorted");
+            count += 1;
+        }
+        anyhow::ensure!(count == PROBE_ENTRIES, "{} entries read back", count);
+        let last = format!("{:010}", PROBE_ENTRIES - 1);
+        let found = cursor.move_on_key_greater_than_or_equal_to(&last)?.map(|(key, _)| key);
+        anyhow::ensure!(found == Some(last.as_bytes()), "the last key can't be found");
+        Ok(())
+    })
+}
+
+/// Splits the configurations into the supported ones and the skipped ones, with the reason.
+pub fn partition_supported(
+    parameters: Vec<Parameters>,
+) -> (Vec<Parameters>, Vec<(Parameters, String)>) {
+    let mut supported = Vec::with_capacity(parameters.len());
+    let mut skipped = Vec::new();
+    for params in parameters {
+        match probe(&params) {
+            Ok(()) => supported.push(params),
+            Err(reason) => skipped.push((params, reason)),
+        }
+    }
+    (supported, skipped)
+}
+
+pub fn print_skipped(skipped: &[(Parameters, String)]) {
+    if skipped.is_empty() {
+        return;
+    }
+    println!("{} configurations skipped, unsupported by this grenad version:", skipped.len());
+    for (params, reason) in skipped {
+        println!("    {}: {}", params.file_name(), reason);
+    }
+    println!();
+}
@@ -0,0 +1,648 @@
+//! The sweeps of the extended tests over the grid of parameters, the configurations
+//! are generated from a dataset, evaluated and reported the same way whatever the
+//! source of the dataset, random keys and values or the entries of a grenad file.
+//!
+//! A feature of the sweeps is implemented once, in `run`, the sources only
+//! generate the files and describe their entries.
+
+use std::io::Cursor;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::time::Instant;
+
+use grenad::Reader;
+use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
+use rand::rngs::StdRng;
+use rand::Rng;
+use rayon::prelude::*;
+
+use crate::arena::{WordArena, Words};
+use crate::budget;
+use crate::checkpoint::{self, Checkpoint};
+use crate::compressed::InputFile;
+use crate::config_list;
+use crate::datagen::{self, Stream, ValueGenerator};
+use crate::experiment::ExperimentId;
+use crate::extract::{self, Extracted};
+use crate::extract_cache;
+use crate::heap::{self, HeapBackend};
+use crate::manifest::Manifest;
+use crate::prefix::{self, PrefixRedundancy};
+use crate::progress::PhaseProgress;
+use crate::refine;
+use crate::sentinel::Sentinel;
+use crate::source_order::{self, SortedKeys, SourceOrder};
+use crate::stats::DatasetStats;
+use crate::{
+    assertion, capture_failure, compare_repetitions, critcmp, evaluate_file, generate_from_params,
+    generate_with_values, print_input_file_results, print_sentinel_runs, print_sweep_results,
+    record_recommendation, report, retention, sentinel_file, sort, stream, summary, support, tidy,
+    with_retries, GeneratedFile, GenerationOptions, Parameters, SweepOptions,
+};
+
+/// The source of the entries of the generated files.
+pub trait Dataset: Sync {
+    /// The name of the dataset in the long results.
+    fn name(&self) -> String;
+
+    /// The keys, sorted and unique, a sample of them when the dataset is sampled.
+    fn words(&self) -> &WordArena;
+
+    /// The number of entries of the dataset.
+    fn entry_count(&self) -> u64 {
+        self.words().len() as u64
+    }
+
+    /// The lengths of the values, in the order of the keys.
+    fn value_lengths(&self) -> Vec<usize>;
+
+    /// The entries held in memory by the heap baseline.
+    fn heap_entries(&self) -> anyhow::Result<Vec<(Vec<u8>, Vec<u8>)>>;
+
+    /// The maximum length of the bitmaps of the values, `None` if unbounded.
+    fn max_bitmap_len(&self) -> anyhow::Result<Option<u64>>;
+
+    /// Writes the entries with the parameters in the folder, the file is
+    /// reused if it already exists.
+    fn generate(
+        &self,
+        folder: &Path,
+        params: &Parameters,
+        generation: GenerationOptions,
+    ) -> anyhow::Result<GeneratedFile>;
+
+    /// The statistics of the entries, `None` if they were not read from a file.
+    fn stats(&self) -> Option<&DatasetStats> {
+        None
+    }
+
+    /// The file the entries were read from, evaluated as is.
+    fn input_file(&self) -> Option<&GeneratedFile> {
+        None
+    }
+
+    /// The parameters the input file was written with, if they are known.
+    fn current_parameters(&self) -> Option<Parameters> {
+        None
+    }
+}
+
+/// The random keys with the values generated for their index.
+pub struct RandomDataset {
+    pub name: String,
+    pub words: WordArena,
+    pub values: ValueGenerator,
+}
+
+impl Dataset for RandomDataset {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn words(&self) -> &WordArena {
+        &self.words
+    }
+
+    fn value_lengths(&self) -> Vec<usize> {
+        (0..self.words.len())
+            .into_par_iter()
+            .map_init(Vec::new, |buffer, i| {
+                self.values.generate(i as u64, buffer);
+                buffer.len()
+            })
+            .collect()
+    }
+
+    fn heap_entries(&self) -> anyhow::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let entries = self.words.iter().enumerate().map(|(i, word)| {
+            let mut value = Vec::new();
+            self.values.generate(i as u64, &mut value);
+            (word.to_vec(), value)
+        });
+        Ok(entries.collect())
+    }
+
+    fn max_bitmap_len(&self) -> anyhow::Result<Option<u64>> {
+        Ok(Manifest::of_values(&self.values).max_bitmap_len)
+    }
+
+    fn generate(
+        &self,
+        folder: &Path,
+        params: &Parameters,
+        generation: GenerationOptions,
+    ) -> anyhow::Result<GeneratedFile> {
+        generate_with_values(self.values, folder, &self.words, params, generation)
+    }
+}
+
+/// The number of keys sampled from a file to be looked up and the seed of the sample.
+#[derive(Debug, Copy, Clone)]
+pub struct KeySample {
+    pub size: NonZeroUsize,
+    pub seed: u64,
+}
+
+/// A uniform sample of the keys of a file and the lengths of their values, read in a
+/// single pass, every key replaces a sampled one with the probability that keeps the
+/// sample uniform.
+struct Reservoir {
+    size: usize,
+    rng: StdRng,
+    seen: u64,
+    entries: Vec<(u64, Vec<u8>, usize)>,
+}
+
+impl Reservoir {
+    fn new(sample: KeySample) -> Reservoir {
+        let rng = datagen::entry_rng(sample.seed, Stream::KeySample, 0);
+        Reservoir { size: sample.size.get(), rng, seen: 0, entries: Vec::new() }
+    }
+
+    fn insert(&mut self, key: &[u8], value_len: usize) {
+        if self.entries.len() < self.size {
+            self.entries.push((self.seen, key.to_vec(), value_len));
+        } else {
+            let replaced = self.rng.gen_range(0..=self.seen);
+            if let Some(entry) = self.entries.get_mut(replaced as usize) {
+                *entry = (self.seen, key.to_vec(), value_len);
+            }
+        }
+        self.seen += 1;
+    }
+
+    /// The sampled keys in the order of the file and the lengths of their values.
+    fn into_sorted(mut self) -> (WordArena, Vec<usize>) {
+        self.entries.sort_unstable_by_key(|(position, _, _)| *position);
+        let words = self.entries.iter().map(|(_, key, _)| key).collect();
+        (words, self.entries.iter().map(|(_, _, value_len)| *value_len).collect())
+    }
+}
+
+/// Extracts the words of the file, or samples them, the anomalies
+/// of the file are skipped when its keys are not `sorted`.
+fn extract_words(
+    bytes: &[u8],
+    sample: Option<KeySample>,
+    sorted: bool,
+) -> anyhow::Result<Extracted> {
+    println!("extracting the words...");
+    let extracted = match sample {
+        Some(sample) => {
+            let extracted = sample_words(bytes, sample)?;
+            let (sampled, entries) = (extracted.words.len(), extracted.stats.entries);
+            println!("{} words sampled out of {} entries!", sampled, entries);
+            return Ok(extracted);
+        }
+        None if sorted => extract::extract(bytes)?,
+        None => extract::extract_sorted(bytes)?,
+    };
+    println!("{} unique words extracted!", extracted.words.len());
+    Ok(extracted)
+}
+
+/// Samples the words of the file in a single pass, the sample can't be extracted in
+/// parallel, the statistics are still the ones of every entry but the anomalies.
+fn sample_words(bytes: &[u8], sample: KeySample) -> anyhow::Result<Extracted> {
+    let mut cursor = Reader::new(Cursor::new(bytes))?.into_cursor()?;
+    let pb = ProgressBar::new(cursor.len())
+        .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
+    let mut reservoir = Reservoir::new(sample);
+    let mut stats = DatasetStats::default();
+    let mut sorted = SortedKeys::default();
+    while let Some((k, v)) = cursor.move_on_next()? {
+        if sorted.keep(k) {
+            reservoir.insert(k, v.len());
+            stats.insert(k, v);
+        }
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+    let (words, value_lengths) = reservoir.into_sorted();
+    Ok(Extracted { words, value_lengths, stats })
+}
+
+/// The entries of a grenad file, the file stays mapped for the generation.
+pub struct FileDataset {
+    file: InputFile,
+    input: GeneratedFile,
+    words: WordArena,
+    value_lengths: Vec<usize>,
+    stats: DatasetStats,
+    current: Option<Parameters>,
+    /// Whether the keys of the file are sorted, it is not evaluated as is otherwise.
+    sorted: bool,
+}
+
+impl FileDataset {
+    /// Maps the file, decompressed if needed, and extracts its keys or a sample of them,
+    /// from the cache of the extractions when given, the parameters of the file are the
+    /// given ones or the ones of its name.
+    pub fn open(
+        path: &Path,
+        download_cache: &Path,
+        current: Option<Parameters>,
+        sample: Option<KeySample>,
+        extraction_cache: Option<&Path>,
+        order: SourceOrder,
+    ) -> anyhow::Result<FileDataset> {
+        let file = InputFile::open(path, download_cache)?;
+        let current = current.or_else(|| {
+            let dataset_path = file.dataset_path();
+            dataset_path.file_name()?.to_str().and_then(Parameters::from_file_name)
+        });
+
+        let sorted = source_order::check(file.bytes(), order)?;
+        let cached = extraction_cache.map(|cache| {
+            extract_cache::path(cache, extract_cache::source_hash(file.bytes()), sample)
+        });
+        let restored = match &cached {
+            Some(path) => extract_cache::read(path).unwrap_or_else(|e| {
+                eprintln!("could not read the cached extraction: {:#}", e);
+                None
+            }),
+            None => None,
+        };
+        let Extracted { words, value_lengths, stats } = match (restored, &cached) {
+            (Some(extracted), Some(path)) => {
+                println!("{} words read from {}", extracted.words.len(), path.display());
+                extracted
+            }
+            (_, cached) => {
+                let extracted = extract_words(file.bytes(), sample, sorted)?;
+                if let Some(path) = cached {
+                    if let Err(e) = extract_cache::write(path, &extracted) {
+                        eprintln!("could not cache the extraction: {:#}", e);
+                    }
+                }
+                extracted
+            }
+        };
+        println!("{}", stats);
+
+        let input = GeneratedFile { path: file.grenad_path().to_path_buf(), write_time: None };
+        Ok(FileDataset { file, input, words, value_lengths, stats, current, sorted })
+    }
+}
+
+impl Dataset for FileDataset {
+    fn name(&self) -> String {
+        self.file.name()
+    }
+
+    fn words(&self) -> &WordArena {
+        &self.words
+    }
+
+    fn entry_count(&self) -> u64 {
+        self.stats.entries
+    }
+
+    fn value_lengths(&self) -> Vec<usize> {
+        self.value_lengths.clone()
+    }
+
+    fn heap_entries(&self) -> anyhow::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut cursor = Reader::new(Cursor::new(self.file.bytes()))?.into_cursor()?;
+        let mut entries = Vec::with_capacity(self.words.len());
+        let mut sorted = SortedKeys::default();
+        while let Some((k, v)) = cursor.move_on_next()? {
+            if sorted.keep(k) {
+                entries.push((k.to_vec(), v.to_vec()));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn max_bitmap_len(&self) -> anyhow::Result<Option<u64>> {
+        Manifest::max_bitmap_len_of(&self.file.dataset_path())
+    }
+
+    fn generate(
+        &self,
+        folder: &Path,
+        params: &Parameters,
+        generation: GenerationOptions,
+    ) -> anyhow::Result<GeneratedFile> {
+        let mut cursor = Reader::new(Cursor::new(self.file.bytes()))?.into_cursor()?;
+        generate_from_params(folder, &mut cursor, params, generation)
+    }
+
+    fn stats(&self) -> Option<&DatasetStats> {
+        Some(&self.stats)
+    }
+
+    fn input_file(&self) -> Option<&GeneratedFile> {
+        self.sorted.then(|| &self.input)
+    }
+
+    fn current_parameters(&self) -> Option<Parameters> {
+        self.current
+    }
+}
+
+/// Where the files of a sweep are generated, the subcommand and
+/// the seed identify the sweep in the reports.
+pub struct Run<'a> {
+    pub subcommand: &'static str,
+    pub seed: u64,
+    pub folder: &'a Path,
+    pub generation: GenerationOptions,
+}
+
+/// Generates the files of the grid from the dataset, evaluates them with the lookups
+/// and reports the results, exits with the code of the failed assertions.
+pub fn run<D: Dataset>(
+    run: Run,
+    options: SweepOptions,
+    dataset: &D,
+    lookups: &[usize],
+) -> anyhow::Result<()> {
+    let started = Instant::now();
+    let Run { subcommand, seed, folder, generation } = run;
+    let SweepOptions {
+        read_method,
+        grid,
+        sort,
+        html_report,
+        critcmp_export,
+        long_results,
+        heap_baseline,
+        summary_markdown,
+        fail_if,
+        prefix_report,
+        model,
+        stream_results,
+        keep_files,
+        checkpoint: checkpointing,
+        time_budget,
+        refine: refinement_rounds,
+        evaluation,
+    } = options;
+    let words = dataset.words();
+
+    let mut parameters = grid.parameters(dataset.entry_count())?;
+    let current = dataset.current_parameters();
+    if let Some(current) = current {
+        println!("the input file was written with {}", current);
+        if !config_list::contains(&parameters, &current) {
+            parameters.push(current);
+        }
+    }
+    let fingerprint = checkpoint::fingerprint(words, lookups);
+    let experiment = ExperimentId::new(fingerprint, &parameters, read_method, &evaluation);
+    println!("experiment {}", experiment);
+
+    let (parameters, mut skipped) = support::partition_supported(parameters);
+    support::print_skipped(&skipped);
+    if stream_results {
+        for (params, reason) in &skipped {
+            stream::emit_skipped(experiment, params, reason);
+        }
+    }
+
+    let (checkpoint, restored) = Checkpoint::open(
+        folder,
+        fingerprint,
+        checkpointing.checkpoint_every.get(),
+        checkpointing.resume,
+        evaluation.workload.validate,
+    )?;
+    let (parameters, restored) = checkpoint::split_restored(parameters, restored);
+    if !restored.is_empty() {
+        println!("{} configurations restored from the checkpoint", restored.len());
+    }
+
+    // The files of a batch are all generated before they are evaluated, the generations
+    // don't compete with the evaluations, the sentinel runs during the last batch only.
+    let evaluate_batch = |parameters: Vec<Parameters>, sentinel_interval: Option<NonZeroUsize>| {
+        println!("generating the test files...");
+        let pb = ProgressBar::new(parameters.len() as u64)
+            .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
+        let params_files = parameters
+            .into_par_iter()
+            .map(|params| {
+                let generated = capture_failure(|| {
+                    with_retries(evaluation.retries, || {
+                        dataset.generate(folder, &params, generation)
+                    })
+                });
+                (params, generated)
+            })
+            .progress_with(pb)
+            .collect::<Vec<_>>();
+
+        println!("evaluating the test files...");
+        let pb = ProgressBar::new(params_files.len() as u64)
+            .with_style(ProgressStyle::default_bar().template("{wide_bar} {pos}/{len} {eta}"));
+        let output = pb.clone();
+        let sentinel = sentinel_interval.and_then(|interval| {
+            let generated = sentinel_file(&params_files)?;
+            let evaluation = &evaluation;
+            Some(Sentinel::new(interval, move || {
+                let mut progress = PhaseProgress::log(output.clone(), "sentinel".to_string());
+                evaluate_file(read_method, &generated, words, lookups, evaluation, &mut progress)
+            }))
+        });
+        if let Some(sentinel) = &sentinel {
+            sentinel.run();
+        }
+
+        let output = pb.clone();
+        let results = params_files
+            .into_par_iter()
+            .map(|(params, generated)| {
+                let mut progress = PhaseProgress::log(output.clone(), params.file_name());
+                let outcome = generated.and_then(|generated| {
+                    capture_failure(|| {
+                        with_retries(evaluation.retries, || {
+                            evaluate_file(
+                                read_method,
+                                &generated,
+                                words,
+                                lookups,
+                                &evaluation,
+                                &mut progress,
+                            )
+                        })
+                    })
+                });
+                if stream_results {
+                    stream::emit_result(experiment, &params, &outcome);
+                }
+                if let Err(e) = checkpoint.record(&params, &outcome) {
+                    eprintln!("could not save the checkpoint: {:#}", e);
+                }
+                if let Some(sentinel) = &sentinel {
+                    sentinel.tick();
+                }
+                (params, outcome)
+            })
+            .progress_with(pb)
+            .collect::<Vec<_>>();
+        let sentinel_runs = sentinel.map(|sentinel| {
+            sentinel.run();
+            sentinel.into_runs()
+        });
+        (results, sentinel_runs)
+    };
+
+    let mut results = Vec::new();
+    let parameters = match time_budget {
+        Some(time_budget) => {
+            let (pilot, parameters) = budget::split_pilot(parameters);
+            let pilot_len = pilot.len();
+            println!(
+                "evaluating {} configurations to estimate the cost of the others...",
+                pilot_len
+            );
+            let before_pilot = Instant::now();
+            results.extend(evaluate_batch(pilot, None).0);
+            let remaining = time_budget.saturating_sub(started.elapsed());
+            let (parameters, beyond_budget) =
+                budget::select(parameters, pilot_len, before_pilot.elapsed(), remaining);
+            budget::print_skipped(&beyond_budget);
+            for params in beyond_budget {
+                if stream_results {
+                    stream::emit_skipped(experiment, &params, budget::BEYOND_BUDGET);
+                }
+                skipped.push((params, budget::BEYOND_BUDGET.to_string()));
+            }
+            parameters
+        }
+        None => parameters,
+    };
+    let (evaluated, sentinel_runs) = evaluate_batch(parameters, evaluation.sentinel_interval);
+    results.extend(evaluated);
+    results.extend(restored);
+
+    for round in 1..=refinement_rounds {
+        sort::sort_results(&mut results, &sort.sort_by, sort.sort_tolerance);
+        let known: Vec<_> = skipped.iter().map(|(params, _)| *params).collect();
+        let (refined, unsupported) =
+            support::partition_supported(refine::refinement(&results, &known));
+        support::print_skipped(&unsupported);
+        if stream_results {
+            for (params, reason) in &unsupported {
+                stream::emit_skipped(experiment, params, reason);
+            }
+        }
+        skipped.extend(unsupported);
+        if refined.is_empty() {
+            println!("nothing left to refine around the best configurations");
+            break;
+        }
+        println!(
+            "refining the best configurations, {} configurations in round {} of {}...",
+            refined.len(),
+            round,
+            refinement_rounds
+        );
+        results.extend(evaluate_batch(refined, None).0);
+    }
+    checkpoint.finish()?;
+
+    let input_file = dataset.input_file().map(|input| {
+        println!("evaluating the input file...");
+        capture_failure(|| {
+            with_retries(evaluation.retries, || {
+                let mut progress = PhaseProgress::bar();
+                evaluate_file(read_method, input, words, lookups, &evaluation, &mut progress)
+            })
+        })
+    });
+
+    let heap = match heap_baseline {
+        Some(kind) => {
+            println!("evaluating the in-memory baseline...");
+            let backend = HeapBackend::build(kind, dataset.heap_entries()?);
+            Some(heap::evaluate(
+                &backend,
+                words,
+                lookups,
+                &evaluation.workload,
+                evaluation.repetitions.get(),
+                dataset.max_bitmap_len()?,
+            ))
+        }
+        None => None,
+    };
+
+    sort::sort_results(&mut results, &sort.sort_by, sort.sort_tolerance);
+    print_sweep_results(&results, dataset.stats());
+    if let Some(input_file) = &input_file {
+        print_input_file_results(input_file, current, &results, &sort.sort_by);
+    }
+    if let Some(heap) = &heap {
+        heap::print_heap_report(heap, &results);
+    }
+    let value_lengths =
+        if prefix_report || model.is_some() { dataset.value_lengths() } else { Vec::new() };
+    if prefix_report {
+        let redundancies: Vec<_> = grid
+            .block_sizes
+            .iter()
+            .map(|&block_size| {
+                let entries = words.iter().zip(value_lengths.iter().copied());
+                PrefixRedundancy::compute(entries, block_size)
+            })
+            .collect();
+        prefix::print_prefix_report(&results, &redundancies);
+    }
+    if let Some(path) = &model {
+        let value_bytes: usize = value_lengths.iter().sum();
+        let mean_value_size = value_bytes as u64 / words.len().max(1) as u64;
+        record_recommendation(path, &results, dataset.entry_count(), mean_value_size)?;
+    }
+
+    if let Some(runs) = &sentinel_runs {
+        print_sentinel_runs(runs);
+    }
+    let comparisons = compare_repetitions(&results, &sort, &evaluation);
+
+    let environment = report::Environment {
+        subcommand,
+        seed,
+        experiment,
+        read_method,
+        sort: &sort,
+        input_file: input_file.as_ref(),
+        current,
+        background_load: evaluation.background_load(),
+        cpu_limit: &evaluation.cpu_limit,
+        skipped: &skipped,
+        heap: heap.as_ref(),
+    };
+    if let Some(path) = html_report {
+        report::write_html_report(
+            &path,
+            &environment,
+            &results,
+            dataset.stats(),
+            comparisons.as_deref(),
+            sentinel_runs.as_deref(),
+        )?;
+        println!("HTML report written to {}", path.display());
+    }
+    if let Some(path) = critcmp_export {
+        critcmp::write_export(&path, &results)?;
+        println!("critcmp export written to {}", path.display());
+    }
+    if let Some(path) = long_results {
+        let run_id = tidy::run_id();
+        tidy::write_long_results(&path, &run_id, experiment, &dataset.name(), &results)?;
+        println!("long results of the run {} written to {}", run_id, path.display());
+    }
+    if let Some(path) = summary_markdown {
+        summary::write_markdown_summary(&path, &environment, &results)?;
+        println!("Markdown summary written to {}", path.display());
+    }
+    let (files, bytes) = retention::remove_files(folder, &results, keep_files)?;
+    if files != 0 {
+        println!("{} files removed from {}, {} bytes freed", files, folder.display(), bytes);
+    }
+    if assertion::check_assertions(&fail_if, &results) {
+        std::process::exit(assertion::FAILED_ASSERTION_EXIT_CODE);
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! The simulation of a remote storage, e.g. `--simulated-latency 500us
+//! --simulated-bandwidth 100MBps`, to study the round trips of the parameters
+//! without provisioning one.
+//!
+//! Every read that doesn't continue the previous one is a round trip paying the
+//! latency, the sequential reads are streamed, and the bytes are transferred at the
+//! bandwidth. The reads are those of grenad, after the buffering of the read method.
+
+use std::io::{self, Read, Seek, SeekFrom};
+use std::thread;
+use std::time::Duration;
+
+/// The latency of the round trips and the bandwidth of the simulated storage.
+#[derive(Debug, Copy, Clone)]
+pub struct Throttle {
+    pub latency: Option<Duration>,
+    /// The bandwidth, in bytes per second.
+    pub bandwidth: Option<u64>,
+}
+
+/// Wraps a reader and delays its reads as the simulated storage would.
+pub struct ThrottledReader<R> {
+    inner: R,
+    throttle: Throttle,
+    position: u64,
+    /// The end of the previous read, the next one streams from there.
+    stream_end: Option<u64>,
+}
+
+impl<R: Seek> ThrottledReader<R> {
+    pub fn new(mut inner: R, throttle: Throttle) -> io::Result<ThrottledReader<R>> {
+        let position = inner.stream_position()?;
+        Ok(ThrottledReader { inner, throttle, position, stream_end: None })
+    }
+}
+
+impl<R: Read> Read for ThrottledReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut delay = Duration::ZERO;
+        if self.stream_end != Some(self.position) {
+            delay += self.throttle.latency.unwrap_or_default();
+        }
+        let read = self.inner.read(buf)?;
+        if let Some(bandwidth) = self.throttle.bandwidth {
+            delay += Duration::from_secs_f64(read as f64 / bandwidth as f64);
+        }
+        if !delay.is_zero() {
+            thread::sleep(delay);
+        }
+        self.position += read as u64;
+        self.stream_end = Some(self.position);
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for ThrottledReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
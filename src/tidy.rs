@@ -0,0 +1,82 @@
+//! The export of the sweep results in a tidy long format, a CSV of one row per
+//! metric of every successful configuration, to be concatenated over the runs and
+//! joined across runs, datasets and machines without parsing the other exports.
+//!
+//! Every run is identified by a random UUID, every evaluation by the UUID of its run
+//! and the id of its configuration, the name of the file without its extension,
+//! which is the same for the same parameters in every run. The runs of the same
+//! experiment share its identifier, the `experiment_id`.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use rand::Rng;
+
+use crate::compressed;
+use crate::experiment::ExperimentId;
+use crate::{Outcome, Parameters};
+
+const HEADER: &str = "run_id,evaluation_id,experiment_id,dataset,configuration_id,compression,\
+                      index_levels,block_size,index_key_interval,metric,value\n";
+
+/// Draws a random UUID, of the version 4.
+pub fn run_id() -> String {
+    let bits: u128 = rand::thread_rng().gen();
+    let bits = bits & !(0xf << 76) | (0x4 << 76);
+    let bits = bits & !(0x3 << 62) | (0x2 << 62);
+    let hex = format!("{:032x}", bits);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Writes the rows of the successful results at `path`, the metrics that were not
+/// measured have no row, the failed and skipped configurations have none either.
+pub fn write_long_results(
+    path: &Path,
+    run_id: &str,
+    experiment: ExperimentId,
+    dataset: &str,
+    results: &[(Parameters, Outcome)],
+) -> anyhow::Result<()> {
+    let mut csv = String::from(HEADER);
+    for (params, outcome) in results {
+        let results = match outcome {
+            Ok(results) => results,
+            Err(_) => continue,
+        };
+        let name = params.file_name();
+        let configuration_id = name.trim_end_matches(".grd");
+        let compression = format!("{:?}", params.compression).to_lowercase();
+        for metric in results.metrics() {
+            let value = metric.to_json();
+            if value == "null" {
+                continue;
+            }
+            writeln!(
+                csv,
+                "{},{}/{},{},{},{},{},{},{},{},{},{}",
+                run_id,
+                run_id,
+                configuration_id,
+                experiment,
+                field(dataset),
+                configuration_id,
+                compression,
+                params.index_levels,
+                params.block_size,
+                params.index_key_interval,
+                metric.name,
+                value,
+            )?;
+        }
+    }
+    compressed::write_export(path, csv)
+}
+
+/// Quotes a CSV field when it contains a separator, a quote or a line break.
+fn field(s: &str) -> String {
+    if s.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
@@ -0,0 +1,87 @@
+//! The split of the random jumps between the cold seeks, that read a block for the
+//! first time in the run, and the warm ones that only read already touched blocks.
+//!
+//! The reader of the cursor is wrapped to record the offsets it reads at, grenad reads
+//! a block from its offset every time it is accessed, a seek is cold when the number of
+//! distinct offsets read so far increased during it. No cache has to be dropped.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::stats;
+
+/// The distinct offsets read by a `TouchTracker`, shared with the workload.
+#[derive(Debug, Clone, Default)]
+pub struct TouchLog(Rc<RefCell<HashSet<u64>>>);
+
+impl TouchLog {
+    pub fn distinct(&self) -> usize {
+        self.0.borrow().len()
+    }
+}
+
+/// Wraps a reader and records the offset of every read into the log.
+pub struct TouchTracker<R> {
+    inner: R,
+    position: u64,
+    log: TouchLog,
+}
+
+impl<R: Seek> TouchTracker<R> {
+    pub fn new(mut inner: R, log: TouchLog) -> io::Result<TouchTracker<R>> {
+        let position = inner.stream_position()?;
+        Ok(TouchTracker { inner, position, log })
+    }
+}
+
+impl<R: Read> Read for TouchTracker<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read != 0 {
+            self.log.0.borrow_mut().insert(self.position);
+        }
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for TouchTracker<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
+
+/// The median latencies of the cold and warm seeks of the jump phase.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct WarmColdSplit {
+    pub cold_seeks: u64,
+    pub warm_seeks: u64,
+    /// The median latency of the seeks that read a block for the first time.
+    pub cold_median: Option<Duration>,
+    pub warm_median: Option<Duration>,
+}
+
+impl WarmColdSplit {
+    pub fn from_latencies(cold: Vec<Duration>, warm: Vec<Duration>) -> WarmColdSplit {
+        WarmColdSplit {
+            cold_seeks: cold.len() as u64,
+            warm_seeks: warm.len() as u64,
+            cold_median: stats::median(cold),
+            warm_median: stats::median(warm),
+        }
+    }
+
+    /// The medians of the repetitions, the seeks are the same in every repetition.
+    pub fn median(splits: &[WarmColdSplit]) -> Option<WarmColdSplit> {
+        let first = splits.first()?;
+        Some(WarmColdSplit {
+            cold_median: stats::median(splits.iter().filter_map(|s| s.cold_median).collect()),
+            warm_median: stats::median(splits.iter().filter_map(|s| s.warm_median).collect()),
+            ..*first
+        })
+    }
+}
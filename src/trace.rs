@@ -0,0 +1,318 @@
+//! The replay of captured access traces, in place of the random jumps, to evaluate
+//! the parameters against the real query-time accesses of an engine.
+//!
+//! A trace is a text file of one operation per line, the empty lines and the lines
+//! starting with `#` are ignored. The keys are escaped, `\\`, `\0`, `\t`, `\n` and
+//! `\xNN` are the only escape sequences and the other bytes are written as is.
+//!
+//! ```text
+//! # seek the first key greater than or equal to, iterate over the next entries
+//! seek hello
+//! iterate 10
+//! # iterate over all the keys starting with the prefix
+//! prefix wor
+//! ```
+//!
+//! The `RecordingCursor` records the operations of an engine while it uses a grenad
+//! cursor, it only depends on grenad and the standard library for it to be copied
+//! into the engine along with the `escape` function.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use grenad::ReaderCursor;
+
+use crate::progress::{PhaseProgress, PROGRESS_STEP};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// Moves on the first key greater than or equal to this one.
+    Seek(Vec<u8>),
+    /// Moves on the next entries, from the current position.
+    Iterate(u64),
+    /// Moves on the first key starting with this prefix and on all the next ones.
+    Prefix(Vec<u8>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    pub operations: Vec<Operation>,
+}
+
+impl Trace {
+    pub fn load(path: &Path) -> anyhow::Result<Trace> {
+        let content =
+            fs::read(path).with_context(|| format!("while reading {}", path.display()))?;
+        let mut operations = Vec::new();
+        for (i, line) in content.split(|&b| b == b'\n').enumerate() {
+            if line.is_empty() || line.starts_with(b"#") {
+                continue;
+            }
+            let operation = parse_operation(line)
+                .with_context(|| format!("at line {} of {}", i + 1, path.display()))?;
+            operations.push(operation);
+        }
+        Ok(Trace { operations })
+    }
+
+    /// Replays the operations on the cursor, returns the time it took.
+    pub fn replay<R: io::Read + io::Seek>(
+        &self,
+        cursor: &mut ReaderCursor<R>,
+        progress: &mut PhaseProgress,
+    ) -> anyhow::Result<Duration> {
+        progress.start_phase("replaying", self.operations.len() as u64);
+        let before_replay = Instant::now();
+        for (n, operation) in (0..).zip(&self.operations) {
+            if n % PROGRESS_STEP == 0 {
+                progress.set_position(n);
+            }
+            match operation {
+                Operation::Seek(key) => {
+                    cursor.move_on_key_greater_than_or_equal_to(key)?;
+                }
+                Operation::Iterate(count) => {
+                    for _ in 0..*count {
+                        if cursor.move_on_next()?.is_none() {
+                            break;
+                        }
+                    }
+                }
+                Operation::Prefix(prefix) => {
+                    let mut entry = cursor.move_on_key_greater_than_or_equal_to(prefix)?;
+                    while let Some((key, _)) = entry {
+                        if !key.starts_with(prefix) {
+                            break;
+                        }
+                        entry = cursor.move_on_next()?;
+                    }
+                }
+            }
+        }
+        Ok(before_replay.elapsed())
+    }
+}
+
+/// Parses the `--trace` argument, the file is loaded before the evaluations start.
+pub fn parse_trace(path: &str) -> Result<Trace, String> {
+    Trace::load(Path::new(path)).map_err(|e| format!("{:#}", e))
+}
+
+fn parse_operation(line: &[u8]) -> anyhow::Result<Operation> {
+    let (name, argument) = match line.iter().position(|&b| b == b' ') {
+        Some(space) => (&line[..space], &line[space + 1..]),
+        None => (line, &[][..]),
+    };
+    match name {
+        b"seek" => Ok(Operation::Seek(unescape(argument)?)),
+        b"prefix" => Ok(Operation::Prefix(unescape(argument)?)),
+        b"iterate" => {
+            let count = std::str::from_utf8(argument)?.trim();
+            Ok(Operation::Iterate(
+                count.parse().with_context(|| format!("invalid count {:?}", count))?,
+            ))
+        }
+        otherwise => {
+            anyhow::bail!(
+                "unknown operation {:?}, expected seek, iterate or prefix",
+                String::from_utf8_lossy(otherwise)
+            )
+        }
+    }
+}
+
+fn escape(bytes: &[u8]) -> String {
+    let mut escaped = String::with_capacity(bytes.len());
+    let escape_byte = |escaped: &mut String, b: u8| {
+        let _ = write!(escaped, "\\x{:02x}", b);
+    };
+    match std::str::from_utf8(bytes) {
+        Ok(text) => {
+            for c in text.chars() {
+                match c {
+                    '\\' => escaped.push_str("\\\\"),
+                    '\0' => escaped.push_str("\\0"),
+                    '\t' => escaped.push_str("\\t"),
+                    '\n' => escaped.push_str("\\n"),
+                    c if c.is_control() => {
+                        c.to_string().bytes().for_each(|b| escape_byte(&mut escaped, b))
+                    }
+                    c => escaped.push(c),
+                }
+            }
+        }
+        // The keys that are not UTF-8 are escaped byte by byte.
+        Err(_) => {
+            for &b in bytes {
+                match b {
+                    b'\\' => escaped.push_str("\\\\"),
+                    0x20..=0x7e => escaped.push(b as char),
+                    b => escape_byte(&mut escaped, b),
+                }
+            }
+        }
+    }
+    escaped
+}
+
+fn unescape(bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.iter().copied();
+    while let Some(b) = bytes.next() {
+        if b != b'\\' {
+            unescaped.push(b);
+            continue;
+        }
+        match bytes.next() {
+            Some(b'\\') => unescaped.push(b'\\'),
+            Some(b'0') => unescaped.push(0),
+            Some(b't') => unescaped.push(b'\t'),
+            Some(b'n') => unescaped.push(b'\n'),
+            Some(b'x') => {
+                let digits = [bytes.next(), bytes.next()];
+                let byte = match digits {
+                    [Some(high), Some(low)] => std::str::from_utf8(&[high, low])
+                        .ok()
+                        .and_then(|digits| u8::from_str_radix(digits, 16).ok()),
+                    _ => None,
+                };
+                unescaped.push(byte.context("`\\x` must be followed by two hexadecimal digits")?);
+            }
+            otherwise => anyhow::bail!(
+                "invalid escape sequence `\\{}`",
+                otherwise.map_or_else(String::new, |b| (b as char).to_string())
+            ),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// Wraps the cursor of an engine and records the operations it executes.
+///
+/// The consecutive moves on the next entries are recorded as a single iteration,
+/// the prefix iterations must be recorded explicitly with `record_prefix`.
+// It is used by the engines that copy this module and the tests, not by this binary.
+#[cfg_attr(not(test), allow(dead_code))]
+pub struct RecordingCursor<R> {
+    cursor: ReaderCursor<R>,
+    trace: Trace,
+    pending_nexts: u64,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+impl<R: io::Read + io::Seek> RecordingCursor<R> {
+    pub fn new(cursor: ReaderCursor<R>) -> RecordingCursor<R> {
+        RecordingCursor { cursor, trace: Trace::default(), pending_nexts: 0 }
+    }
+
+    pub fn move_on_key_greater_than_or_equal_to<A: AsRef<[u8]>>(
+        &mut self,
+        key: A,
+    ) -> Result<Option<(&[u8], &[u8])>, grenad::Error> {
+        self.push_pending_nexts();
+        self.trace.operations.push(Operation::Seek(key.as_ref().to_vec()));
+        self.cursor.move_on_key_greater_than_or_equal_to(key)
+    }
+
+    pub fn move_on_next(&mut self) -> Result<Option<(&[u8], &[u8])>, grenad::Error> {
+        self.pending_nexts += 1;
+        self.cursor.move_on_next()
+    }
+
+    /// Records an iteration over the keys starting with the prefix, the engine
+    /// executes it with the cursor returned by `inner`.
+    pub fn record_prefix(&mut self, prefix: &[u8]) {
+        self.push_pending_nexts();
+        self.trace.operations.push(Operation::Prefix(prefix.to_vec()));
+    }
+
+    /// The wrapped cursor, the operations executed on it are not recorded.
+    pub fn inner(&mut self) -> &mut ReaderCursor<R> {
+        &mut self.cursor
+    }
+
+    /// Writes the recorded operations in the format of the traces.
+    pub fn write_trace_into<W: Write>(mut self, mut writer: W) -> io::Result<()> {
+        self.push_pending_nexts();
+        for operation in &self.trace.operations {
+            let line = match operation {
+                Operation::Seek(key) => format!("seek {}\n", escape(key)),
+                Operation::Iterate(count) => format!("iterate {}\n", count),
+                Operation::Prefix(prefix) => format!("prefix {}\n", escape(prefix)),
+            };
+            writer.write_all(line.as_bytes())?;
+        }
+        writer.flush()
+    }
+
+    fn push_pending_nexts(&mut self) {
+        if self.pending_nexts != 0 {
+            self.trace.operations.push(Operation::Iterate(self.pending_nexts));
+            self.pending_nexts = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use grenad::{Reader, WriterBuilder};
+
+    use super::*;
+
+    #[test]
+    fn recorded_operations_parse_back() {
+        let mut writer = WriterBuilder::new().build(Vec::new());
+        for key in [&b"hello"[..], b"hello world", b"help", b"world"] {
+            writer.insert(key, b"").unwrap();
+        }
+        let bytes = writer.into_inner().unwrap();
+        let cursor = Reader::new(Cursor::new(bytes)).unwrap().into_cursor().unwrap();
+
+        let mut recording = RecordingCursor::new(cursor);
+        let seeks = [&b"hello world"[..], b"line\nbreak\ttab\\", b"\xff\x00 \xfe"];
+        for key in seeks {
+            recording.move_on_key_greater_than_or_equal_to(key).unwrap();
+        }
+        for _ in 0..3 {
+            recording.move_on_next().unwrap();
+        }
+        recording.record_prefix(b"hel");
+        let entry = recording.inner().move_on_key_greater_than_or_equal_to(b"hel").unwrap();
+        assert_eq!(entry.map(|(key, _)| key.to_vec()), Some(b"hello".to_vec()));
+        recording.move_on_next().unwrap();
+
+        let mut written = Vec::new();
+        recording.write_trace_into(&mut written).unwrap();
+        let operations: Vec<_> = written
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| parse_operation(line).unwrap())
+            .collect();
+
+        let mut expected: Vec<_> = seeks.iter().map(|key| Operation::Seek(key.to_vec())).collect();
+        expected.extend([
+            Operation::Iterate(3),
+            Operation::Prefix(b"hel".to_vec()),
+            Operation::Iterate(1),
+        ]);
+        assert_eq!(operations, expected);
+    }
+
+    #[test]
+    fn escaped_keys_unescape() {
+        let keys = [&b"with space"[..], b"new\nline", b"back\\slash", b"\x00\x01\x7f", b"\xff\xfe"];
+        for key in keys {
+            let escaped = escape(key);
+            assert!(!escaped.contains('\n'), "{:?}", escaped);
+            assert_eq!(unescape(escaped.as_bytes()).unwrap(), key);
+        }
+        assert!(unescape(b"\\q").is_err());
+        assert!(unescape(b"\\x4").is_err());
+    }
+}
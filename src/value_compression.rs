@@ -0,0 +1,66 @@
+//! The compression of the values stored on their own, by the fst value log and by LMDB,
+//! every value is compressed alone with the compression of the grenad blocks, to compare
+//! the backends at equal compression settings.
+//!
+//! The lz4 values are prefixed by their decompressed length, the snappy and zstd
+//! formats record it.
+
+use grenad::CompressionType;
+
+pub const POSSIBLE_COMPRESSIONS: &[&str] = &["none", "snappy", "lz4", "zstd"];
+
+pub fn compress(
+    compression: CompressionType,
+    value: &[u8],
+    output: &mut Vec<u8>,
+) -> anyhow::Result<()> {
+    output.clear();
+    match compression {
+        CompressionType::None => output.extend_from_slice(value),
+        CompressionType::Snappy => {
+            output.resize(snap::raw::max_compress_len(value.len()), 0);
+            let len = snap::raw::Encoder::new().compress(value, output)?;
+            output.truncate(len);
+        }
+        CompressionType::Lz4 => output.extend_from_slice(&lz4_flex::compress_prepend_size(value)),
+        CompressionType::Zstd => *output = zstd::stream::encode_all(value, 0)?,
+        otherwise => anyhow::bail!(
+            "the {:?} compression of the values is not supported, only none, snappy, lz4 and zstd",
+            otherwise
+        ),
+    }
+    Ok(())
+}
+
+pub fn decompress(
+    compression: CompressionType,
+    bytes: &[u8],
+    output: &mut Vec<u8>,
+) -> anyhow::Result<()> {
+    output.clear();
+    match compression {
+        CompressionType::Snappy => {
+            output.resize(snap::raw::decompress_len(bytes)?, 0);
+            snap::raw::Decoder::new().decompress(bytes, output)?;
+        }
+        CompressionType::Lz4 => *output = lz4_flex::decompress_size_prepended(bytes)?,
+        CompressionType::Zstd => *output = zstd::stream::decode_all(bytes)?,
+        _ => output.extend_from_slice(bytes),
+    }
+    Ok(())
+}
+
+/// The decompressed value, the bytes themselves without compression, they are not copied.
+pub fn decompressed<'a>(
+    compression: CompressionType,
+    bytes: &'a [u8],
+    output: &'a mut Vec<u8>,
+) -> anyhow::Result<&'a [u8]> {
+    match compression {
+        CompressionType::None => Ok(bytes),
+        compression => {
+            decompress(compression, bytes, output)?;
+            Ok(output)
+        }
+    }
+}
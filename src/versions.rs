@@ -0,0 +1,146 @@
+//! The evaluation of the same parameters with several versions of grenad, to
+//! quantify the regressions and the improvements of grenad itself.
+//!
+//! The current version is always evaluated, the other ones are compiled in with
+//! the `grenad_0_4` and `grenad_main` features. The versions that don't support
+//! the index parameters ignore them, it is reported with the results.
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::datagen::ValueGenerator;
+use crate::Parameters;
+
+/// The timings of one grenad version, measured on the same dataset and lookups.
+pub struct VersionResults {
+    pub write_time: Duration,
+    pub file_size: u64,
+    pub iter_time: Duration,
+    pub jump_time: Duration,
+    pub index_parameters: bool,
+}
+
+type Evaluate =
+    fn(&Path, ValueGenerator, &[Vec<u8>], &[usize], &Parameters) -> anyhow::Result<VersionResults>;
+
+/// Generates the same file with every compiled in version and evaluates it.
+pub fn evaluate_versions(
+    folder: &Path,
+    values: ValueGenerator,
+    words: &[Vec<u8>],
+    lookups: &[usize],
+    params: &Parameters,
+) -> Vec<(&'static str, anyhow::Result<VersionResults>)> {
+    let versions: &[(&str, Evaluate)] = &[
+        (current::NAME, current::evaluate),
+        #[cfg(feature = "grenad_0_4")]
+        (v0_4::NAME, v0_4::evaluate),
+        #[cfg(feature = "grenad_main")]
+        (main_branch::NAME, main_branch::evaluate),
+    ];
+    versions
+        .iter()
+        .map(|(name, evaluate)| (*name, evaluate(folder, values, words, lookups, params)))
+        .collect()
+}
+
+macro_rules! grenad_version {
+    (@index $builder:ident, $params:ident, index_parameters) => {
+        $builder
+            .index_levels($params.index_levels)
+            .index_key_interval($params.index_key_interval);
+    };
+    (@index $builder:ident, $params:ident,) => {};
+    (@supported index_parameters) => {
+        true
+    };
+    (@supported) => {
+        false
+    };
+    ($module:ident, $krate:ident, $name:expr $(, $index:ident)?) => {
+        mod $module {
+            use std::fs::{self, File};
+            use std::io::BufWriter;
+            use std::path::Path;
+            use std::time::Instant;
+
+            use anyhow::Context;
+            use $krate::{CompressionType, Reader, WriterBuilder};
+
+            use super::VersionResults;
+            use crate::datagen::ValueGenerator;
+            use crate::Parameters;
+
+            pub const NAME: &str = $name;
+
+            pub fn evaluate(
+                folder: &Path,
+                values: ValueGenerator,
+                words: &[Vec<u8>],
+                lookups: &[usize],
+                params: &Parameters,
+            ) -> anyhow::Result<VersionResults> {
+                let path = folder.join(format!("{}.{}", NAME, params.file_name()));
+
+                let before_write = Instant::now();
+                let file = File::create(&path)
+                    .with_context(|| format!("while creating {}", path.display()))?;
+                let mut builder = WriterBuilder::new();
+                builder.compression_type(compression(params.compression));
+                builder.block_size(params.block_size);
+                grenad_version!(@index builder, params, $($index)?);
+                let mut writer = builder.build(BufWriter::new(file));
+                let mut buffer = Vec::new();
+                for (i, word) in words.iter().enumerate() {
+                    values.generate(i as u64, &mut buffer);
+                    writer.insert(word, &buffer)?;
+                }
+                writer.into_inner()?.into_inner()?;
+                let write_time = before_write.elapsed();
+                let file_size = fs::metadata(&path)?.len();
+
+                let file = File::open(&path)?;
+                let mut cursor = Reader::new(file)?.into_cursor()?;
+
+                let before_iter = Instant::now();
+                let mut count = 0;
+                while let Some((key, _value)) = cursor.move_on_next()? {
+                    assert_eq!(key, &words[count][..]);
+                    count += 1;
+                }
+                assert_eq!(count, words.len());
+                let iter_time = before_iter.elapsed();
+
+                let before_jump = Instant::now();
+                for &index in lookups {
+                    let word = &words[index][..];
+                    match cursor.move_on_key_greater_than_or_equal_to(word)? {
+                        Some((key, _value)) => assert_eq!(key, word),
+                        None => panic!("{:?} not found", String::from_utf8_lossy(word)),
+                    }
+                }
+                let jump_time = before_jump.elapsed();
+
+                let index_parameters = grenad_version!(@supported $($index)?);
+                Ok(VersionResults { write_time, file_size, iter_time, jump_time, index_parameters })
+            }
+
+            fn compression(compression: grenad::CompressionType) -> CompressionType {
+                match compression {
+                    grenad::CompressionType::None => CompressionType::None,
+                    grenad::CompressionType::Zlib => CompressionType::Zlib,
+                    grenad::CompressionType::Snappy => CompressionType::Snappy,
+                    grenad::CompressionType::Zstd => CompressionType::Zstd,
+                    grenad::CompressionType::Lz4 => CompressionType::Lz4,
+                    grenad::CompressionType::Lz4hc => CompressionType::Lz4hc,
+                }
+            }
+        }
+    };
+}
+
+grenad_version!(current, grenad, "current", index_parameters);
+#[cfg(feature = "grenad_0_4")]
+grenad_version!(v0_4, grenad_0_4, "0.4");
+#[cfg(feature = "grenad_main")]
+grenad_version!(main_branch, grenad_main, "main");